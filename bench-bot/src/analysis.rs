@@ -0,0 +1,105 @@
+use crate::report::Report;
+
+/// Req/Sec per megabyte of peak memory.
+pub fn requests_per_mb(report: &Report) -> Option<f64> {
+    Some(report.metrics().req_per_sec() / report.max_memory())
+        .filter(|rate| rate.is_finite() && *rate > 0.0)
+}
+
+/// Req/Sec of `report` in percent of the fastest of `reports`, incomplete
+/// and pathological ones aside as they don't compete.
+pub fn percent_of_best(report: &Report, reports: &[Report]) -> Option<f64> {
+    let best = reports
+        .iter()
+        .filter(|r| r.is_reliable())
+        .map(|r| r.metrics().req_per_sec())
+        .fold(0.0, f64::max);
+
+    Some(report.metrics().req_per_sec() / best * 100.0).filter(|percent| percent.is_finite())
+}
+
+/// Microseconds of server CPU time per completed request, over every round.
+pub fn cpu_per_request(report: &Report) -> Option<f64> {
+    per_request(report, report.cpu_time()?).map(|seconds| seconds * 1e6)
+}
+
+/// Joules per million completed requests, over every round.
+pub fn energy_per_million_requests(report: &Report) -> Option<f64> {
+    per_request(report, report.energy()?).map(|joules| joules * 1e6)
+}
+
+fn per_request(report: &Report, total: f64) -> Option<f64> {
+    let requests = report.requests_of_all_rounds();
+
+    Some(total / requests).filter(|_| requests > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = r#"
+Running 30s test @ http://127.0.0.1:3000
+  16 threads and 500 connections
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+    Req/Sec    36.10k     2.64k   74.83k    75.41%
+  17275966 requests in 30.09s, 1.95GB read
+Requests/sec: 574184.09
+Transfer/sec:     66.26MB
+"#;
+
+    fn report(max_memory: f64) -> Report {
+        Report::new(
+            "actix-web",
+            max_memory,
+            OUTPUT.parse().expect("parse metric fail"),
+        )
+    }
+
+    #[test]
+    fn derived() {
+        let given = report(13.7).with_cpu_time(34.55).with_energy(1727.6);
+
+        assert_eq!(requests_per_mb(&given).map(f64::round), Some(41911.0));
+        assert_eq!(
+            cpu_per_request(&given).map(|us| (us * 100.0).round() / 100.0),
+            Some(2.0)
+        );
+        assert_eq!(
+            energy_per_million_requests(&given).map(f64::round),
+            Some(100.0)
+        );
+    }
+
+    #[test]
+    fn relative() {
+        let given = vec![
+            report(13.7),
+            Report::new(
+                "axum",
+                12.4,
+                OUTPUT
+                    .replace("574184.09", "470830.95")
+                    .parse()
+                    .expect("parse metric fail"),
+            ),
+        ];
+
+        assert_eq!(percent_of_best(&given[0], &given), Some(100.0));
+        assert_eq!(
+            percent_of_best(&given[1], &given).map(f64::round),
+            Some(82.0)
+        );
+        assert_eq!(percent_of_best(&given[0], &[]), None);
+    }
+
+    #[test]
+    fn not_measured() {
+        let given = report(0.0);
+
+        assert_eq!(requests_per_mb(&given), None);
+        assert_eq!(cpu_per_request(&given), None);
+        assert_eq!(energy_per_million_requests(&given), None);
+    }
+}
@@ -0,0 +1,211 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::config::Artifacts;
+use crate::status::LOG_FILE;
+
+/// Shareable link to an uploaded file.
+#[derive(Debug, PartialEq)]
+pub struct Link {
+    pub name: String,
+    pub url: String,
+}
+
+/// Upload the files of the run of `scenarios` in `output_dir` below `prefix`
+/// of the configured bucket with the aws CLI and presign every report, page,
+/// chart and log. Files of earlier runs sharing the directory stay behind.
+pub fn upload(
+    output_dir: &Path,
+    scenarios: &[String],
+    artifacts: &Artifacts,
+    prefix: &str,
+) -> Result<Vec<Link>, String> {
+    let destination = format!("{}/{}", artifacts.bucket.trim_end_matches('/'), prefix);
+    let source = output_dir.to_string_lossy();
+    let names = run_files(output_dir, scenarios);
+
+    let mut args = vec![
+        "s3",
+        "cp",
+        "--recursive",
+        &source,
+        &destination,
+        "--exclude",
+        "*",
+    ];
+    for name in &names {
+        args.extend(["--include", name.as_str()]);
+    }
+    aws(artifacts, &args)?;
+
+    let names: Vec<String> = names
+        .into_iter()
+        .filter(|name| {
+            [".md", ".html", ".svg", ".log"]
+                .iter()
+                .any(|extension| name.ends_with(extension))
+        })
+        .collect();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let expires_in = artifacts.expires_in.to_string();
+            let url = aws(
+                artifacts,
+                &[
+                    "s3",
+                    "presign",
+                    &format!("{}/{}", destination, name),
+                    "--expires-in",
+                    &expires_in,
+                ],
+            )?;
+
+            Ok(Link { name, url })
+        })
+        .collect()
+}
+
+/// Markdown list of `links`.
+pub fn links_markdown(links: &[Link]) -> String {
+    links
+        .iter()
+        .map(|link| format!("- [{}]({})", link.name, link.url))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Files of `output_dir` the run of `scenarios` wrote, sorted: the run log,
+/// the pages and results of each scenario and its directory of details.
+fn run_files(output_dir: &Path, scenarios: &[String]) -> Vec<String> {
+    let mut names = vec![LOG_FILE.to_string()];
+    for scenario in scenarios {
+        names.extend(
+            ["md", "html", "json", "csv"]
+                .iter()
+                .map(|extension| format!("{}.{}", scenario, extension)),
+        );
+        names.extend(walk(output_dir, &output_dir.join(scenario)));
+    }
+    names.retain(|name| output_dir.join(name).is_file());
+    names.sort();
+
+    names
+}
+
+/// Files below `dir` by their path relative to `root`, with `/` separators
+/// as in object keys.
+pub fn walk(root: &Path, dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut names = Vec::new();
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            names.extend(walk(root, &path));
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let parts: Vec<_> = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect();
+            names.push(parts.join("/"));
+        }
+    }
+
+    names
+}
+
+fn aws(artifacts: &Artifacts, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new("aws");
+    command.args(args);
+    if let Some(endpoint) = &artifacts.endpoint {
+        command.args(["--endpoint-url", endpoint]);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| format!("running aws: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "aws {}: {}",
+            args[..2].join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, fs};
+
+    #[test]
+    fn relative_names() {
+        let dir = env::temp_dir().join("bench-bot-test-artifacts");
+        fs::create_dir_all(dir.join("hello-world")).unwrap();
+        fs::write(dir.join("hello-world.md"), "").unwrap();
+        fs::write(dir.join("hello-world/axum.md"), "").unwrap();
+
+        let mut actual = walk(&dir, &dir);
+        actual.sort();
+
+        assert_eq!(
+            actual,
+            vec![
+                "hello-world.md".to_string(),
+                "hello-world/axum.md".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn files_of_the_run() {
+        let dir = env::temp_dir().join("bench-bot-test-run-files");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("hello-world")).unwrap();
+        fs::create_dir_all(dir.join("json")).unwrap();
+        for name in [
+            LOG_FILE,
+            "history.jsonl",
+            "hello-world.md",
+            "hello-world.html",
+            "hello-world/axum.log",
+            "json.md",
+            "json/axum.log",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+
+        assert_eq!(
+            run_files(&dir, &["hello-world".to_string()]),
+            [
+                LOG_FILE,
+                "hello-world.html",
+                "hello-world.md",
+                "hello-world/axum.log"
+            ]
+        );
+    }
+
+    #[test]
+    fn links() {
+        let given = vec![Link {
+            name: "hello-world.md".to_string(),
+            url: "https://bench-results.s3.amazonaws.com/runs/1700000000/\
+                hello-world.md?X-Amz-Expires=604800"
+                .to_string(),
+        }];
+
+        assert_eq!(
+            links_markdown(&given),
+            "- [hello-world.md](https://bench-results.s3.amazonaws.com/runs/1700000000/\
+                hello-world.md?X-Amz-Expires=604800)"
+        );
+    }
+}
@@ -0,0 +1,226 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+/// Crates replacing the system allocator, by dependency name.
+const ALLOCATORS: [(&str, &str); 4] = [
+    ("mimalloc", "mimalloc"),
+    ("jemallocator", "jemalloc"),
+    ("tikv-jemallocator", "jemalloc"),
+    ("snmalloc-rs", "snmalloc"),
+];
+
+/// What gives away precomputed or cached responses in source.
+const CACHING_PATTERNS: [&str; 5] = [
+    "Bytes::from_static",
+    "lazy_static!",
+    "Lazy<",
+    "OnceCell",
+    "OnceLock",
+];
+
+/// Settings of an implementation that may make it faster than its framework
+/// alone, detected heuristically.
+#[derive(Debug, PartialEq)]
+pub struct Audit {
+    pub framework_name: String,
+    /// Global allocator, `system` unless another one is used.
+    pub allocator: String,
+    /// Worker configuration found in source, `None` keeps the framework's.
+    pub workers: Option<String>,
+    /// Patterns hinting at responses computed once.
+    pub caching: Vec<&'static str>,
+}
+
+/// Audit of every member of the workspace, with its release profile.
+pub fn audit(workspace_dir: &Path, members: &[PathBuf]) -> (String, Vec<Audit>) {
+    let profile = fs::read_to_string(workspace_dir.join("Cargo.toml"))
+        .map(|content| release_profile(&content))
+        .unwrap_or_default();
+
+    let mut audits: Vec<Audit> = members
+        .iter()
+        .map(|member| {
+            let dir = workspace_dir.join(member);
+            let cargo_toml = fs::read_to_string(dir.join("Cargo.toml")).unwrap_or_default();
+            let mut sources = String::new();
+            read_sources(&dir.join("src"), &mut sources);
+
+            let framework_name = member
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            audit_member(&framework_name, &cargo_toml, &sources)
+        })
+        .collect();
+    audits.sort_by(|a, b| a.framework_name.cmp(&b.framework_name));
+
+    (profile, audits)
+}
+
+fn read_sources(dir: &Path, sources: &mut String) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+        if path.is_dir() {
+            read_sources(&path, sources);
+        } else if path.extension().is_some_and(|extension| extension == "rs") {
+            sources.push_str(&fs::read_to_string(&path).unwrap_or_default());
+        }
+    }
+}
+
+/// Audit of one implementation from its `Cargo.toml` and all of its source.
+pub fn audit_member(framework_name: &str, cargo_toml: &str, sources: &str) -> Audit {
+    let dependencies: Vec<String> = toml::from_str::<toml::Value>(cargo_toml)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("dependencies")?
+                .as_table()
+                .map(|table| table.keys().cloned().collect())
+        })
+        .unwrap_or_default();
+
+    let global_allocator =
+        Regex::new(r"#\[global_allocator\]\s*static\s+\w+\s*:\s*([\w:]+)").unwrap();
+    let allocator = ALLOCATORS
+        .iter()
+        .find(|(dependency, _)| dependencies.iter().any(|d| d == dependency))
+        .map(|(_, name)| name.to_string())
+        .or_else(|| {
+            global_allocator
+                .captures(sources)
+                .map(|cap| cap[1].to_string())
+        })
+        .unwrap_or_else(|| "system".to_string());
+
+    let workers = Regex::new(concat!(
+        r#"(?:worker_threads|\.workers)\((?:[^()]|\([^()]*\))*\)"#,
+        r#"|worker_threads\s*=\s*\w+|flavor\s*=\s*"current_thread""#
+    ))
+    .unwrap()
+    .find(sources)
+    .map(|m| m.as_str().to_string());
+
+    Audit {
+        framework_name: framework_name.to_string(),
+        allocator,
+        workers,
+        caching: CACHING_PATTERNS
+            .iter()
+            .copied()
+            .filter(|pattern| sources.contains(pattern))
+            .collect(),
+    }
+}
+
+/// `lto` and `codegen-units` of the workspace's release profile, which
+/// applies to every member alike.
+pub fn release_profile(workspace_cargo_toml: &str) -> String {
+    let value = toml::from_str::<toml::Value>(workspace_cargo_toml).ok();
+    let release = value
+        .as_ref()
+        .and_then(|value| value.get("profile")?.get("release"));
+    let setting = |key: &str| {
+        release
+            .and_then(|release| release.get(key))
+            .map_or("default".to_string(), |value| value.to_string())
+    };
+
+    format!(
+        "lto = {}, codegen-units = {}",
+        setting("lto"),
+        setting("codegen-units")
+    )
+}
+
+/// Section listing what was found, so readers can judge the comparison.
+pub fn to_markdown(profile: &str, audits: &[Audit]) -> String {
+    let mut res = format!(
+        "Detected from each implementation's `Cargo.toml` and source, heuristics may miss \
+            settings. Every implementation is built with the release profile of the workspace: \
+            {}.\n\n| Framework Name | Allocator | Workers | Response Caching |\n|---|---|---|---|",
+        profile
+    );
+
+    for audit in audits {
+        let workers = audit
+            .workers
+            .as_ref()
+            .map_or("default".to_string(), |workers| format!("`{}`", workers));
+        let caching = if audit.caching.is_empty() {
+            "-".to_string()
+        } else {
+            audit
+                .caching
+                .iter()
+                .map(|pattern| format!("`{}`", pattern))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        res.push_str(&format!(
+            "\n|{}|{}|{}|{}|",
+            audit.framework_name, audit.allocator, workers, caching
+        ));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn member() {
+        let cargo_toml = r#"
+            [package]
+            name = "hello-world-actix-web"
+
+            [dependencies]
+            actix-web = "4"
+            mimalloc = { version = "0.1", default-features = false }
+        "#;
+        let sources = r#"
+            static BODY: Lazy<Bytes> = Lazy::new(|| Bytes::from_static(b"Hello, World!"));
+
+            fn main() {
+                HttpServer::new(app).workers(num_cpus::get()).bind("0.0.0.0:3000")
+            }
+        "#;
+
+        let actual = audit_member("actix-web", cargo_toml, sources);
+
+        assert_eq!(
+            actual,
+            Audit {
+                framework_name: "actix-web".to_string(),
+                allocator: "mimalloc".to_string(),
+                workers: Some(".workers(num_cpus::get())".to_string()),
+                caching: vec!["Bytes::from_static", "Lazy<"],
+            }
+        );
+    }
+
+    #[test]
+    fn defaults() {
+        let actual = audit_member(
+            "axum",
+            "[dependencies]\naxum = \"0.5\"",
+            "#[tokio::main]\nasync fn main() {}",
+        );
+
+        assert_eq!(actual.allocator, "system");
+        assert_eq!(actual.workers, None);
+        assert!(actual.caching.is_empty());
+        assert_eq!(
+            release_profile("[workspace]\n\n[profile.release]\nlto = \"fat\""),
+            "lto = \"fat\", codegen-units = default"
+        );
+    }
+}
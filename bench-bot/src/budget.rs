@@ -0,0 +1,232 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::timings::PhaseTimings;
+use crate::units;
+
+/// Seconds assumed for building one member without timings of an earlier
+/// run, a release build from scratch.
+const DEFAULT_BUILD: f64 = 60.0;
+
+/// Seconds assumed for starting and stopping one server and parsing its
+/// results, the harness waits a second for servers to come up.
+const DEFAULT_OVERHEAD: f64 = 2.0;
+
+/// Work a run is going to do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Plan {
+    pub builds: usize,
+    pub benchmarks: usize,
+    pub rounds: usize,
+    /// Seconds of each round.
+    pub duration: f64,
+    /// Seconds between benchmarks.
+    pub cooldown: f64,
+}
+
+/// Seconds spent per framework besides measuring.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overheads {
+    pub build: f64,
+    pub benchmark: f64,
+}
+
+impl Default for Overheads {
+    fn default() -> Self {
+        Self {
+            build: DEFAULT_BUILD,
+            benchmark: DEFAULT_OVERHEAD,
+        }
+    }
+}
+
+impl Overheads {
+    /// Mean overheads of the frameworks of an earlier run, the defaults
+    /// without any.
+    pub fn from_timings(timings: &BTreeMap<String, PhaseTimings>) -> Self {
+        if timings.is_empty() {
+            return Self::default();
+        }
+
+        // builds serving several scenarios count with one of them only
+        let builds: Vec<f64> = timings
+            .values()
+            .map(|timing| timing.build)
+            .filter(|build| *build > 0.0)
+            .collect();
+        let count = timings.len() as f64;
+        Self {
+            build: if builds.is_empty() {
+                DEFAULT_BUILD
+            } else {
+                builds.iter().sum::<f64>() / builds.len() as f64
+            },
+            benchmark: timings
+                .values()
+                .map(|timing| timing.provision + timing.teardown + timing.parse)
+                .sum::<f64>()
+                / count,
+        }
+    }
+}
+
+impl Plan {
+    /// Estimated wall-clock seconds of the run.
+    pub fn estimate(&self, overheads: &Overheads) -> f64 {
+        let benchmark = overheads.benchmark + self.rounds.max(1) as f64 * self.duration;
+
+        self.builds as f64 * overheads.build
+            + self.benchmarks as f64 * benchmark
+            + self.benchmarks.saturating_sub(1) as f64 * self.cooldown
+    }
+
+    /// Most rounds up to the planned ones the run fits `budget` seconds
+    /// with, `None` when not even one round does.
+    pub fn fit_rounds(&self, overheads: &Overheads, budget: f64) -> Option<usize> {
+        (1..=self.rounds.max(1)).rev().find(|&rounds| {
+            Plan {
+                rounds,
+                ..self.clone()
+            }
+            .estimate(overheads)
+                <= budget
+        })
+    }
+}
+
+/// Budget given as seconds or with a unit, e.g. `5400`, `90m` or `1.5h`.
+pub fn parse(input: &str) -> Result<Duration, String> {
+    if let Ok(seconds) = input.trim().parse::<u64>() {
+        return Ok(Duration::from_secs(seconds));
+    }
+
+    units::try_parse_duration(input).map_err(|e| {
+        format!(
+            "invalid duration {:?} ({}), expected seconds or a unit like 90m or 2h",
+            input, e
+        )
+    })
+}
+
+/// Seconds as hours, minutes and seconds, e.g. `1h 05m 30s`.
+pub fn human(seconds: f64) -> String {
+    let seconds = seconds.round() as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan() -> Plan {
+        Plan {
+            builds: 10,
+            benchmarks: 12,
+            rounds: 3,
+            duration: 30.0,
+            cooldown: 5.0,
+        }
+    }
+
+    #[test]
+    fn estimate() {
+        let overheads = Overheads {
+            build: 20.0,
+            benchmark: 2.0,
+        };
+
+        assert_eq!(
+            plan().estimate(&overheads),
+            10.0 * 20.0 + 12.0 * (2.0 + 90.0) + 11.0 * 5.0
+        );
+        assert_eq!(plan().fit_rounds(&overheads, 1500.0), Some(3));
+        assert_eq!(plan().fit_rounds(&overheads, 1000.0), Some(2));
+        assert_eq!(plan().fit_rounds(&overheads, 500.0), None);
+    }
+
+    #[test]
+    fn overheads() {
+        let timings = BTreeMap::from([
+            (
+                "axum".to_string(),
+                PhaseTimings {
+                    build: 30.0,
+                    provision: 1.0,
+                    measure: 30.0,
+                    teardown: 0.5,
+                    parse: 0.5,
+                },
+            ),
+            (
+                "tide".to_string(),
+                PhaseTimings {
+                    build: 50.0,
+                    provision: 1.0,
+                    measure: 30.0,
+                    teardown: 0.5,
+                    parse: 0.5,
+                },
+            ),
+        ]);
+
+        assert_eq!(
+            Overheads::from_timings(&timings),
+            Overheads {
+                build: 40.0,
+                benchmark: 2.0
+            }
+        );
+
+        let built_elsewhere = BTreeMap::from([
+            (
+                "axum".to_string(),
+                PhaseTimings {
+                    build: 30.0,
+                    provision: 1.0,
+                    measure: 30.0,
+                    teardown: 0.5,
+                    parse: 0.5,
+                },
+            ),
+            (
+                "tide".to_string(),
+                PhaseTimings {
+                    build: 0.0,
+                    provision: 1.0,
+                    measure: 30.0,
+                    teardown: 0.5,
+                    parse: 0.5,
+                },
+            ),
+        ]);
+        assert_eq!(
+            Overheads::from_timings(&built_elsewhere),
+            Overheads {
+                build: 30.0,
+                benchmark: 2.0
+            }
+        );
+        assert_eq!(
+            Overheads::from_timings(&BTreeMap::new()),
+            Overheads::default()
+        );
+    }
+
+    #[test]
+    fn budgets() {
+        assert_eq!(parse("5400"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse("1.5h"), Ok(Duration::from_secs(5400)));
+        assert!(parse("soon").is_err());
+        assert!(parse("-5m").is_err());
+        assert_eq!(human(3930.0), "1h 05m 30s");
+        assert_eq!(human(42.0), "42s");
+    }
+}
@@ -0,0 +1,104 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::format::NumberFormat;
+
+/// How many connections a framework serves before requests start failing,
+/// found by doubling them from the fewest probed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct Capacity {
+    /// Most connections probed without a failed request, `None` when already
+    /// the fewest failed.
+    pub max_error_free: Option<u32>,
+    /// Fewest connections probed with failed requests, `None` when even the
+    /// most probed had none.
+    pub breaks_at: Option<u32>,
+}
+
+impl Capacity {
+    /// Cell of the comparison table, a lower or upper bound when the probe
+    /// didn't find where errors start.
+    pub fn cell(&self, format: &NumberFormat) -> String {
+        match (self.max_error_free, self.breaks_at) {
+            (Some(max), Some(_)) => format.count(u64::from(max)),
+            (Some(max), None) => format!("≥ {}", format.count(u64::from(max))),
+            (None, Some(breaks_at)) => format!("< {}", format.count(u64::from(breaks_at))),
+            (None, None) => "-".to_string(),
+        }
+    }
+}
+
+/// Connections of each step, doubling from `start` up to `limit`, `limit`
+/// itself being the last.
+pub fn steps(start: u32, limit: u32) -> Vec<u32> {
+    let mut steps = Vec::new();
+    let mut connections = start.max(1);
+    while connections < limit {
+        steps.push(connections);
+        connections = connections.saturating_mul(2);
+    }
+    steps.push(limit.max(start));
+
+    steps
+}
+
+/// Probe `steps` in order until the first with failed requests, `error_free`
+/// measures one. `None` when a step couldn't be measured.
+pub fn probe(steps: &[u32], mut error_free: impl FnMut(u32) -> Option<bool>) -> Option<Capacity> {
+    let mut capacity = Capacity {
+        max_error_free: None,
+        breaks_at: None,
+    };
+
+    for connections in steps {
+        if error_free(*connections)? {
+            capacity.max_error_free = Some(*connections);
+        } else {
+            capacity.breaks_at = Some(*connections);
+            break;
+        }
+    }
+
+    Some(capacity)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn doubling() {
+        assert_eq!(steps(16, 1000), [16, 32, 64, 128, 256, 512, 1000]);
+        assert_eq!(steps(16, 64), [16, 32, 64]);
+        assert_eq!(steps(16, 8), [16]);
+    }
+
+    #[test]
+    fn breaks() {
+        let given = steps(16, 1000);
+
+        assert_eq!(
+            probe(&given, |connections| Some(connections < 256)),
+            Some(Capacity {
+                max_error_free: Some(128),
+                breaks_at: Some(256),
+            })
+        );
+        assert_eq!(
+            probe(&given, |_| Some(true))
+                .unwrap()
+                .cell(&NumberFormat::default()),
+            "≥ 1000"
+        );
+        assert_eq!(
+            probe(&given, |_| Some(false))
+                .unwrap()
+                .cell(&NumberFormat::default()),
+            "< 16"
+        );
+        assert_eq!(
+            probe(&given, |connections| (connections < 64).then_some(true)),
+            None
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::BTreeMap;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::Collector;
+use crate::cpu;
+use crate::hooks::Context;
+use crate::port;
+
+/// Version of what a [`CommandCollector`] is given and has to print, told to
+/// it as `BENCHBOT_COLLECTOR_ABI`. Collectors shipped as programs check it
+/// to refuse a protocol they don't know.
+pub const ABI_VERSION: u32 = 1;
+
+/// How long a command collector may take to print its values and exit once
+/// its stdin closed, it's killed then.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Builds a collector of its name and its section of `bench.toml`.
+pub type Factory = fn(&str, &Collector) -> Result<Box<dyn MetricCollector>, String>;
+
+/// Collectors by the `kind` naming them in `bench.toml`. A collector built
+/// into bench-bot, e.g. behind a cargo feature, registers its factory here,
+/// any other tool ships as a program run by a `command` collector.
+pub const KINDS: &[(&str, Factory)] = &[("command", CommandCollector::factory)];
+
+/// Source of metrics besides the load tool's, running while a server is
+/// measured. Values are reported by name next to the built-in columns.
+pub trait MetricCollector {
+    /// Name of the collector, for logs.
+    fn name(&self) -> &str;
+
+    /// Start collecting for the server of `context`.
+    fn start(&mut self, context: &Context) -> Result<(), String>;
+
+    /// Stop collecting, values of the period since `start` by metric name.
+    fn stop(&mut self) -> Result<BTreeMap<String, f64>, String>;
+}
+
+/// Collector of `bench.toml`, a command any tool can implement. It is told
+/// about the benchmark by `BENCHBOT_*` variables and, once its stdin
+/// closes, prints a JSON object of numbers such as `{"GPU %": 12.5}` and
+/// exits.
+pub struct CommandCollector {
+    name: String,
+    command: String,
+    child: Option<Child>,
+    stop_timeout: Duration,
+}
+
+impl CommandCollector {
+    pub fn new(name: &str, command: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            child: None,
+            stop_timeout: STOP_TIMEOUT,
+        }
+    }
+
+    fn factory(name: &str, collector: &Collector) -> Result<Box<dyn MetricCollector>, String> {
+        let command = collector
+            .command
+            .as_deref()
+            .ok_or_else(|| format!("collector {} has no command", name))?;
+
+        Ok(Box::new(Self::new(name, command)))
+    }
+}
+
+impl MetricCollector for CommandCollector {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn start(&mut self, context: &Context) -> Result<(), String> {
+        let child = Command::new("sh")
+            .args(["-c", &self.command])
+            .envs(context.vars())
+            .env("BENCHBOT_COLLECTOR_ABI", ABI_VERSION.to_string())
+            .current_dir(context.workspace_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("starting collector {}: {}", self.name, e))?;
+
+        self.child = Some(child);
+        Ok(())
+    }
+
+    fn stop(&mut self) -> Result<BTreeMap<String, f64>, String> {
+        let mut child = self
+            .child
+            .take()
+            .ok_or_else(|| format!("collector {} wasn't started", self.name))?;
+        drop(child.stdin.take());
+
+        // a collector that never exits would hang the run, its output is a
+        // few bytes buffered in the pipe meanwhile
+        let stopped = Instant::now();
+        while child
+            .try_wait()
+            .map_err(|e| format!("stopping collector {}: {}", self.name, e))?
+            .is_none()
+        {
+            if stopped.elapsed() >= self.stop_timeout {
+                port::kill_tree(&cpu::tree_pids(child.id()));
+                let _ = child.wait();
+                return Err(format!(
+                    "collector {} didn't exit within {:?} of stopping, killed it",
+                    self.name, self.stop_timeout
+                ));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let output = child
+            .wait_with_output()
+            .map_err(|e| format!("stopping collector {}: {}", self.name, e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "collector {} exited with {}",
+                self.name, output.status
+            ));
+        }
+
+        serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("parsing output of collector {}: {}", self.name, e))
+    }
+}
+
+/// Collector `name` configured as `collector`, by the factory of its kind.
+pub fn build(name: &str, collector: &Collector) -> Result<Box<dyn MetricCollector>, String> {
+    let kind = collector.kind.as_deref().unwrap_or("command");
+    let (_, factory) = KINDS
+        .iter()
+        .find(|(known, _)| *known == kind)
+        .ok_or_else(|| {
+            let kinds: Vec<&str> = KINDS.iter().map(|(known, _)| *known).collect();
+            format!(
+                "unknown kind {:?} of collector {}, expected one of {}",
+                kind,
+                name,
+                kinds.join(", ")
+            )
+        })?;
+
+    factory(name, collector)
+}
+
+/// Collectors configured in `bench.toml`, leaving out the ones failing to
+/// build.
+pub fn configured(collectors: &BTreeMap<String, Collector>) -> Vec<Box<dyn MetricCollector>> {
+    collectors
+        .iter()
+        .filter_map(|(name, collector)| {
+            build(name, collector)
+                .map_err(|e| log::warn!("{}.", e))
+                .ok()
+        })
+        .collect()
+}
+
+/// Start `collectors`, leaving out the ones failing to.
+pub fn start_all(
+    collectors: Vec<Box<dyn MetricCollector>>,
+    context: &Context,
+) -> Vec<Box<dyn MetricCollector>> {
+    collectors
+        .into_iter()
+        .filter_map(|mut collector| match collector.start(context) {
+            Ok(()) => {
+                log::debug!("Started collector {}.", collector.name());
+                Some(collector)
+            }
+            Err(e) => {
+                log::warn!("{}.", e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Stop `collectors`, values of the ones that succeeded. Metrics of the same
+/// name are taken from the collector listed last.
+pub fn stop_all(collectors: Vec<Box<dyn MetricCollector>>) -> BTreeMap<String, f64> {
+    let mut values = BTreeMap::new();
+
+    for mut collector in collectors {
+        match collector.stop() {
+            Ok(collected) => values.extend(collected),
+            Err(e) => log::warn!("{}.", e),
+        }
+    }
+
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn command() {
+        let context = Context {
+            framework: "axum",
+            scenario: Some("json"),
+            url: None,
+            server_pid: None,
+            round: None,
+            workspace_dir: Path::new("."),
+            output_dir: Path::new("."),
+        };
+        let given = vec![
+            Box::new(CommandCollector::new(
+                "gpu",
+                r#"read _; echo "{\"GPU %\": 12.5, \"$BENCHBOT_FRAMEWORK\": $BENCHBOT_COLLECTOR_ABI}""#,
+            )) as Box<dyn MetricCollector>,
+            Box::new(CommandCollector::new("broken", "echo not json")),
+            Box::new(CommandCollector {
+                stop_timeout: Duration::from_millis(200),
+                ..CommandCollector::new("hanging", "sleep 30")
+            }),
+        ];
+
+        let started = start_all(given, &context);
+        assert_eq!(started.len(), 3);
+
+        let stopped = Instant::now();
+        let actual = stop_all(started);
+        assert_eq!(
+            actual,
+            BTreeMap::from([("GPU %".to_string(), 12.5), ("axum".to_string(), 1.0)])
+        );
+        assert!(stopped.elapsed() < Duration::from_secs(5));
+    }
+
+    #[test]
+    fn kinds() {
+        let collector = |kind: Option<&str>, command: Option<&str>| Collector {
+            kind: kind.map(str::to_string),
+            command: command.map(str::to_string),
+        };
+
+        assert_eq!(
+            build("gpu", &collector(None, Some("gpu-sampler"))).map(|c| c.name().to_string()),
+            Ok("gpu".to_string())
+        );
+        assert_eq!(
+            build("gpu", &collector(Some("command"), None)).err(),
+            Some("collector gpu has no command".to_string())
+        );
+        assert_eq!(
+            build("gpu", &collector(Some("nvml"), None)).err(),
+            Some("unknown kind \"nvml\" of collector gpu, expected one of command".to_string())
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use clap::ArgEnum;
+
+use crate::report::SKEWED_THREAD_BALANCE;
+use crate::MEMORY_SAMPLE_INTERVAL;
+
+/// A single column of the comparison table, declared in the order of the
+/// table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum Column {
+    FrameworkName,
+    Version,
+    LatencyAvg,
+    LatencyStdev,
+    LatencyMin,
+    LatencyP50,
+    LatencyP75,
+    LatencyP90,
+    LatencyP99,
+    LatencyMax,
+    RequestTotal,
+    RequestReqPerSec,
+    PercentOfBest,
+    Errors,
+    MaxConnections,
+    StatusCodes,
+    RequestCv,
+    Grade,
+    ThreadBalance,
+    TransferTotal,
+    TransferRate,
+    MaxMemory,
+    RequestsPerMb,
+    CpuPerRequest,
+    EnergyPerMillionRequests,
+}
+
+impl Column {
+    pub fn header(self) -> &'static str {
+        match self {
+            Column::FrameworkName => "Framework Name",
+            Column::Version => "Version",
+            Column::LatencyAvg => "Latency.Avg",
+            Column::LatencyStdev => "Latency.Stdev",
+            Column::LatencyMin => "Latency.Min",
+            Column::LatencyP50 => "Latency.50P",
+            Column::LatencyP75 => "Latency.75P",
+            Column::LatencyP90 => "Latency.90P",
+            Column::LatencyP99 => "Latency.99P",
+            Column::LatencyMax => "Latency.Max",
+            Column::RequestTotal => "Request.Total",
+            Column::RequestReqPerSec => "Request.Req/Sec",
+            Column::PercentOfBest => "% of Best",
+            Column::Errors => "Errors",
+            Column::MaxConnections => "Max. Connections",
+            Column::StatusCodes => "Status 2xx/4xx/5xx",
+            Column::RequestCv => "Request.CV",
+            Column::Grade => "Grade",
+            Column::ThreadBalance => "Thread.Balance",
+            Column::TransferTotal => "Transfer.Total",
+            Column::TransferRate => "Transfer.Rate",
+            Column::MaxMemory => "Max. Memory Usage",
+            Column::RequestsPerMb => "Req/Sec per MB",
+            Column::CpuPerRequest => "CPU/Request",
+            Column::EnergyPerMillionRequests => "Energy/1M Req",
+        }
+    }
+
+    /// What the column means, how it is collected and what to keep in mind
+    /// reading it, printed by `bench-bot explain`.
+    pub fn explain(self) -> Explanation {
+        let (definition, collection, caveats) = match self {
+            Column::FrameworkName => (
+                "Name of the implementation.".to_string(),
+                "Directory name of the workspace member.".to_string(),
+                "Links to the framework's detail page in published results.".to_string(),
+            ),
+            Column::Version => (
+                "Version of the framework crate the implementation was built with.".to_string(),
+                "Resolved version in the workspace's `Cargo.lock`, with the short commit when the \
+                    implementation pins a git revision.".to_string(),
+                "The version of the crate named by `framework` in the implementation's manifest, \
+                    not of its dependencies.".to_string(),
+            ),
+            Column::LatencyAvg => (
+                "Mean latency of all requests.".to_string(),
+                "`Latency` row of wrk's thread stats.".to_string(),
+                "A few slow requests move the mean, compare percentiles for tail \
+                    behaviour.".to_string(),
+            ),
+            Column::LatencyStdev => (
+                "Standard deviation of request latency.".to_string(),
+                "`Latency` row of wrk's thread stats.".to_string(),
+                "Latency is not normally distributed, a large stdev mostly hints at a long \
+                    tail.".to_string(),
+            ),
+            Column::LatencyMin => (
+                "Fastest request of the run.".to_string(),
+                "`Latencies` row of rewrk, the minimum of tools reporting one.".to_string(),
+                "wrk and bombardier print no minimum, shown as `-` for them. Mostly the cost of an \
+                    idle round trip, not of the framework.".to_string(),
+            ),
+            Column::LatencyP50 | Column::LatencyP75 | Column::LatencyP90 | Column::LatencyP99 => (
+                format!(
+                    "{}th percentile of request latency.",
+                    match self {
+                        Column::LatencyP50 => 50,
+                        Column::LatencyP75 => 75,
+                        Column::LatencyP90 => 90,
+                        _ => 99,
+                    }
+                ),
+                "`Latency Distribution` printed by wrk run with `--latency`.".to_string(),
+                format!(
+                    "wrk does not correct for coordinated omission, a stalled server delays the \
+                        requests measuring it. Shown as `-` when wrk printed no distribution.{}",
+                    if self == Column::LatencyP99 { " With several rounds a 95% confidence \
+                        interval is added." } else { "" }
+                ),
+            ),
+            Column::LatencyMax => (
+                "Slowest request of the run.".to_string(),
+                "`Latency` row of wrk's thread stats.".to_string(),
+                "A single outlier, often a connection setup or a GC pause of the machine, hardly \
+                    comparable between runs.".to_string(),
+            ),
+            Column::RequestTotal => (
+                "Requests completed during the run.".to_string(),
+                "`requests in` line of wrk.".to_string(),
+                "Includes non-2xx responses, wrk counts every completed request.".to_string(),
+            ),
+            Column::RequestReqPerSec => (
+                "Completed requests per second.".to_string(),
+                "`Requests/sec` line of wrk, total requests over the measured \
+                    duration.".to_string(),
+                "With several rounds the round with the median throughput is reported, with a \
+                    bootstrap 95% confidence interval of the mean.".to_string(),
+            ),
+            Column::PercentOfBest => (
+                "Req/Sec relative to the fastest framework of the table.".to_string(),
+                "Req/Sec over the highest Req/Sec of the table, incomplete reports left out as the \
+                    best.".to_string(),
+                "Relative to the table it's in, so it changes with the frameworks compared. Left \
+                    out of detail pages.".to_string(),
+            ),
+            Column::Errors => (
+                "Failed requests and their share of all requests.".to_string(),
+                "`Socket errors` (connect, read, write, timeout) and `Non-2xx or 3xx responses` \
+                    lines of wrk.".to_string(),
+                "A server dropping connections completes fewer requests per connection but can \
+                    look fast, check this before Req/Sec.".to_string(),
+            ),
+            Column::MaxConnections => (
+                "Most concurrent connections served without a failed request.".to_string(),
+                "wrk runs of 2 seconds with `--probe-connections`, doubling the connections from \
+                    one per thread up to the limit until a run has errors.".to_string(),
+                "Only as fine as the doubling, `≥` when even the limit had no errors, `<` when the \
+                    fewest already had some. Shown as `-` unless probed.".to_string(),
+            ),
+            Column::StatusCodes => (
+                "Responses by class of status code.".to_string(),
+                "Status code counts of oha, vegeta and autocannon, wrk only counts the non-2xx or \
+                    3xx ones.".to_string(),
+                "Shown as `-` for tools that don't report them. 1xx and 3xx responses are left \
+                    out.".to_string(),
+            ),
+            Column::RequestCv => (
+                "Coefficient of variation of Req/Sec across rounds.".to_string(),
+                "Population standard deviation over the mean of every round's Req/Sec.".to_string(),
+                "Needs `--rounds` of at least 2, shown as `-` otherwise.".to_string(),
+            ),
+            Column::Grade => (
+                "Letter grade of the run to run noise of Req/Sec.".to_string(),
+                "A below 2%, B below 5%, C below 10%, D below 20% coefficient of variation, F \
+                    above.".to_string(),
+                "Needs `--rounds` of at least 2. Noisy results are not worth comparing \
+                    closely.".to_string(),
+            ),
+            Column::ThreadBalance => (
+                "How evenly wrk's threads shared the load.".to_string(),
+                "Standard deviation over the mean of the per-thread `Req/Sec` row of \
+                    wrk.".to_string(),
+                format!(
+                    "Above {} is flagged as skewed, usually a server accepting connections \
+                        unevenly.",
+                    SKEWED_THREAD_BALANCE
+                ),
+            ),
+            Column::TransferTotal => (
+                "Bytes read by wrk during the run.".to_string(),
+                "`read` part of wrk's `requests in` line.".to_string(),
+                "Depends on response headers, frameworks sending more headers transfer \
+                    more.".to_string(),
+            ),
+            Column::TransferRate => (
+                "Bytes read per second.".to_string(),
+                "`Transfer/sec` line of wrk.".to_string(),
+                "Depends on response headers as much as on throughput.".to_string(),
+            ),
+            Column::MaxMemory => (
+                "Peak resident memory of the server process.".to_string(),
+                format!(
+                    "RSS of the `cargo run` process sampled every {}ms while wrk runs, the maximum \
+                        is kept.",
+                    MEMORY_SAMPLE_INTERVAL.as_millis()
+                ),
+                "Short spikes between two samples are missed. Memory of child processes is not \
+                    included.".to_string(),
+            ),
+            Column::RequestsPerMb => (
+                "Req/Sec per megabyte of peak memory, crediting frameworks that are fast and \
+                    lean.".to_string(),
+                "Req/Sec divided by Max. Memory Usage.".to_string(),
+                "Inherits the caveats of both, memory of child processes is not \
+                    included.".to_string(),
+            ),
+            Column::CpuPerRequest => (
+                "Server CPU time spent per completed request.".to_string(),
+                "User and system time of the `cargo run` process and its children from `/proc`, \
+                    taken before and after the rounds and divided by the requests of all \
+                    rounds.".to_string(),
+                "Steadier across machines than Req/Sec, but still depends on the CPU. Shown as `-` \
+                    where `/proc` is not available.".to_string(),
+            ),
+            Column::EnergyPerMillionRequests => (
+                "Joules consumed per million completed requests.".to_string(),
+                "RAPL package energy counters in `/sys/class/powercap`, read before and after the \
+                    rounds.".to_string(),
+                "Covers the whole CPU package, wrk and anything else running included. Needs RAPL \
+                    and root to read it, shown as `-` otherwise.".to_string(),
+            ),
+        };
+
+        Explanation {
+            definition,
+            collection,
+            caveats,
+        }
+    }
+}
+
+/// Description of a column, see [`Column::explain`].
+#[derive(Debug)]
+pub struct Explanation {
+    pub definition: String,
+    pub collection: String,
+    pub caveats: String,
+}
+
+impl Explanation {
+    pub fn to_text(&self, header: &str) -> String {
+        format!(
+            "{}\n\nDefinition: {}\nCollection: {}\nCaveats:    {}",
+            header, self.definition, self.collection, self.caveats
+        )
+    }
+}
+
+/// Predefined column sets for different audiences.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum ColumnPreset {
+    /// Every metric wrk reports or bench-bot measures. Latency.Min and the
+    /// status codes only other load tools report, Max. Connections only a
+    /// probe measures, so they are added when wanted.
+    Full,
+    /// Headline numbers only.
+    Compact,
+    /// Latency average, spread and percentiles.
+    Latency,
+    /// Throughput against memory usage.
+    Memory,
+}
+
+impl ColumnPreset {
+    pub fn columns(self) -> &'static [Column] {
+        match self {
+            ColumnPreset::Full => &[
+                Column::FrameworkName,
+                Column::Version,
+                Column::LatencyAvg,
+                Column::LatencyStdev,
+                Column::LatencyP50,
+                Column::LatencyP75,
+                Column::LatencyP90,
+                Column::LatencyP99,
+                Column::LatencyMax,
+                Column::RequestTotal,
+                Column::RequestReqPerSec,
+                Column::PercentOfBest,
+                Column::Errors,
+                Column::RequestCv,
+                Column::Grade,
+                Column::ThreadBalance,
+                Column::TransferTotal,
+                Column::TransferRate,
+                Column::MaxMemory,
+                Column::RequestsPerMb,
+                Column::CpuPerRequest,
+                Column::EnergyPerMillionRequests,
+            ],
+            ColumnPreset::Compact => &[
+                Column::FrameworkName,
+                Column::LatencyAvg,
+                Column::LatencyP99,
+                Column::RequestReqPerSec,
+                Column::PercentOfBest,
+                Column::Errors,
+                Column::Grade,
+                Column::MaxMemory,
+                Column::CpuPerRequest,
+            ],
+            ColumnPreset::Latency => &[
+                Column::FrameworkName,
+                Column::LatencyAvg,
+                Column::LatencyStdev,
+                Column::LatencyMin,
+                Column::LatencyP50,
+                Column::LatencyP75,
+                Column::LatencyP90,
+                Column::LatencyP99,
+                Column::LatencyMax,
+            ],
+            ColumnPreset::Memory => &[
+                Column::FrameworkName,
+                Column::RequestReqPerSec,
+                Column::MaxMemory,
+                Column::RequestsPerMb,
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explain() {
+        let text: Vec<String> = [Column::LatencyP99, Column::ThreadBalance, Column::MaxMemory]
+            .iter()
+            .map(|column| column.explain().to_text(column.header()))
+            .collect();
+
+        insta::assert_snapshot!(text.join("\n\n"));
+    }
+}
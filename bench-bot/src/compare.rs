@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ThresholdMetric, Thresholds};
+use crate::environment::Environment;
+use crate::report::Report;
+use crate::timings::PhaseTimings;
+
+/// A metric of a framework that got worse than its threshold allows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regression {
+    /// Scenario of the report that regressed.
+    pub scenario: Option<String>,
+    pub framework_name: String,
+    pub metric: ThresholdMetric,
+    pub baseline: f64,
+    pub current: f64,
+    /// Change in percent, positive means worse.
+    pub change: f64,
+    pub threshold: f64,
+}
+
+/// Reports of one scenario together with the environment they were measured in.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Results {
+    pub environment: Environment,
+    pub reports: Vec<Report>,
+    /// Harness time of each framework of the scenario by phase. A build
+    /// serving several scenarios counts with the first benchmarked.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub timings: BTreeMap<String, PhaseTimings>,
+    /// wrk Lua script the scenario was measured with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Load generator invocation the scenario was measured with.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+// results files used to be a bare list of reports
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredResults {
+    Results(Results),
+    Reports(Vec<Report>),
+}
+
+/// Load results previously saved with [`save_results`].
+pub fn load_results(path: &Path) -> Result<Results, String> {
+    let content = fs::read(path).map_err(|e| format!("reading {:?}: {}", path, e))?;
+
+    match serde_json::from_slice(&content).map_err(|e| format!("parsing {:?}: {}", path, e))? {
+        StoredResults::Results(results) => Ok(results),
+        StoredResults::Reports(reports) => Ok(Results {
+            environment: Environment::default(),
+            reports,
+            timings: BTreeMap::new(),
+            script: None,
+            command: None,
+        }),
+    }
+}
+
+pub fn save_results(path: &Path, results: &Results) -> Result<(), String> {
+    let content = serde_json::to_vec_pretty(results).map_err(|e| e.to_string())?;
+
+    fs::write(path, content).map_err(|e| format!("writing {:?}: {}", path, e))
+}
+
+/// Regressions of `current` against `baseline`, frameworks missing on
+/// either side are ignored. Reports compare within their scenario only, so
+/// of a run of several scenarios just that of the baseline is checked.
+pub fn find_regressions(
+    baseline: &[Report],
+    current: &[Report],
+    thresholds: &Thresholds,
+) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+
+    // metrics of output cut off early compare to nothing
+    for report in current.iter().filter(|r| !r.is_incomplete()) {
+        let Some(base) = baseline
+            .iter()
+            .filter(|b| b.scenario() == report.scenario())
+            .find(|b| b.framework_name() == report.framework_name() && b.is_reliable())
+        else {
+            continue;
+        };
+
+        for metric in ThresholdMetric::ALL {
+            let Some(threshold) = thresholds.get(report.framework_name(), metric) else {
+                continue;
+            };
+
+            let (baseline_value, current_value) = (value(base, metric), value(report, metric));
+            if baseline_value <= 0.0 || current_value <= 0.0 {
+                continue;
+            }
+
+            let change = match metric {
+                // lower throughput is worse
+                ThresholdMetric::Rps => (baseline_value - current_value) / baseline_value * 100.0,
+                // each percentile on its own, a throughput gain doesn't
+                // excuse a worse tail
+                _ => (current_value - baseline_value) / baseline_value * 100.0,
+            };
+
+            if change > threshold.0 {
+                regressions.push(Regression {
+                    scenario: report.scenario().map(str::to_string),
+                    framework_name: report.framework_name().to_string(),
+                    metric,
+                    baseline: baseline_value,
+                    current: current_value,
+                    change,
+                    threshold: threshold.0,
+                });
+            }
+        }
+    }
+
+    regressions
+}
+
+fn value(report: &Report, metric: ThresholdMetric) -> f64 {
+    match metric {
+        ThresholdMetric::Rps => report.metrics().req_per_sec(),
+        ThresholdMetric::P50 => report.metrics().p50(),
+        ThresholdMetric::P75 => report.metrics().p75(),
+        ThresholdMetric::P90 => report.metrics().p90(),
+        ThresholdMetric::P99 => report.metrics().p99(),
+        ThresholdMetric::Memory => report.max_memory(),
+    }
+}
+
+/// Markdown table listing `regressions`.
+pub fn regressions_table(regressions: &[Regression]) -> String {
+    let mut res = String::from(
+        "| Framework Name | Metric | Baseline | Current | Change | \
+        Threshold |\n|---|---|---|---|---|---|",
+    );
+
+    for r in regressions {
+        res.push_str(&format!(
+            "\n|{}|{}|{:.2}|{:.2}|{:.1}%|{:.1}%|",
+            r.framework_name,
+            r.metric.name(),
+            r.baseline,
+            r.current,
+            r.change,
+            r.threshold
+        ));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn report(name: &str, max_memory: f64, req_per_sec: &str, p99: &str) -> Report {
+        let output = format!(
+            r#"
+    Latency   814.27us  498.47us   8.42ms   69.23%
+  Latency Distribution
+     50%  707.00us
+     75%    1.07ms
+     90%    1.50ms
+     99%    {}
+  17275966 requests in 30.09s, 1.95GB read
+Requests/sec: {}
+Transfer/sec:     66.26MB
+"#,
+            p99, req_per_sec
+        );
+
+        Report::new(name, max_memory, output.parse().expect("parse metric fail"))
+    }
+
+    #[test]
+    fn regressions() {
+        let config: Config = toml::from_str(
+            r#"
+            [thresholds]
+            rps = "3%"
+            p99 = "10%"
+            memory = "15%"
+
+            [thresholds.frameworks.tide]
+            rps = "50%"
+            "#,
+        )
+        .expect("parse config fail");
+
+        let baseline = vec![
+            report("axum", 10.0, "100000.00", "2.00ms"),
+            report("tide", 10.0, "10000.00", "40.00ms"),
+        ];
+        let current = vec![
+            report("axum", 12.0, "90000.00", "2.10ms"),
+            report("tide", 10.0, "8000.00", "40.00ms"),
+            report("viz", 10.0, "1.00", "1.00ms"),
+        ];
+
+        let actual = find_regressions(&baseline, &current, &config.thresholds);
+
+        assert_eq!(actual.len(), 2);
+        assert_eq!(actual[0].framework_name, "axum");
+        assert_eq!(actual[0].metric, ThresholdMetric::Rps);
+        assert_eq!(actual[1].metric, ThresholdMetric::Memory);
+
+        insta::assert_snapshot!(regressions_table(&actual));
+    }
+
+    #[test]
+    fn regressions_of_the_baseline_scenario() {
+        let config: Config = toml::from_str(
+            r#"
+            [thresholds]
+            rps = "3%"
+            "#,
+        )
+        .expect("parse config fail");
+
+        let baseline =
+            vec![report("axum", 10.0, "500000.00", "2.00ms").with_scenario("hello-world")];
+        let current = vec![
+            report("axum", 10.0, "510000.00", "2.00ms").with_scenario("hello-world"),
+            report("axum", 10.0, "120000.00", "2.00ms").with_scenario("json"),
+        ];
+
+        assert_eq!(
+            find_regressions(&baseline, &current, &config.thresholds),
+            []
+        );
+
+        let current = [report("axum", 10.0, "400000.00", "2.00ms").with_scenario("hello-world")];
+        let actual = find_regressions(&baseline, &current, &config.thresholds);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].scenario.as_deref(), Some("hello-world"));
+    }
+
+    #[test]
+    fn tail_regression_despite_throughput_gain() {
+        let config: Config = toml::from_str(
+            r#"
+            [thresholds]
+            rps = "3%"
+            p50 = "5%"
+            p99 = "20%"
+            "#,
+        )
+        .expect("parse config fail");
+
+        let baseline = vec![report("axum", 10.0, "100000.00", "2.00ms")];
+        let current = vec![report("axum", 10.0, "120000.00", "2.60ms")];
+
+        let actual = find_regressions(&baseline, &current, &config.thresholds);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].metric, ThresholdMetric::P99);
+        assert_eq!(actual[0].change.round(), 30.0);
+    }
+
+    #[test]
+    fn results_without_environment() {
+        let path = std::env::temp_dir().join("bench-bot-results-without-environment.json");
+        fs::write(
+            &path,
+            serde_json::to_vec(&vec![report("axum", 10.0, "100000.00", "2.00ms")]).unwrap(),
+        )
+        .unwrap();
+
+        let actual = load_results(&path).expect("load results fail");
+
+        assert!(actual.environment.is_empty());
+        assert_eq!(actual.reports[0].framework_name(), "axum");
+    }
+
+    #[test]
+    fn corrupt_results() {
+        let path = std::env::temp_dir().join("bench-bot-corrupt-results.json");
+        let mut given =
+            serde_json::to_value(vec![report("axum", 10.0, "100000.00", "2.00ms")]).unwrap();
+        *given.pointer_mut("/0/metrics/latency/p99").unwrap() = serde_json::json!(1e300);
+        fs::write(&path, serde_json::to_vec(&given).unwrap()).unwrap();
+
+        assert!(load_results(&path).is_err());
+    }
+}
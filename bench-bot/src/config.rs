@@ -0,0 +1,505 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Contents of `bench.toml`, every section is optional.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Rust toolchain every framework is built with, e.g. `1.75.0`.
+    pub toolchain: Option<String>,
+    pub thresholds: Thresholds,
+    pub weights: Weights,
+    pub artifacts: Option<Artifacts>,
+    pub digest: Option<DigestMail>,
+    pub hooks: Hooks,
+    /// Commands collecting metrics while servers are measured, by name.
+    pub collectors: BTreeMap<String, Collector>,
+    /// wrk Lua scripts of scenarios relative to the workspace, e.g.
+    /// `echo = "scripts/echo.lua"` to send request bodies.
+    pub scripts: BTreeMap<String, PathBuf>,
+    /// Least Req/Sec of scenarios, e.g. `hello-world = 1000`. Frameworks
+    /// measuring less are aborted and reported as pathological.
+    pub floors: BTreeMap<String, f64>,
+    /// Named bundles of run parameters, selected with `--profile`.
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("reading {:?}: {}", path, e))?;
+
+        toml::from_str(&content).map_err(|e| format!("parsing {:?}: {}", path, e))
+    }
+}
+
+/// Environment variables starting with this override config keys, `__`
+/// separating the levels, e.g. `BENCHBOT_THRESHOLDS__RPS=5%`.
+pub const ENV_PREFIX: &str = "BENCHBOT_";
+
+/// Replacement of a single config key, e.g. `thresholds.rps=5%`. Values are
+/// read as TOML and taken as a string when that fails.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Override {
+    pub key: Vec<String>,
+    pub value: toml::Value,
+}
+
+impl FromStr for Override {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid override {:?}, expected KEY=VALUE", s))?;
+        let key: Vec<String> = key.trim().split('.').map(str::to_string).collect();
+
+        if key.iter().any(String::is_empty) {
+            return Err(format!("invalid key in override {:?}", s));
+        }
+
+        Ok(Override {
+            key,
+            value: parse_value(value.trim()),
+        })
+    }
+}
+
+impl fmt::Display for Override {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}={}", self.key.join("."), self.value)
+    }
+}
+
+impl Override {
+    /// Set the key of this override in `config`, creating tables as needed.
+    pub fn apply(&self, config: &mut toml::Value) -> Result<(), String> {
+        let (last, tables) = self.key.split_last().expect("keys are never empty");
+        let mut table = config;
+
+        for name in tables {
+            table = table
+                .as_table_mut()
+                .ok_or_else(|| format!("{} is not a table", self.key.join(".")))?
+                .entry(name.clone())
+                .or_insert_with(|| toml::Value::Table(Default::default()));
+        }
+
+        table
+            .as_table_mut()
+            .ok_or_else(|| format!("{} is not a table", self.key.join(".")))?
+            .insert(last.clone(), self.value.clone());
+
+        Ok(())
+    }
+}
+
+/// Overrides from `BENCHBOT_*` environment variables naming a config key.
+/// Variables without `__` are command line flags, e.g. `BENCHBOT_DURATION`.
+pub fn env_overrides() -> Vec<Override> {
+    let mut overrides: Vec<Override> = env::vars()
+        .filter_map(|(name, value)| {
+            let key = name.strip_prefix(ENV_PREFIX)?;
+            key.contains("__").then(|| Override {
+                key: key.split("__").map(str::to_lowercase).collect(),
+                value: parse_value(&value),
+            })
+        })
+        .collect();
+
+    // the environment is unordered, keep the result reproducible
+    overrides.sort_by(|a, b| a.key.cmp(&b.key));
+    overrides
+}
+
+fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<BTreeMap<String, toml::Value>>(&format!("value = {}", raw))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Allowed regressions against a baseline run.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Thresholds {
+    /// Maximum drop of Req/Sec.
+    pub rps: Option<Percent>,
+    /// Maximum rise of the median latency.
+    pub p50: Option<Percent>,
+    /// Maximum rise of the 75th percentile latency.
+    pub p75: Option<Percent>,
+    /// Maximum rise of the 90th percentile latency.
+    pub p90: Option<Percent>,
+    /// Maximum rise of the 99th percentile latency.
+    pub p99: Option<Percent>,
+    /// Maximum rise of memory usage.
+    pub memory: Option<Percent>,
+    /// Per-framework overrides, e.g. wider bands for noisy implementations.
+    pub frameworks: BTreeMap<String, ThresholdSet>,
+}
+
+impl Thresholds {
+    /// Threshold of `metric` for `framework`, falling back to the global one.
+    pub fn get(&self, framework: &str, metric: ThresholdMetric) -> Option<Percent> {
+        let default = match metric {
+            ThresholdMetric::Rps => self.rps,
+            ThresholdMetric::P50 => self.p50,
+            ThresholdMetric::P75 => self.p75,
+            ThresholdMetric::P90 => self.p90,
+            ThresholdMetric::P99 => self.p99,
+            ThresholdMetric::Memory => self.memory,
+        };
+
+        self.frameworks
+            .get(framework)
+            .and_then(|set| set.get(metric))
+            .or(default)
+    }
+}
+
+/// Run parameters of a profile, unset ones keep the command line defaults.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub duration: Option<usize>,
+    pub connections: Option<usize>,
+    pub rounds: Option<usize>,
+    /// Cooling down between benchmarks in seconds.
+    pub cooldown: Option<u64>,
+    /// Scenarios to run, all when unset.
+    pub scenarios: Option<Vec<String>>,
+    /// Overrides the top-level `toolchain`.
+    pub toolchain: Option<String>,
+}
+
+/// S3 compatible storage the output directory is uploaded to.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Artifacts {
+    /// Destination url, e.g. `s3://bench-results/runs`.
+    pub bucket: String,
+    /// Endpoint of non-AWS storage such as MinIO.
+    pub endpoint: Option<String>,
+    /// Lifetime of presigned links in seconds.
+    #[serde(default = "Artifacts::default_expires_in")]
+    pub expires_in: u64,
+}
+
+impl Artifacts {
+    fn default_expires_in() -> u64 {
+        // the maximum AWS allows, a week
+        7 * 24 * 60 * 60
+    }
+}
+
+/// Shell commands run at phases of every benchmark, e.g. to start tracing
+/// the server. They are told about the benchmark by `BENCHBOT_*`
+/// variables.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Hooks {
+    /// Before building each member.
+    pub pre_build: Option<String>,
+    /// Once the server is up.
+    pub post_start: Option<String>,
+    /// Before every round of the load tool.
+    pub pre_measure: Option<String>,
+    /// After the server was stopped.
+    pub post_teardown: Option<String>,
+}
+
+/// Collector of metrics of the measured period, see `src/collector.rs`.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Collector {
+    /// Kind of collector registered in `collector::KINDS`, `command` when
+    /// missing.
+    pub kind: Option<String>,
+    /// Program of a `command` collector, run by `sh -c`.
+    pub command: Option<String>,
+}
+
+/// Recipients of the digest mailed by `bench-bot digest --send`.
+#[derive(Debug, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct DigestMail {
+    /// SMTP server, e.g. `smtps://smtp.example.com:465`.
+    pub smtp_url: String,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Login of the SMTP server, the password is read from
+    /// `BENCHBOT_SMTP_PASSWORD`.
+    pub user: Option<String>,
+}
+
+/// Weights of scenarios in the overall score, e.g. `json = 3`.
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct Weights(BTreeMap<String, f64>);
+
+impl Weights {
+    /// Weight of `scenario`, scenarios not listed weigh 1.
+    pub fn get(&self, scenario: &str) -> f64 {
+        self.0.get(scenario).copied().unwrap_or(1.0)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &f64)> {
+        self.0.iter()
+    }
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThresholdSet {
+    /// Maximum drop of Req/Sec.
+    pub rps: Option<Percent>,
+    /// Maximum rise of the median latency.
+    pub p50: Option<Percent>,
+    /// Maximum rise of the 75th percentile latency.
+    pub p75: Option<Percent>,
+    /// Maximum rise of the 90th percentile latency.
+    pub p90: Option<Percent>,
+    /// Maximum rise of the 99th percentile latency.
+    pub p99: Option<Percent>,
+    /// Maximum rise of memory usage.
+    pub memory: Option<Percent>,
+}
+
+impl ThresholdSet {
+    fn get(&self, metric: ThresholdMetric) -> Option<Percent> {
+        match metric {
+            ThresholdMetric::Rps => self.rps,
+            ThresholdMetric::P50 => self.p50,
+            ThresholdMetric::P75 => self.p75,
+            ThresholdMetric::P90 => self.p90,
+            ThresholdMetric::P99 => self.p99,
+            ThresholdMetric::Memory => self.memory,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdMetric {
+    Rps,
+    P50,
+    P75,
+    P90,
+    P99,
+    Memory,
+}
+
+impl ThresholdMetric {
+    pub const ALL: [ThresholdMetric; 6] = [
+        ThresholdMetric::Rps,
+        ThresholdMetric::P50,
+        ThresholdMetric::P75,
+        ThresholdMetric::P90,
+        ThresholdMetric::P99,
+        ThresholdMetric::Memory,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ThresholdMetric::Rps => "rps",
+            ThresholdMetric::P50 => "p50",
+            ThresholdMetric::P75 => "p75",
+            ThresholdMetric::P90 => "p90",
+            ThresholdMetric::P99 => "p99",
+            ThresholdMetric::Memory => "memory",
+        }
+    }
+}
+
+/// A percentage written as `"3%"` in `bench.toml`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Percent(pub f64);
+
+impl JsonSchema for Percent {
+    fn schema_name() -> String {
+        "Percent".to_string()
+    }
+
+    fn json_schema(_: &mut schemars::gen::SchemaGenerator) -> schemars::schema::Schema {
+        schemars::schema::SchemaObject {
+            instance_type: Some(schemars::schema::InstanceType::String.into()),
+            string: Some(Box::new(schemars::schema::StringValidation {
+                pattern: Some(r"^\s*\d+(\.\d+)?\s*%\s*$".to_string()),
+                ..Default::default()
+            })),
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl FromStr for Percent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Percent::try_from(s.to_string())
+    }
+}
+
+impl TryFrom<String> for Percent {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value
+            .trim()
+            .strip_suffix('%')
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|v| *v >= 0.0)
+            .map(Percent)
+            .ok_or_else(|| format!("invalid percentage {:?}, expected e.g. \"3%\"", value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds() {
+        let given = r#"
+            [thresholds]
+            rps = "3%"
+            p99 = "10%"
+            memory = "15%"
+
+            [thresholds.frameworks.tide]
+            rps = "20%"
+        "#;
+        let config: Config = toml::from_str(given).expect("parse config fail");
+
+        assert_eq!(
+            config.thresholds.get("axum", ThresholdMetric::Rps),
+            Some(Percent(3.0))
+        );
+        assert_eq!(
+            config.thresholds.get("tide", ThresholdMetric::Rps),
+            Some(Percent(20.0))
+        );
+        assert_eq!(
+            config.thresholds.get("tide", ThresholdMetric::P99),
+            Some(Percent(10.0))
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let config: Config = toml::from_str("").expect("parse config fail");
+
+        assert_eq!(config.toolchain, None);
+        assert!(config.artifacts.is_none());
+        assert_eq!(config.thresholds.get("axum", ThresholdMetric::Memory), None);
+        assert_eq!(config.weights.get("json"), 1.0);
+    }
+
+    #[test]
+    fn artifacts() {
+        let given = r#"
+            [artifacts]
+            bucket = "s3://bench-results/runs"
+            endpoint = "https://minio.example.com:9000"
+        "#;
+        let config: Config = toml::from_str(given).expect("parse config fail");
+        let artifacts = config.artifacts.expect("artifacts missing");
+
+        assert_eq!(artifacts.bucket, "s3://bench-results/runs");
+        assert_eq!(artifacts.expires_in, 604800);
+    }
+
+    #[test]
+    fn profiles() {
+        let given = r#"
+            toolchain = "1.75.0"
+
+            [profiles.quick]
+            duration = 5
+            scenarios = ["hello-world"]
+
+            [profiles.nightly]
+            rounds = 5
+            toolchain = "nightly-2024-01-01"
+        "#;
+        let config: Config = toml::from_str(given).expect("parse config fail");
+
+        assert_eq!(config.profiles["quick"].duration, Some(5));
+        assert_eq!(config.profiles["quick"].rounds, None);
+        assert_eq!(
+            config.profiles["nightly"].toolchain.as_deref(),
+            Some("nightly-2024-01-01")
+        );
+    }
+
+    #[test]
+    fn unknown_key() {
+        let given = r#"
+            [thresholds]
+            rsp = "3%"
+        "#;
+
+        let actual = toml::from_str::<Config>(given).unwrap_err().to_string();
+
+        assert!(actual.contains("unknown field `rsp`"), "{}", actual);
+        assert!(actual.contains("key `thresholds` at line 2"), "{}", actual);
+    }
+
+    #[test]
+    fn overrides() {
+        let mut given: toml::Value = toml::from_str("[thresholds]\nrps = \"3%\"").unwrap();
+
+        for o in [
+            "thresholds.rps=5%",
+            "thresholds.frameworks.actix-web.p99=20%",
+            "profiles.ci.rounds=2",
+        ] {
+            o.parse::<Override>().unwrap().apply(&mut given).unwrap();
+        }
+        let config: Config = given.try_into().expect("parse config fail");
+
+        assert_eq!(
+            config.thresholds.get("axum", ThresholdMetric::Rps),
+            Some(Percent(5.0))
+        );
+        assert_eq!(
+            config.thresholds.get("actix-web", ThresholdMetric::P99),
+            Some(Percent(20.0))
+        );
+        assert_eq!(config.profiles["ci"].rounds, Some(2));
+        assert_eq!(
+            "profiles.ci.rounds=2"
+                .parse::<Override>()
+                .unwrap()
+                .to_string(),
+            "profiles.ci.rounds=2"
+        );
+        assert_eq!(
+            "thresholds.rps=5%".parse::<Override>().unwrap().to_string(),
+            "thresholds.rps=\"5%\""
+        );
+        assert!("thresholds".parse::<Override>().is_err());
+        assert!("thresholds.rps.max=1"
+            .parse::<Override>()
+            .unwrap()
+            .apply(&mut toml::from_str("[thresholds]\nrps = \"3%\"").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_percent() {
+        let given = r#"
+            [thresholds]
+            rps = "three"
+        "#;
+
+        assert!(toml::from_str::<Config>(given).is_err());
+    }
+}
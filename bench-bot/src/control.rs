@@ -0,0 +1,102 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// File in the output directory a run picks up control requests from.
+pub const CONTROL_FILE: &str = "control";
+
+/// Request to a run in progress.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Control {
+    /// Stop the run and report the frameworks benchmarked so far.
+    Cancel,
+    /// Abandon the current framework and continue with the next one.
+    Skip,
+}
+
+impl Control {
+    pub fn name(self) -> &'static str {
+        match self {
+            Control::Cancel => "cancel",
+            Control::Skip => "skip",
+        }
+    }
+
+    /// Ask the run writing to `dir` to follow this request.
+    pub fn send(self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(CONTROL_FILE);
+
+        fs::write(&path, self.name()).map_err(|e| format!("writing {:?}: {}", path, e))
+    }
+
+    /// Pending request of the run writing to `dir`, removed once taken.
+    pub fn take(dir: &Path) -> Option<Self> {
+        let path = dir.join(CONTROL_FILE);
+        let content = fs::read_to_string(&path).ok()?;
+        let _ = fs::remove_file(&path);
+
+        match content.trim() {
+            "cancel" => Some(Control::Cancel),
+            "skip" => Some(Control::Skip),
+            other => {
+                log::warn!("Ignoring unknown control request {:?}.", other);
+                None
+            }
+        }
+    }
+}
+
+/// Run `command` to completion, killing it when a request arrives in `dir`.
+/// The output is buffered in pipes, which is fine for the few kilobytes
+/// load tools print.
+pub fn run_interruptible(command: &mut Command, dir: &Path) -> io::Result<Result<Output, Control>> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    loop {
+        if child.try_wait()?.is_some() {
+            return child.wait_with_output().map(Ok);
+        }
+
+        if let Some(control) = Control::take(dir) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(Err(control));
+        }
+
+        thread::sleep(Duration::from_millis(200));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn send_and_take() {
+        let dir = env::temp_dir().join("bench-bot-test-control");
+        fs::create_dir_all(&dir).unwrap();
+
+        Control::Skip.send(&dir).unwrap();
+
+        assert_eq!(Control::take(&dir), Some(Control::Skip));
+        assert_eq!(Control::take(&dir), None);
+    }
+
+    #[test]
+    fn interrupt() {
+        let dir = env::temp_dir().join("bench-bot-test-interrupt");
+        fs::create_dir_all(&dir).unwrap();
+        Control::Cancel.send(&dir).unwrap();
+
+        let actual = run_interruptible(Command::new("sleep").arg("10"), &dir).unwrap();
+
+        assert_eq!(actual.err(), Some(Control::Cancel));
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+
+use sysinfo::{Pid, PidExt, ProcessExt, ProcessRefreshKind, System, SystemExt};
+
+/// Clock ticks per second of the times in `/proc/<pid>/stat`, USER_HZ is
+/// fixed at 100 on the platforms benchmarks run on.
+const CLOCK_TICKS_PER_SEC: f64 = 100.0;
+
+/// CPU seconds spent so far by `pid` and every process below it, the server
+/// runs as a child of `cargo run`. `None` without `/proc`.
+pub fn tree_time(pid: u32) -> Option<f64> {
+    let mut pids = tree(pid).into_iter();
+    let mut total = process_time(pids.next()?)?;
+    for pid in pids {
+        // a child exiting in between counts as idle
+        total += process_time(pid).unwrap_or(0.0);
+    }
+
+    Some(total)
+}
+
+/// `pid` and every process below it.
+pub fn tree_pids(pid: u32) -> Vec<u32> {
+    tree(pid).into_iter().map(|pid| pid.as_u32()).collect()
+}
+
+fn tree(pid: u32) -> Vec<Pid> {
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(ProcessRefreshKind::new());
+
+    let root = Pid::from_u32(pid);
+    let mut tree = vec![root];
+    let mut parents = vec![root];
+
+    while let Some(parent) = parents.pop() {
+        for (pid, process) in sys.processes() {
+            if process.parent() == Some(parent) {
+                tree.push(*pid);
+                parents.push(*pid);
+            }
+        }
+    }
+
+    tree
+}
+
+fn process_time(pid: Pid) -> Option<f64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+
+    parse_stat(&stat)
+}
+
+/// utime plus stime of a `/proc/<pid>/stat` line in seconds.
+fn parse_stat(stat: &str) -> Option<f64> {
+    // the command name may contain spaces, fields are counted after it
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+
+    // utime and stime are the 14th and 15th field of the line
+    let utime: f64 = fields.get(11)?.parse().ok()?;
+    let stime: f64 = fields.get(12)?.parse().ok()?;
+
+    Some((utime + stime) / CLOCK_TICKS_PER_SEC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stat() {
+        let given = "4242 (hello world) S 4200 4242 4200 34816 4242 4194560 1545 0 0 0 1250 311 0 \
+            0 20 0 17 0 47615 1174036480 3516 18446744073709551615";
+
+        assert_eq!(parse_stat(given), Some(15.61));
+        assert_eq!(parse_stat("4242 (truncated) S 4200"), None);
+    }
+}
@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Environment of servers: backtraces of panics, but not of every error
+/// value of libraries capturing one, which would slow down serving.
+pub const BACKTRACE_ENV: [(&str, &str); 2] = [("RUST_BACKTRACE", "1"), ("RUST_LIB_BACKTRACE", "0")];
+
+/// Lines of a backtrace kept for the failure record.
+const MAX_LINES: usize = 40;
+
+/// File the stderr of the server of `framework` is written to, in the detail
+/// directory of its scenario.
+pub fn stderr_path(detail_dir: &Path, framework: &str) -> PathBuf {
+    detail_dir.join(format!("{}.stderr.log", framework))
+}
+
+/// Allow `pids` to dump core, `cargo run` doesn't pass on a limit set for
+/// the harness. Best effort, without `prlimit` there are no core dumps.
+pub fn enable_core_dumps(pids: &[u32]) {
+    for pid in pids {
+        let _ = Command::new("prlimit")
+            .args(["--core=unlimited", "--pid", &pid.to_string()])
+            .output();
+    }
+}
+
+/// Why the server crashed and where, from the first of its panic message
+/// with the backtrace below it, the stack of a core dump of one of `pids`
+/// or the signal cargo reports.
+pub fn backtrace(stderr: &str, pids: &[u32]) -> Option<String> {
+    panic(stderr)
+        .or_else(|| pids.iter().find_map(|pid| core_dump(*pid)))
+        .or_else(|| signal(stderr))
+}
+
+// `thread 'tokio-runtime-worker' panicked at ...` and the frames below
+fn panic(stderr: &str) -> Option<String> {
+    let lines: Vec<&str> = stderr.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("thread '") && line.contains("panicked at"))?;
+
+    let snippet: Vec<&str> = lines[start..]
+        .iter()
+        .take(MAX_LINES)
+        .take_while(|line| !line.starts_with("note: Some details are omitted"))
+        .copied()
+        .collect();
+
+    Some(snippet.join("\n"))
+}
+
+// cargo: `process didn't exit successfully: ... (signal: 11, SIGSEGV: invalid memory reference)`
+fn signal(stderr: &str) -> Option<String> {
+    stderr
+        .lines()
+        .find(|line| {
+            line.contains("process didn't exit successfully") && line.contains("(signal: ")
+        })
+        .map(|line| line.trim().to_string())
+}
+
+// the stack systemd-coredump symbolized, `Stack trace of thread ...:` up to
+// the blank line ending it
+fn core_dump(pid: u32) -> Option<String> {
+    let output = Command::new("coredumpctl")
+        .args(["info", "--no-pager", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    stack_trace(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn stack_trace(info: &str) -> Option<String> {
+    let lines: Vec<&str> = info.lines().map(str::trim).collect();
+    let start = lines
+        .iter()
+        .position(|line| line.starts_with("Stack trace of thread"))?;
+
+    let snippet: Vec<&str> = lines[start..]
+        .iter()
+        .take(MAX_LINES)
+        .take_while(|line| !line.is_empty())
+        .copied()
+        .collect();
+
+    Some(snippet.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn panics() {
+        let given = "\
+   Compiling hello-world-axum v0.1.0
+thread 'tokio-runtime-worker' panicked at src/main.rs:12:5:
+index out of bounds: the len is 0 but the index is 3
+stack backtrace:
+   0: rust_begin_unwind
+   1: core::panicking::panic_bounds_check
+   2: hello_world_axum::handler
+             at ./src/main.rs:12:5
+note: Some details are omitted, run with `RUST_BACKTRACE=full` for a verbose backtrace.
+error: process didn't exit successfully: `target/release/hello-world-axum` (exit status: 101)";
+
+        let actual = backtrace(given, &[]).unwrap();
+
+        assert!(actual.starts_with("thread 'tokio-runtime-worker' panicked at src/main.rs:12:5:"));
+        assert!(actual.ends_with("at ./src/main.rs:12:5"));
+    }
+
+    #[test]
+    fn signals() {
+        let given = "error: process didn't exit successfully: \
+            `target/release/hello-world-may-minihttp` (signal: 11, SIGSEGV: invalid memory \
+            reference)";
+
+        assert_eq!(
+            backtrace(given, &[]).as_deref(),
+            Some(
+                "error: process didn't exit successfully: \
+                `target/release/hello-world-may-minihttp` (signal: 11, SIGSEGV: invalid memory \
+                reference)"
+            )
+        );
+        assert_eq!(backtrace("Listening on 127.0.0.1:3000", &[]), None);
+    }
+
+    #[test]
+    fn core_dumps() {
+        let given = "
+           PID: 4242 (hello-world-may)
+        Signal: 11 (SEGV)
+       Message: Process 4242 (hello-world-may) of user 1000 dumped core.
+
+                Stack trace of thread 4243:
+                #0  0x000055d0c1a2b3c4 may::coroutine::yield_now (hello-world-may + 0x2b3c4)
+                #1  0x000055d0c1a2c000 hello_world_may::main (hello-world-may + 0x2c000)
+
+                Stack trace of thread 4242:
+                #0  0x00007f00 epoll_wait (libc.so.6 + 0x1000)
+";
+
+        assert_eq!(
+            stack_trace(given).as_deref(),
+            Some(
+                "Stack trace of thread 4243:\n#0  0x000055d0c1a2b3c4 may::coroutine::yield_now \
+                (hello-world-may + 0x2b3c4)\n#1  0x000055d0c1a2c000 hello_world_may::main \
+                (hello-world-may + 0x2c000)"
+            )
+        );
+    }
+}
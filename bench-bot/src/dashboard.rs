@@ -0,0 +1,480 @@
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Take, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::control::Control;
+use crate::html::escape;
+use crate::lock;
+use crate::ratelimit::RateLimiter;
+use crate::status::{Status, LOG_FILE};
+use crate::submission;
+
+/// Lines of the run log shown on the dashboard.
+const LOG_TAIL_LINES: usize = 50;
+
+/// Largest request body accepted, a submission of every framework is far
+/// smaller.
+const MAX_BODY: usize = 10 * 1024 * 1024;
+
+/// Largest request line and headers accepted, and longest line of them.
+const MAX_HEAD: usize = 64 * 1024;
+const MAX_LINE: usize = 8 * 1024;
+
+/// Longest a client may take sending its whole request or each write of the
+/// response, requests being handled one at a time a slow one holds up
+/// everyone else.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Who may skip and cancel the run from the dashboard.
+pub struct Access<'a> {
+    /// Store results posted by `submit`, see [`crate::submission`].
+    pub accept_submissions: bool,
+    /// Required as `?token=` by the control routes. Without it they are
+    /// open, unless submissions are accepted: the dashboard is public then.
+    pub control_token: Option<&'a str>,
+}
+
+impl Access<'_> {
+    fn controls(&self, token: Option<&str>) -> bool {
+        match self.control_token {
+            Some(expected) => token == Some(expected),
+            None => !self.accept_submissions,
+        }
+    }
+}
+
+/// Serve a status page of the run writing to `output_dir` until killed.
+/// Requests are handled one at a time, which is plenty for a few people
+/// looking at a stalled run. Clients sending more than their share of
+/// requests are turned away until their token bucket refills.
+pub fn serve(addr: &str, output_dir: &Path, access: &Access) -> Result<(), String> {
+    let listener = TcpListener::bind(addr).map_err(|e| format!("binding {}: {}", addr, e))?;
+    log::info!("Dashboard listening on http://{}", addr);
+    let mut limiter = RateLimiter::default();
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(mut stream) => {
+                let admitted = match stream.peer_addr() {
+                    Ok(peer) => limiter.admit(peer.ip(), Instant::now()),
+                    Err(_) => Ok(()),
+                };
+                if let Err(wait) = admitted {
+                    // not logged, a flood would flood the log too
+                    let _ = write!(
+                        stream,
+                        "HTTP/1.1 429 Too Many Requests\r\nRetry-After: {}\r\nContent-Length: \
+                            0\r\nConnection: close\r\n\r\n",
+                        wait.as_secs_f64().ceil().max(1.0) as u64
+                    );
+                    continue;
+                }
+
+                if let Err(e) = handle(stream, output_dir, access) {
+                    log::warn!("Dashboard request failed: {}", e);
+                }
+            }
+            Err(e) => log::warn!("Dashboard connection failed: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+fn handle(mut stream: TcpStream, output_dir: &Path, access: &Access) -> io::Result<()> {
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let deadline = Deadline {
+        stream: &stream,
+        at: Instant::now() + TIMEOUT,
+    };
+    let mut reader = BufReader::new(deadline.take(MAX_HEAD as u64));
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or("GET"), parts.next().unwrap_or("/"));
+    let (path, token) = match target.split_once('?') {
+        Some((path, query)) => (
+            path,
+            query
+                .split('&')
+                .find_map(|param| param.strip_prefix("token=")),
+        ),
+        None => (target, None),
+    };
+    let controls = access.controls(token).then_some(token);
+
+    if (method, path) == ("POST", "/submissions") {
+        let response = match read_body(&mut reader)? {
+            _ if !access.accept_submissions => {
+                ("404 Not Found", "not accepting submissions".to_string())
+            }
+            _ if submission::is_full(output_dir) => (
+                "507 Insufficient Storage",
+                "not accepting more submissions".to_string(),
+            ),
+            None => ("413 Payload Too Large", "submission too large".to_string()),
+            Some(body) => match submission::accept(output_dir, &body) {
+                Ok(scenario) => {
+                    log::info!("Accepted a submission of {}.", scenario);
+                    ("201 Created", format!("/community/{}", scenario))
+                }
+                Err(e) => ("400 Bad Request", e),
+            },
+        };
+        return write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: \
+                close\r\n\r\n{}",
+            response.0,
+            response.1.len(),
+            response.1
+        );
+    }
+
+    let control = match (method, path) {
+        ("POST", "/cancel") => Some(Control::Cancel),
+        ("POST", "/skip") => Some(Control::Skip),
+        _ => None,
+    };
+    if let Some(control) = control {
+        let Some(token) = controls else {
+            return write!(
+                stream,
+                "HTTP/1.1 403 Forbidden\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+            );
+        };
+        if let Err(e) = control.send(output_dir) {
+            log::warn!("Sending {} request failed: {}", control.name(), e);
+        }
+        return write!(
+            stream,
+            "HTTP/1.1 303 See Other\r\nLocation: /{}\r\nContent-Length: 0\r\nConnection: \
+                close\r\n\r\n",
+            query(token)
+        );
+    }
+
+    let community = path
+        .strip_prefix("/community/")
+        .and_then(|scenario| Some((scenario, submission::load(output_dir, scenario)?)));
+
+    let (status, content_type, body) = match (path, community) {
+        ("/", _) => (
+            "200 OK",
+            "text/html; charset=utf-8",
+            page(output_dir, controls).into_bytes(),
+        ),
+        (_, Some((scenario, submissions))) => (
+            "200 OK",
+            "text/html; charset=utf-8",
+            submission::page(scenario, &submission::distributions(&submissions)).into_bytes(),
+        ),
+        _ => match path
+            .strip_prefix("/files/")
+            .and_then(|name| report_file(output_dir, name))
+        {
+            Some(file) => match fs::read(&file) {
+                Ok(content) => ("200 OK", content_type(&file), content),
+                Err(_) => ("404 Not Found", "text/plain", b"not found".to_vec()),
+            },
+            None => ("404 Not Found", "text/plain", b"not found".to_vec()),
+        },
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(&body)
+}
+
+/// Reads of a client's request failing once the deadline `at` passed, so
+/// trickling it a byte at a time doesn't hold up the dashboard either.
+struct Deadline<'a> {
+    stream: &'a TcpStream,
+    at: Instant,
+}
+
+impl Read for Deadline<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let remaining = self.at.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(remaining))?;
+
+        self.stream.read(buf)
+    }
+}
+
+/// A line of the request head, an error when it exceeds [`MAX_LINE`].
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE as u64).read_line(&mut line)?;
+    if line.len() >= MAX_LINE && !line.ends_with('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "request line too long",
+        ));
+    }
+
+    Ok(line)
+}
+
+/// Body of a request whose request line was read from `reader`, `None` when
+/// it exceeds [`MAX_BODY`].
+fn read_body(reader: &mut BufReader<Take<Deadline>>) -> io::Result<Option<Vec<u8>>> {
+    let mut length = 0;
+    loop {
+        let header = read_line(reader)?;
+        if header.trim().is_empty() {
+            if reader.get_ref().limit() == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "request head too large",
+                ));
+            }
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Ok(None);
+    }
+    reader.get_mut().set_limit(length as u64);
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    Ok(Some(body))
+}
+
+// only plain file names of the output directory, nothing above it
+fn report_file(output_dir: &Path, name: &str) -> Option<PathBuf> {
+    let valid = !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\']);
+
+    valid.then(|| output_dir.join(name))
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|e| e.to_str()) {
+        Some("json") => "application/json",
+        Some("csv") => "text/csv; charset=utf-8",
+        Some("html") => "text/html; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+// the token passed on to the control routes, so whoever opened the page
+// with it can use them
+fn query(token: Option<&str>) -> String {
+    token
+        .map(|token| format!("?token={}", escape(token)))
+        .unwrap_or_default()
+}
+
+/// `controls` is `None` when the viewer may not skip or cancel the run,
+/// otherwise the token they passed.
+fn page(output_dir: &Path, controls: Option<Option<&str>>) -> String {
+    let status = Status::load(output_dir);
+    let log = fs::read_to_string(output_dir.join(LOG_FILE)).unwrap_or_default();
+
+    let mut reports: Vec<String> = fs::read_dir(output_dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".md") || name.ends_with(".html"))
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort();
+
+    let waiting = lock::queue(&lock::machine_lock());
+
+    render(
+        status.as_ref(),
+        controls,
+        &waiting,
+        &log,
+        &reports,
+        &submission::scenarios(output_dir),
+    )
+}
+
+/// `waiting` are the pids of the runs queued for the machine, next first.
+fn render(
+    status: Option<&Status>,
+    controls: Option<Option<&str>>,
+    waiting: &[u32],
+    log: &str,
+    reports: &[String],
+    community: &[String],
+) -> String {
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta http-equiv=\"refresh\" content=\"5\">\n<title>bench-bot</title>\n</head>\n\
+         <body>\n<h1>bench-bot</h1>\n",
+    );
+
+    match status {
+        Some(status) => {
+            html.push_str(&format!(
+                "<p>Phase: <b>{}</b>{}</p>\n",
+                escape(&status.phase),
+                status
+                    .current
+                    .as_deref()
+                    .map(|current| format!(", current: <b>{}</b>", escape(current)))
+                    .unwrap_or_default()
+            ));
+            if let Some(token) = controls.filter(|_| status.phase == "benchmarking") {
+                html.push_str(&format!(
+                    "<form method=\"post\" action=\"/skip{0}\">\
+                     <button>Skip current</button></form>\n\
+                     <form method=\"post\" action=\"/cancel{0}\">\
+                     <button>Cancel run</button></form>\n",
+                    query(token)
+                ));
+            }
+            html.push_str(&list("Queue", status.queue.iter().map(|m| escape(m))));
+            html.push_str(&list("Done", status.done.iter().map(|m| escape(m))));
+        }
+        None => html.push_str("<p>No run has written a status yet.</p>\n"),
+    }
+
+    if !waiting.is_empty() {
+        let positions = waiting
+            .iter()
+            .enumerate()
+            .map(|(ahead, pid)| format!("{}. pid {}", ahead + 1, pid));
+        html.push_str(&list("Waiting Runs", positions));
+    }
+
+    let lines: Vec<&str> = log.lines().collect();
+    let tail = lines[lines.len().saturating_sub(LOG_TAIL_LINES)..].join("\n");
+    html.push_str(&format!("<h2>Log</h2>\n<pre>{}</pre>\n", escape(&tail)));
+
+    let links = reports
+        .iter()
+        .map(|name| format!("<a href=\"/files/{0}\">{0}</a>", escape(name)));
+    html.push_str(&list("Reports", links));
+
+    if !community.is_empty() {
+        let links = community
+            .iter()
+            .map(|scenario| format!("<a href=\"/community/{0}\">{0}</a>", escape(scenario)));
+        html.push_str(&list("Community Results", links));
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+// `items` are html already
+fn list(title: &str, items: impl Iterator<Item = String>) -> String {
+    let items: String = items.map(|item| format!("<li>{}</li>\n", item)).collect();
+    if items.is_empty() {
+        return format!("<h2>{}</h2>\n<p>-</p>\n", title);
+    }
+
+    format!("<h2>{}</h2>\n<ul>\n{}</ul>\n", title, items)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn page() {
+        let status = Status {
+            phase: "benchmarking".to_string(),
+            current: Some("hello-world/axum".to_string()),
+            queue: vec!["hello-world/tide".to_string()],
+            done: vec!["hello-world/actix-web".to_string()],
+            updated: 1_700_000_000,
+        };
+        let log = "[2024-01-01T00:00:00Z INFO ] Benchmarking \"hello-world/axum\"";
+
+        insta::assert_snapshot!(render(
+            Some(&status),
+            Some(None),
+            &[],
+            log,
+            &["hello-world.md".to_string()],
+            &[]
+        ));
+    }
+
+    #[test]
+    fn waiting_runs() {
+        let actual = render(None, None, &[4242, 4343], "", &[], &[]);
+
+        assert!(actual.contains(
+            "<h2>Waiting Runs</h2>\n<ul>\n<li>1. pid 4242</li>\n<li>2. pid 4343</li>\n</ul>"
+        ));
+    }
+
+    #[test]
+    fn control_access() {
+        let local = Access {
+            accept_submissions: false,
+            control_token: None,
+        };
+        let public = Access {
+            accept_submissions: true,
+            control_token: None,
+        };
+        let token = Access {
+            accept_submissions: true,
+            control_token: Some("secret"),
+        };
+
+        assert!(local.controls(None));
+        assert!(!public.controls(None));
+        assert!(!token.controls(None));
+        assert!(!token.controls(Some("guess")));
+        assert!(token.controls(Some("secret")));
+    }
+
+    #[test]
+    fn long_request_line() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        client
+            .write_all(format!("GET /{}", "a".repeat(2 * MAX_LINE)).as_bytes())
+            .unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let access = Access {
+            accept_submissions: false,
+            control_token: None,
+        };
+
+        let actual = handle(stream, &env::temp_dir(), &access);
+
+        assert_eq!(actual.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn files_stay_in_output_dir() {
+        let dir = Path::new("/tmp/result");
+
+        assert_eq!(
+            report_file(dir, "hello-world.md"),
+            Some(dir.join("hello-world.md"))
+        );
+        assert_eq!(report_file(dir, "../etc/passwd"), None);
+        assert_eq!(report_file(dir, ".bench-bot.lock"), None);
+    }
+}
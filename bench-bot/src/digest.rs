@@ -0,0 +1,371 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{self, Command, Stdio};
+
+use crate::config::DigestMail;
+use crate::history::RunRecord;
+use crate::html::escape;
+
+/// Movers listed in a digest.
+const TOP_MOVERS: usize = 5;
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Summary of the runs of a period for people not watching the repository.
+#[derive(Debug, PartialEq)]
+pub struct Digest {
+    pub runs: usize,
+    pub failures: usize,
+    /// Largest Req/Sec changes between the first and last run of the period.
+    pub movers: Vec<Mover>,
+    /// (scenario, framework) benchmarked for the first time.
+    pub new_frameworks: Vec<(String, String)>,
+    /// (scenario, framework, sparkline) of Req/Sec over the period.
+    pub trends: Vec<(String, String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Mover {
+    pub scenario: String,
+    pub framework_name: String,
+    pub before: f64,
+    pub after: f64,
+    /// Change in percent.
+    pub change: f64,
+}
+
+/// Digest of the records with a timestamp of at least `since`.
+pub fn build(records: &[RunRecord], since: u64) -> Digest {
+    let (earlier, period): (Vec<&RunRecord>, Vec<&RunRecord>) =
+        records.iter().partition(|r| r.timestamp < since);
+
+    let mut failures_by_run = BTreeMap::new();
+    let mut series: BTreeMap<(&str, &str), Vec<f64>> = BTreeMap::new();
+    for record in &period {
+        failures_by_run.insert(&record.id, record.failures);
+        // a replicate would weigh its original's numbers twice
+        if record.is_replicate() {
+            continue;
+        }
+        for report in record.reports.iter().filter(|r| r.is_reliable()) {
+            series
+                .entry((&record.scenario, report.framework_name()))
+                .or_default()
+                .push(report.metrics().req_per_sec());
+        }
+    }
+
+    let known: BTreeSet<(&str, &str)> = earlier
+        .iter()
+        .flat_map(|r| {
+            r.reports
+                .iter()
+                .map(|report| (r.scenario.as_str(), report.framework_name()))
+        })
+        .collect();
+
+    // without earlier history every framework would be new
+    let new_frameworks = if earlier.is_empty() {
+        Vec::new()
+    } else {
+        series
+            .keys()
+            .filter(|key| !known.contains(*key))
+            .map(|(scenario, framework)| (scenario.to_string(), framework.to_string()))
+            .collect()
+    };
+
+    let mut movers: Vec<Mover> = series
+        .iter()
+        .filter(|(_, values)| values.len() > 1 && values[0] > 0.0)
+        .map(|((scenario, framework_name), values)| {
+            let (before, after) = (values[0], values[values.len() - 1]);
+            Mover {
+                scenario: scenario.to_string(),
+                framework_name: framework_name.to_string(),
+                before,
+                after,
+                change: (after - before) / before * 100.0,
+            }
+        })
+        .collect();
+    movers.sort_by(|a, b| b.change.abs().total_cmp(&a.change.abs()));
+    movers.truncate(TOP_MOVERS);
+
+    let trends = series
+        .iter()
+        .map(|((scenario, framework), values)| {
+            (
+                scenario.to_string(),
+                framework.to_string(),
+                sparkline(values),
+            )
+        })
+        .collect();
+
+    Digest {
+        runs: failures_by_run.len(),
+        failures: failures_by_run.values().sum(),
+        movers,
+        new_frameworks,
+        trends,
+    }
+}
+
+/// Text sparkline of `values`, readable in mail clients blocking images.
+pub fn sparkline(values: &[f64]) -> String {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+
+    values
+        .iter()
+        .map(|v| {
+            if max > min {
+                SPARKS[((v - min) / (max - min) * (SPARKS.len() - 1) as f64).round() as usize]
+            } else {
+                SPARKS[SPARKS.len() / 2]
+            }
+        })
+        .collect()
+}
+
+impl Digest {
+    pub fn to_html(&self, days: u64) -> String {
+        let mut html = format!(
+            "<h1>bench-bot digest</h1>\n<p>{} runs in the last {} days, {} failed \
+                implementations.</p>\n",
+            self.runs, days, self.failures
+        );
+
+        html.push_str("<h2>Top Movers</h2>\n");
+        if self.movers.is_empty() {
+            html.push_str("<p>-</p>\n");
+        } else {
+            html.push_str(
+                "<table>\n<tr><th>Scenario</th><th>Framework Name</th>\
+                 <th>Req/Sec Before</th><th>Req/Sec After</th><th>Change</th></tr>\n",
+            );
+            for m in &self.movers {
+                html.push_str(&format!(
+                    "<tr><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{:+.1}%</td></tr>\n",
+                    escape(&m.scenario),
+                    escape(&m.framework_name),
+                    m.before,
+                    m.after,
+                    m.change
+                ));
+            }
+            html.push_str("</table>\n");
+        }
+
+        html.push_str("<h2>New Frameworks</h2>\n");
+        if self.new_frameworks.is_empty() {
+            html.push_str("<p>-</p>\n");
+        } else {
+            html.push_str("<ul>\n");
+            for (scenario, framework) in &self.new_frameworks {
+                html.push_str(&format!(
+                    "<li>{} ({})</li>\n",
+                    escape(framework),
+                    escape(scenario)
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        html.push_str("<h2>Trends</h2>\n<table>\n");
+        for (scenario, framework, sparkline) in &self.trends {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td>{}</td><td style=\"font-family: monospace\">{}</td></tr>\n",
+                escape(scenario),
+                escape(framework),
+                sparkline
+            ));
+        }
+        html.push_str("</table>\n");
+
+        html
+    }
+}
+
+/// Mail `html` to the configured recipients, over SMTP with curl. The
+/// password reaches curl as config on its stdin, arguments are visible to
+/// everyone on the machine, so the message is uploaded from a file.
+pub fn send(mail: &DigestMail, subject: &str, html: &str) -> Result<(), String> {
+    let message = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/html; \
+            charset=utf-8\r\n\r\n{}\r\n",
+        mail.from,
+        mail.to.join(", "),
+        subject,
+        html
+    );
+
+    let mut command = Command::new("curl");
+    command.args([
+        "--silent",
+        "--show-error",
+        "--ssl-reqd",
+        "--url",
+        &mail.smtp_url,
+        "--mail-from",
+        &mail.from,
+    ]);
+    for to in &mail.to {
+        command.args(["--mail-rcpt", to]);
+    }
+    let config = match &mail.user {
+        Some(user) => {
+            let password = env::var("BENCHBOT_SMTP_PASSWORD").unwrap_or_default();
+            curl_config("user", &format!("{}:{}", user, password))
+        }
+        None => String::new(),
+    };
+
+    let message_path = env::temp_dir().join(format!("bench-bot-digest-{}.eml", process::id()));
+    fs::write(&message_path, message).map_err(|e| format!("writing {:?}: {}", message_path, e))?;
+
+    let output = command
+        .args(["--config", "-", "--upload-file"])
+        .arg(&message_path)
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            child
+                .stdin
+                .take()
+                .expect("stdin is piped")
+                .write_all(config.as_bytes())?;
+            child.wait_with_output()
+        })
+        .map_err(|e| format!("running curl: {}", e));
+    let _ = fs::remove_file(&message_path);
+
+    let output = output?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "sending mail: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Line of a curl config setting `option` to `value`, quoted so any
+/// password survives.
+fn curl_config(option: &str, value: &str) -> String {
+    format!(
+        "{} = \"{}\"\n",
+        option,
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Report;
+
+    fn record(timestamp: u64, failures: usize, reports: &[(&str, f64)]) -> RunRecord {
+        let reports = reports
+            .iter()
+            .map(|(name, req_per_sec)| {
+                let output = format!(
+                    "    Latency   814.27us  498.47us   8.42ms   69.23%\nRequests/sec: {:.2}\n",
+                    req_per_sec
+                );
+                Report::new(name, 12.0, output.parse().expect("parse metric fail"))
+            })
+            .collect();
+
+        RunRecord {
+            id: timestamp.to_string(),
+            timestamp,
+            commit: None,
+            scenario: "hello-world".to_string(),
+            reports,
+            failures,
+            fingerprint: None,
+            replicate_of: None,
+            machine: None,
+        }
+    }
+
+    #[test]
+    fn quoted_config() {
+        assert_eq!(
+            curl_config("user", r#"bench-bot:p"a\ss"#),
+            "user = \"bench-bot:p\\\"a\\\\ss\"\n"
+        );
+    }
+
+    #[test]
+    fn sparklines() {
+        assert_eq!(sparkline(&[1.0, 2.0, 3.0, 8.0]), "▁▂▃█");
+        assert_eq!(sparkline(&[5.0, 5.0]), "▅▅");
+    }
+
+    #[test]
+    fn weekly() {
+        let given = vec![
+            record(100, 0, &[("axum", 500000.0)]),
+            record(200, 1, &[("axum", 500000.0), ("tide", 80000.0)]),
+            record(300, 0, &[("axum", 450000.0), ("tide", 88000.0)]),
+        ];
+
+        let actual = build(&given, 150);
+
+        assert_eq!(actual.runs, 2);
+        assert_eq!(actual.failures, 1);
+        assert_eq!(
+            actual.new_frameworks,
+            vec![("hello-world".to_string(), "tide".to_string())]
+        );
+        insta::assert_snapshot!(actual.to_html(7));
+    }
+
+    #[test]
+    fn incomplete_is_no_mover() {
+        let mut given = vec![
+            record(100, 0, &[("axum", 500000.0)]),
+            record(200, 0, &[("axum", 50000.0)]),
+        ];
+        given[1].reports = given[1]
+            .reports
+            .drain(..)
+            .map(Report::with_incomplete)
+            .collect();
+
+        let actual = build(&given, 0);
+
+        assert_eq!(actual.movers, Vec::new());
+        assert_eq!(
+            actual.trends,
+            vec![(
+                "hello-world".to_string(),
+                "axum".to_string(),
+                sparkline(&[500000.0])
+            )]
+        );
+    }
+
+    #[test]
+    fn escapes_names() {
+        let given = vec![
+            record(100, 0, &[("a<b>", 500000.0)]),
+            record(200, 0, &[("a<b>", 450000.0)]),
+        ];
+
+        let actual = build(&given, 0).to_html(7);
+
+        assert!(actual.contains("<td>a&lt;b&gt;</td>"));
+        assert!(!actual.contains("a<b>"));
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::Path;
+
+/// Powercap sysfs tree Linux exposes RAPL through, on Intel and AMD alike.
+const POWERCAP_DIR: &str = "/sys/class/powercap";
+
+/// Energy counter of one RAPL package.
+#[derive(Debug)]
+pub struct Counter {
+    energy_uj: u64,
+    /// Value the counter wraps around at.
+    max_energy_range_uj: u64,
+}
+
+/// Counters of every package, `None` without RAPL or without permission to
+/// read it, `energy_uj` is only readable by root on recent kernels.
+pub fn sample() -> Option<Vec<Counter>> {
+    let mut counters = Vec::new();
+
+    for entry in fs::read_dir(POWERCAP_DIR).ok()? {
+        let entry = entry.ok()?;
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        // packages only, their subzones (`intel-rapl:0:0`) are part of them
+        if name.starts_with("intel-rapl:") && name.matches(':').count() == 1 {
+            counters.push(read_counter(&entry.path())?);
+        }
+    }
+
+    Some(counters).filter(|counters| !counters.is_empty())
+}
+
+fn read_counter(zone: &Path) -> Option<Counter> {
+    let read = |file: &str| {
+        fs::read_to_string(zone.join(file))
+            .ok()?
+            .trim()
+            .parse()
+            .ok()
+    };
+
+    Some(Counter {
+        energy_uj: read("energy_uj")?,
+        max_energy_range_uj: read("max_energy_range_uj")?,
+    })
+}
+
+/// Joules consumed by all packages between two samples.
+pub fn joules(started: &[Counter], ended: &[Counter]) -> f64 {
+    let micro_joules: u64 = started
+        .iter()
+        .zip(ended)
+        .map(|(started, ended)| {
+            if ended.energy_uj >= started.energy_uj {
+                ended.energy_uj - started.energy_uj
+            } else {
+                ended.max_energy_range_uj - started.energy_uj + ended.energy_uj
+            }
+        })
+        .sum();
+
+    micro_joules as f64 / 1e6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counter(energy_uj: u64) -> Counter {
+        Counter {
+            energy_uj,
+            max_energy_range_uj: 262_143_328_850,
+        }
+    }
+
+    #[test]
+    fn consumed() {
+        assert_eq!(
+            joules(
+                &[counter(1_000_000), counter(5)],
+                &[counter(3_500_000), counter(1_000_005)]
+            ),
+            3.5
+        );
+    }
+
+    #[test]
+    fn wraps_around() {
+        assert_eq!(
+            joules(&[counter(262_142_328_850)], &[counter(500_000)]),
+            1.5
+        );
+    }
+}
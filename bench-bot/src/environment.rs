@@ -0,0 +1,218 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::process::Command;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sysinfo::{CpuExt, System, SystemExt};
+
+/// Software and hardware a run was measured on, keyed by e.g. `rustc`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Environment(BTreeMap<String, String>);
+
+/// Keys of [`Environment::machine_profile`].
+const MACHINE_PROFILE: [&str; 8] = [
+    "cpu",
+    "cpus",
+    "os",
+    "kernel",
+    "rustc",
+    "cargo",
+    "wrk",
+    "cpu governor",
+];
+
+/// A key whose value differs between two environments.
+#[derive(Debug, PartialEq)]
+pub struct Difference {
+    pub key: String,
+    pub baseline: Option<String>,
+    pub current: Option<String>,
+}
+
+impl Environment {
+    pub fn capture(sys: &System) -> Self {
+        let mut env = BTreeMap::new();
+
+        env.insert("cpu".to_string(), sys.global_cpu_info().brand().to_string());
+        env.insert("cpus".to_string(), sys.cpus().len().to_string());
+
+        let mut insert = |key: &str, value: Option<String>| {
+            if let Some(value) = value.filter(|v| !v.is_empty()) {
+                env.insert(key.to_string(), value);
+            }
+        };
+
+        insert("os", sys.long_os_version());
+        insert("kernel", sys.kernel_version());
+        insert("toolchain", env::var("RUSTUP_TOOLCHAIN").ok());
+        insert("rustc", first_line("rustc", &["--version"]));
+        insert("cargo", first_line("cargo", &["--version"]));
+        insert("wrk", first_line("wrk", &["--version"]));
+        insert(
+            "cpu governor",
+            fs::read_to_string("/sys/devices/system/cpu/cpu0/cpufreq/scaling_governor")
+                .ok()
+                .map(|s| s.trim().to_string()),
+        );
+
+        Environment(env)
+    }
+
+    /// Keys that are missing on either side or have different values.
+    pub fn diff(&self, current: &Environment) -> Vec<Difference> {
+        let mut keys: Vec<&String> = self.0.keys().chain(current.0.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        keys.into_iter()
+            .filter(|key| self.0.get(*key) != current.0.get(*key))
+            .map(|key| Difference {
+                key: key.clone(),
+                baseline: self.0.get(key).cloned(),
+                current: current.0.get(key).cloned(),
+            })
+            .collect()
+    }
+
+    /// (key, value) of every entry, by key.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn to_markdown(&self) -> String {
+        self.0
+            .iter()
+            .map(|(key, value)| format!("- {}: {}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Environment listed below the `# Environment` heading of `markdown`,
+    /// as [`Environment::to_markdown`] writes it.
+    pub fn from_markdown(markdown: &str) -> Self {
+        let env = markdown
+            .lines()
+            .skip_while(|line| line.trim() != "# Environment")
+            .skip(1)
+            .map(str::trim)
+            .skip_while(|line| line.is_empty())
+            .take_while(|line| line.starts_with("- "))
+            .filter_map(|line| line[2..].split_once(": "))
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        Environment(env)
+    }
+
+    /// The hardware and software versions only, nothing naming the machine
+    /// or its owner, for sharing a run with others.
+    pub fn machine_profile(&self) -> Self {
+        Environment(
+            self.0
+                .iter()
+                .filter(|(key, _)| MACHINE_PROFILE.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        )
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Markdown table listing `differences`.
+pub fn differences_table(differences: &[Difference]) -> String {
+    let mut res = String::from("| Key | Baseline | Current |\n|---|---|---|");
+
+    for d in differences {
+        res.push_str(&format!(
+            "\n|{}|{}|{}|",
+            d.key,
+            d.baseline.as_deref().unwrap_or("-"),
+            d.current.as_deref().unwrap_or("-")
+        ));
+    }
+
+    res
+}
+
+// wrk prints its version and exits with an error, so the status is ignored
+fn first_line(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    stdout.lines().next().map(|line| line.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn environment(entries: &[(&str, &str)]) -> Environment {
+        Environment(
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn diff() {
+        let baseline = environment(&[
+            ("cpu", "AMD EPYC 7B13"),
+            ("kernel", "6.1.0"),
+            ("rustc", "rustc 1.70.0"),
+            ("cpu governor", "performance"),
+        ]);
+        let current = environment(&[
+            ("cpu", "AMD EPYC 7B13"),
+            ("kernel", "6.1.0"),
+            ("rustc", "rustc 1.71.0"),
+        ]);
+
+        let actual = baseline.diff(&current);
+
+        assert_eq!(actual.len(), 2);
+        assert!(baseline.diff(&baseline).is_empty());
+        insta::assert_snapshot!(differences_table(&actual));
+    }
+
+    #[test]
+    fn machine_profile() {
+        let given = environment(&[
+            ("cpu", "AMD EPYC 7B13"),
+            ("toolchain", "/home/alice/toolchains/nightly"),
+            ("hostname", "alice-desktop"),
+        ]);
+
+        assert_eq!(
+            given.machine_profile(),
+            environment(&[("cpu", "AMD EPYC 7B13")])
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        let given = environment(&[
+            ("cpu", "AMD EPYC 7B13"),
+            ("rustc", "rustc 1.64.0 (a55dd71d5 2022-09-19)"),
+        ]);
+
+        let published = format!(
+            "# Environment\n\n{}\n\n# Benchmark\n\n- not: environment",
+            given.to_markdown()
+        );
+        assert_eq!(Environment::from_markdown(&published), given);
+        assert!(Environment::from_markdown("# Benchmark").is_empty());
+    }
+}
@@ -0,0 +1,169 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::TcpStream;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::manifest::Benchmark;
+use crate::report::FlatReport;
+
+/// Something a run did, see [`EventStream`].
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum Event {
+    RunStarted {
+        frameworks: Vec<String>,
+        /// Benchmarks in the order they run, `<scenario>/<framework>`.
+        benchmarks: Vec<String>,
+        rounds: usize,
+    },
+    Built {
+        framework: String,
+        success: bool,
+        seconds: f64,
+    },
+    Measured {
+        scenario: String,
+        report: Box<FlatReport>,
+    },
+    Anomaly {
+        scenario: String,
+        framework: String,
+        kind: Anomaly,
+        message: String,
+    },
+    RunFinished {
+        failures: usize,
+        /// Frameworks regressed against the baseline.
+        regressions: Vec<String>,
+    },
+}
+
+impl Event {
+    pub fn anomaly(benchmark: &Benchmark, kind: Anomaly, message: impl Into<String>) -> Self {
+        Event::Anomaly {
+            scenario: benchmark.scenario.clone(),
+            framework: benchmark.framework_name(),
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+/// What went wrong with a benchmark, or looks off about its result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Anomaly {
+    PortInUse,
+    ServerExited,
+    LoadToolFailed,
+    Unparsable,
+    Incomplete,
+    Pathological,
+    SkewedThreads,
+}
+
+/// A line of the event stream.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct EventLine {
+    /// Unix timestamp in seconds.
+    pub time: u64,
+    #[serde(flatten)]
+    pub event: Event,
+}
+
+/// JSON Lines of [`Event`]s written as the run progresses, to a file or a
+/// `tcp://host:port` socket. Observers get the run without polling its
+/// status.
+pub struct EventStream {
+    sink: Option<Box<dyn Write>>,
+}
+
+impl EventStream {
+    /// A stream writing nowhere, when no observer asked for events.
+    pub fn none() -> Self {
+        Self { sink: None }
+    }
+
+    /// A stream appending to the file at `target`, or connected to it when it
+    /// is a `tcp://` address.
+    pub fn open(target: &str) -> Result<Self, String> {
+        let sink: Box<dyn Write> = match target.strip_prefix("tcp://") {
+            Some(addr) => Box::new(
+                TcpStream::connect(addr).map_err(|e| format!("connecting to {}: {}", addr, e))?,
+            ),
+            None => Box::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(target)
+                    .map_err(|e| format!("opening {}: {}", target, e))?,
+            ),
+        };
+
+        Ok(Self { sink: Some(sink) })
+    }
+
+    /// Write `event`. Observers are informational, a run keeps going without
+    /// them once writing fails.
+    pub fn emit(&mut self, event: Event) {
+        let Some(sink) = &mut self.sink else {
+            return;
+        };
+        let line = EventLine {
+            time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            event,
+        };
+        let mut content = serde_json::to_vec(&line).expect("events always serialize");
+        content.push(b'\n');
+
+        if let Err(e) = sink.write_all(&content).and_then(|_| sink.flush()) {
+            log::warn!("Writing events failed, not writing any more: {}.", e);
+            self.sink = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn lines() {
+        let path = env::temp_dir().join("bench-bot-test-events.jsonl");
+        let _ = fs::remove_file(&path);
+
+        let mut events = EventStream::open(path.to_str().unwrap()).expect("open events fail");
+        events.emit(Event::Built {
+            framework: "axum".to_string(),
+            success: true,
+            seconds: 41.5,
+        });
+        events.emit(Event::Anomaly {
+            scenario: "hello-world".to_string(),
+            framework: "axum".to_string(),
+            kind: Anomaly::PortInUse,
+            message: "port 3000 in use".to_string(),
+        });
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "built");
+        assert_eq!(lines[0]["framework"], "axum");
+        assert!(lines[0]["time"].as_u64().unwrap() > 0);
+        assert_eq!(lines[1]["event"], "anomaly");
+        assert_eq!(lines[1]["kind"], "port-in-use");
+    }
+}
@@ -0,0 +1,200 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+
+use sysinfo::{Pid, PidExt, ProcessExt, Signal, System, SystemExt};
+
+/// Folded stacks of `framework` in the detail directory of `scenario`, next
+/// to the results file `results` of that run.
+pub fn folded_path(results: &Path, scenario: &str, framework: &str) -> PathBuf {
+    results
+        .with_file_name(scenario)
+        .join(format!("{}.folded", framework))
+}
+
+/// `perf record` sampling the stacks of `pids` until [`Recording::finish`].
+/// Dropping it stops sampling and removes the samples.
+pub struct Recording {
+    child: Child,
+    data: PathBuf,
+    folded: PathBuf,
+}
+
+/// Start sampling `pids`, the server and the processes below it, into
+/// `data`, to be written as folded stacks to `folded`. Stacks an earlier
+/// run left there are removed, they'd pass for this run's.
+pub fn record(pids: &[u32], data: &Path, folded: &Path) -> Result<Recording, String> {
+    let _ = fs::remove_file(folded);
+
+    let pids: Vec<String> = pids.iter().map(u32::to_string).collect();
+    let child = Command::new("perf")
+        .args([
+            "record",
+            "-F",
+            "99",
+            "-g",
+            "-q",
+            "-p",
+            &pids.join(","),
+            "-o",
+        ])
+        .arg(data)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("starting perf record: {}", e))?;
+
+    Ok(Recording {
+        child,
+        data: data.to_path_buf(),
+        folded: folded.to_path_buf(),
+    })
+}
+
+impl Recording {
+    /// Stop sampling and write the samples as folded stacks.
+    pub fn finish(mut self) -> Result<(), String> {
+        // perf only writes a readable file when interrupted
+        let mut sys = System::new();
+        let pid = Pid::from_u32(self.child.id());
+        sys.refresh_process(pid);
+        match sys
+            .process(pid)
+            .and_then(|process| process.kill_with(Signal::Interrupt))
+        {
+            Some(true) => {}
+            _ => return Err("stopping perf record: it isn't running".to_string()),
+        }
+        self.child
+            .wait()
+            .map_err(|e| format!("stopping perf record: {}", e))?;
+
+        let script = Command::new("perf")
+            .args(["script", "-i"])
+            .arg(&self.data)
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|e| format!("running perf script: {}", e))?;
+        if !script.status.success() {
+            return Err(format!("perf script exited with {}", script.status));
+        }
+
+        let stacks = pipe(Command::new("inferno-collapse-perf"), &script.stdout)?;
+        fs::write(&self.folded, stacks).map_err(|e| format!("writing {:?}: {}", self.folded, e))
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        // a no-op when finished, perf has exited then
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.data);
+    }
+}
+
+/// Render the folded stacks `folded` as a flamegraph to `svg`.
+pub fn render(folded: &Path, title: &str, svg: &Path) -> Result<(), String> {
+    let stacks = fs::read(folded).map_err(|e| format!("reading {:?}: {}", folded, e))?;
+    let mut flamegraph = Command::new("inferno-flamegraph");
+    flamegraph.args(["--title", title]);
+
+    let content = pipe(flamegraph, &stacks)?;
+    fs::write(svg, content).map_err(|e| format!("writing {:?}: {}", svg, e))
+}
+
+/// Render how the stacks of `current` differ from the ones of `baseline` to
+/// `svg`, red frames grew and blue ones shrank.
+pub fn differential(
+    baseline: &Path,
+    current: &Path,
+    title: &str,
+    svg: &Path,
+) -> Result<(), String> {
+    // normalized, a run sampled for longer would grow every frame
+    let diff = Command::new("inferno-diff-folded")
+        .arg("-n")
+        .arg(baseline)
+        .arg(current)
+        .output()
+        .map_err(|e| format!("running inferno-diff-folded: {}", e))?;
+    if !diff.status.success() {
+        return Err(format!("inferno-diff-folded exited with {}", diff.status));
+    }
+
+    let mut flamegraph = Command::new("inferno-flamegraph");
+    flamegraph.args(["--title", title]);
+
+    let content = pipe(flamegraph, &diff.stdout)?;
+    fs::write(svg, content).map_err(|e| format!("writing {:?}: {}", svg, e))
+}
+
+/// Output of `command` given `input` on stdin.
+fn pipe(mut command: Command, input: &[u8]) -> Result<Vec<u8>, String> {
+    let name = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("running {}: {}", name, e))?;
+
+    // written from here while the output is read, large inputs would fill
+    // both pipes otherwise
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_vec();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("running {}: {}", name, e))?;
+    writer
+        .join()
+        .expect("writing stdin panicked")
+        .map_err(|e| format!("writing to {}: {}", name, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", name, output.status));
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn baseline_stacks() {
+        assert_eq!(
+            folded_path(
+                Path::new("results/2022-08-01/hello-world.json"),
+                "hello-world",
+                "axum"
+            ),
+            PathBuf::from("results/2022-08-01/hello-world/axum.folded")
+        );
+    }
+
+    #[test]
+    fn failed_recording_leaves_no_samples() {
+        let dir = env::temp_dir().join("bench-bot-test-flamegraph");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let (data, folded) = (dir.join("axum.perf.data"), dir.join("axum.folded"));
+        fs::write(&data, "not perf samples").unwrap();
+
+        // sleep stands in for perf, interrupted like it without writing samples
+        let recording = Recording {
+            child: Command::new("sleep").arg("10").spawn().unwrap(),
+            data: data.clone(),
+            folded: folded.clone(),
+        };
+
+        assert!(recording.finish().is_err());
+        assert!(!data.exists());
+        assert!(!folded.exists());
+    }
+}
@@ -0,0 +1,287 @@
+use std::time::Duration;
+
+use clap::ArgEnum;
+
+use crate::units;
+
+/// How values carrying a unit are displayed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum UnitDisplay {
+    /// Fixed base units: milliseconds, megabytes and plain counts.
+    Raw,
+    /// Scaled to the most readable unit, e.g. `812µs`, `17.28M`, `1.2GB`.
+    Humanized,
+}
+
+/// Number formatting used by a renderer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NumberFormat {
+    /// Decimal places, `None` keeps the natural precision of each metric.
+    pub decimals: Option<usize>,
+    pub units: UnitDisplay,
+    pub thousands_separator: Option<char>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimals: None,
+            units: UnitDisplay::Raw,
+            thousands_separator: None,
+        }
+    }
+}
+
+impl NumberFormat {
+    /// A latency, in the unit keeping three significant digits when
+    /// humanized.
+    pub fn latency(&self, latency: Duration) -> String {
+        let ns = latency.as_nanos() as f64;
+
+        if self.units == UnitDisplay::Raw {
+            return format!("{}ms", self.number(ns / 1e6, 4));
+        }
+
+        let (divisor, unit) = latency_unit(ns);
+        let value = ns / divisor;
+        let decimals = match value {
+            value if value >= 99.95 => 0,
+            value if value >= 9.995 => 1,
+            _ => 2,
+        };
+
+        format!("{}{}", self.number(value, decimals), unit)
+    }
+
+    /// Memory given in megabytes.
+    pub fn memory(&self, mb: f64) -> String {
+        match self.units {
+            UnitDisplay::Humanized if mb >= 1024.0 => format!("{}GB", self.number(mb / 1024.0, 1)),
+            UnitDisplay::Humanized if mb < 1.0 => format!("{}KB", self.number(mb * 1024.0, 1)),
+            _ => format!("{}MB", self.number(mb, 1)),
+        }
+    }
+
+    /// CPU time given in microseconds.
+    pub fn cpu_time(&self, us: f64) -> String {
+        match self.units {
+            UnitDisplay::Humanized if us >= 1000.0 => format!("{}ms", self.number(us / 1000.0, 2)),
+            _ => format!("{}us", self.number(us, 2)),
+        }
+    }
+
+    /// A count such as total requests.
+    pub fn count(&self, count: u64) -> String {
+        self.scaled(count as f64, 0)
+    }
+
+    /// A per second rate.
+    pub fn rate(&self, rate: f64) -> String {
+        self.scaled(rate, 2)
+    }
+
+    /// A byte size, in the binary units wrk prints whatever the display.
+    pub fn bytes(&self, bytes: f64) -> String {
+        let (value, unit) = units::scale_bytes(bytes);
+
+        format!("{}{}", self.number(value, 2), unit)
+    }
+
+    /// A dimensionless ratio.
+    pub fn ratio(&self, ratio: f64) -> String {
+        self.number(ratio, 2)
+    }
+
+    /// A rate with its uncertainty, scaled like [`NumberFormat::rate`], e.g.
+    /// `574k ±6k` humanized.
+    pub fn uncertain_rate(&self, rate: f64, half_width: f64) -> String {
+        let (divisor, suffix) = match self.units {
+            UnitDisplay::Humanized => magnitude(rate),
+            UnitDisplay::Raw => (1.0, ""),
+        };
+
+        self.uncertain(rate / divisor, half_width / divisor, suffix)
+    }
+
+    /// A latency in milliseconds with its uncertainty, scaled like
+    /// [`NumberFormat::latency`], e.g. `2.6ms ±0.1ms`.
+    pub fn uncertain_latency(&self, ms: f64, half_width: f64) -> String {
+        let (divisor, unit) = match self.units {
+            UnitDisplay::Humanized => latency_unit(ms * 1e6),
+            UnitDisplay::Raw => (1e6, "ms"),
+        };
+
+        self.uncertain(ms * 1e6 / divisor, half_width * 1e6 / divisor, unit)
+    }
+
+    // the first significant digit of the uncertainty decides the precision
+    // of both, unless decimals are given
+    fn uncertain(&self, value: f64, half_width: f64, unit: &str) -> String {
+        let decimals = if half_width > 0.0 {
+            (-half_width.log10().floor()).max(0.0) as usize
+        } else {
+            2
+        };
+
+        format!(
+            "{}{} ±{}{}",
+            self.number(value, decimals),
+            unit,
+            self.number(half_width, decimals),
+            unit
+        )
+    }
+
+    fn scaled(&self, value: f64, natural_decimals: usize) -> String {
+        match self.units {
+            UnitDisplay::Humanized if value >= 1e3 => {
+                let (divisor, suffix) = magnitude(value);
+                format!("{}{}", self.number(value / divisor, 2), suffix)
+            }
+            _ => self.number(value, natural_decimals),
+        }
+    }
+
+    fn number(&self, value: f64, natural_decimals: usize) -> String {
+        let formatted = format!("{:.*}", self.decimals.unwrap_or(natural_decimals), value);
+
+        let Some(separator) = self.thousands_separator else {
+            return formatted;
+        };
+
+        let (integer, fraction) = match formatted.find('.') {
+            Some(dot) => formatted.split_at(dot),
+            None => (formatted.as_str(), ""),
+        };
+        let (sign, digits) = match integer.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", integer),
+        };
+
+        let mut grouped = String::with_capacity(formatted.len() + digits.len() / 3);
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push(separator);
+            }
+            grouped.push(digit);
+        }
+
+        format!("{}{}{}", sign, grouped, fraction)
+    }
+}
+
+/// Divisor and suffix of the magnitude of a count or rate, e.g. `k`.
+fn magnitude(value: f64) -> (f64, &'static str) {
+    match value {
+        v if v >= 1e9 => (1e9, "G"),
+        v if v >= 1e6 => (1e6, "M"),
+        v if v >= 1e3 => (1e3, "k"),
+        _ => (1.0, ""),
+    }
+}
+
+/// Divisor and unit of the most readable unit of a latency in nanoseconds.
+fn latency_unit(ns: f64) -> (f64, &'static str) {
+    // rounding may carry over into the next unit, e.g. 999.7us
+    match ns {
+        ns if ns < 999.5 => (1.0, "ns"),
+        ns if ns < 999_500.0 => (1e3, "µs"),
+        ns if ns < 999_500_000.0 => (1e6, "ms"),
+        _ => (1e9, "s"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn humanized() -> NumberFormat {
+        NumberFormat {
+            units: UnitDisplay::Humanized,
+            ..NumberFormat::default()
+        }
+    }
+
+    #[test]
+    fn raw_keeps_natural_precision() {
+        let format = NumberFormat::default();
+
+        assert_eq!(format.latency(Duration::from_nanos(814_270)), "0.8143ms");
+        assert_eq!(format.memory(13.73), "13.7MB");
+        assert_eq!(format.count(17275966), "17275966");
+        assert_eq!(format.rate(574184.09), "574184.09");
+        assert_eq!(format.ratio(0.0731), "0.07");
+        assert_eq!(format.cpu_time(12.345), "12.35us");
+        assert_eq!(format.bytes(69_478_645.0), "66.26MB");
+    }
+
+    #[test]
+    fn decimals_override() {
+        let format = NumberFormat {
+            decimals: Some(1),
+            ..NumberFormat::default()
+        };
+
+        assert_eq!(format.latency(Duration::from_nanos(814_270)), "0.8ms");
+        assert_eq!(format.rate(574184.09), "574184.1");
+    }
+
+    #[test]
+    fn humanized_units() {
+        let format = humanized();
+
+        assert_eq!(format.latency(Duration::from_nanos(814_270)), "814µs");
+        assert_eq!(format.latency(Duration::from_micros(8_420)), "8.42ms");
+        assert_eq!(format.latency(Duration::from_micros(42_300)), "42.3ms");
+        assert_eq!(format.latency(Duration::from_millis(1_520)), "1.52s");
+        assert_eq!(format.latency(Duration::from_nanos(999_700)), "1.00ms");
+        assert_eq!(format.latency(Duration::from_nanos(950)), "950ns");
+        assert_eq!(format.memory(2048.0), "2.0GB");
+        assert_eq!(format.memory(0.5), "512.0KB");
+        assert_eq!(format.cpu_time(1520.0), "1.52ms");
+        assert_eq!(format.count(17275966), "17.28M");
+        assert_eq!(format.rate(574184.09), "574.18k");
+        assert_eq!(format.rate(999.5), "999.50");
+    }
+
+    #[test]
+    fn uncertainty() {
+        let format = humanized();
+
+        assert_eq!(format.uncertain_rate(574184.09, 6012.3), "574k ±6k");
+        assert_eq!(format.uncertain_rate(574184.09, 612.5), "574.2k ±0.6k");
+        assert_eq!(format.uncertain_rate(10747.7, 0.0), "10.75k ±0.00k");
+        assert_eq!(format.uncertain_latency(2.56, 0.13), "2.6ms ±0.1ms");
+        assert_eq!(format.uncertain_latency(0.8143, 0.0212), "814µs ±21µs");
+    }
+
+    #[test]
+    fn uncertainty_follows_the_format() {
+        let raw = NumberFormat::default();
+        assert_eq!(raw.uncertain_rate(574184.09, 6012.3), "574184 ±6012");
+        assert_eq!(raw.uncertain_latency(0.8143, 0.0212), "0.81ms ±0.02ms");
+
+        let format = NumberFormat {
+            decimals: Some(1),
+            thousands_separator: Some(','),
+            ..NumberFormat::default()
+        };
+        assert_eq!(
+            format.uncertain_rate(574184.09, 6012.3),
+            "574,184.1 ±6,012.3"
+        );
+    }
+
+    #[test]
+    fn thousands_separator() {
+        let format = NumberFormat {
+            thousands_separator: Some(','),
+            ..NumberFormat::default()
+        };
+
+        assert_eq!(format.count(17275966), "17,275,966");
+        assert_eq!(format.rate(574184.09), "574,184.09");
+        assert_eq!(format.rate(999.0), "999.00");
+        assert_eq!(format.memory(1024.25), "1,024.2MB");
+    }
+}
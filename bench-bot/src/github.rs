@@ -0,0 +1,223 @@
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::artifacts::{self, Link};
+use crate::compare::{self, Regression};
+
+/// Label of issues about regressed frameworks.
+pub const REGRESSION_LABEL: &str = "regression";
+
+/// Label of issues about implementations that fail to build or crash.
+pub const BROKEN_LABEL: &str = "broken";
+
+/// Lines of a failure log quoted in an issue.
+const LOG_EXCERPT_LINES: usize = 40;
+
+/// An implementation that failed to build or crashed under load.
+#[derive(Debug)]
+pub struct Breakage {
+    pub framework_name: String,
+    /// `build` or `benchmark`.
+    pub stage: &'static str,
+    pub log: String,
+}
+
+#[derive(Deserialize)]
+struct IssueRef {
+    number: u64,
+    title: String,
+}
+
+/// Comment on the open issue titled `title`, or open one if there is none.
+/// Talks to GitHub through the `gh` CLI of the current checkout.
+pub fn upsert_issue(title: &str, body: &str, label: &str) -> Result<(), String> {
+    match find_open_issue(title)? {
+        Some(number) => gh(&["issue", "comment", &number.to_string(), "--body", body]),
+        None => gh(&[
+            "issue", "create", "--title", title, "--body", body, "--label", label,
+        ]),
+    }
+}
+
+/// Close the open issue titled `title` with `comment`, if there is one.
+pub fn close_issue(title: &str, comment: &str) -> Result<bool, String> {
+    match find_open_issue(title)? {
+        Some(number) => {
+            gh(&["issue", "close", &number.to_string(), "--comment", comment]).map(|_| true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Comment `body` on the pull request `number`.
+pub fn comment_pr(number: u64, body: &str) -> Result<(), String> {
+    gh(&["pr", "comment", &number.to_string(), "--body", body])
+}
+
+fn find_open_issue(title: &str) -> Result<Option<u64>, String> {
+    let output = Command::new("gh")
+        .args([
+            "issue",
+            "list",
+            "--state",
+            "open",
+            "--search",
+            &format!("in:title {}", title),
+        ])
+        .args(["--json", "number,title"])
+        .output()
+        .map_err(|e| format!("running gh: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "listing issues: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let issues: Vec<IssueRef> =
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("parsing issue list: {}", e))?;
+
+    // the search is fuzzy, only an exact title counts
+    Ok(issues
+        .into_iter()
+        .find(|issue| issue.title == title)
+        .map(|issue| issue.number))
+}
+
+fn gh(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("gh")
+        .args(args)
+        .output()
+        .map_err(|e| format!("running gh: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "gh {}: {}",
+            args[..2].join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+pub fn regression_title(framework_name: &str) -> String {
+    format!("Performance regression: {}", framework_name)
+}
+
+/// Issue body mentioning `maintainers`, with the regressed metrics and the
+/// comparison table of the run.
+pub fn regression_body(
+    maintainers: &[String],
+    regressions: &[Regression],
+    comparison: &str,
+) -> String {
+    let mut body = String::new();
+
+    if !maintainers.is_empty() {
+        let mentions: Vec<String> = maintainers
+            .iter()
+            .map(|m| format!("@{}", m.trim_start_matches('@')))
+            .collect();
+        body.push_str(&format!("cc {}\n\n", mentions.join(" ")));
+    }
+
+    body.push_str("A scheduled benchmark run found regressions against the baseline.\n\n");
+    body.push_str(&compare::regressions_table(regressions));
+    body.push_str("\n\n<details><summary>Comparison</summary>\n\n");
+    body.push_str(comparison);
+    body.push_str("\n\n</details>\n");
+
+    body
+}
+
+/// Pull request comment with the comparison table of the run and links to
+/// its uploaded artifacts.
+pub fn pr_comment_body(comparison: &str, links: &[Link]) -> String {
+    let mut body = format!("Benchmarked by bench-bot.\n\n{}\n", comparison);
+
+    if !links.is_empty() {
+        body.push_str("\n### Artifacts\n\n");
+        body.push_str(&artifacts::links_markdown(links));
+        body.push('\n');
+    }
+
+    body
+}
+
+pub fn broken_title(framework_name: &str) -> String {
+    format!("Broken implementation: {}", framework_name)
+}
+
+/// Issue body with the tail of the failure log and the environment.
+pub fn broken_body(breakage: &Breakage, environment: &str) -> String {
+    let lines: Vec<&str> = breakage.log.trim_end().lines().collect();
+    let excerpt = lines[lines.len().saturating_sub(LOG_EXCERPT_LINES)..].join("\n");
+
+    format!(
+        "The {} failed on a scheduled run, this issue is closed automatically once it passes \
+            again.\n\n```\n{}\n```\n\n\
+            <details><summary>Environment</summary>\n\n{}\n\n</details>\n",
+        breakage.stage, excerpt, environment
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ThresholdMetric;
+
+    #[test]
+    fn regression_issue() {
+        let regression = Regression {
+            scenario: None,
+            framework_name: "axum".to_string(),
+            metric: ThresholdMetric::Rps,
+            baseline: 100000.0,
+            current: 90000.0,
+            change: 10.0,
+            threshold: 3.0,
+        };
+        let comparison = "| Framework Name | Request.Req/Sec |\n|---|---|\n|axum|90000.00|";
+
+        insta::assert_snapshot!(regression_body(
+            &["davidpdrsn".to_string(), "@jplatte".to_string()],
+            &[regression],
+            comparison
+        ));
+    }
+
+    #[test]
+    fn pr_comment() {
+        let links = vec![Link {
+            name: "hello-world.html".to_string(),
+            url: "https://bench-results.s3.amazonaws.com/runs/1700000000/\
+                hello-world.html?X-Amz-Expires=604800"
+                .to_string(),
+        }];
+        let comparison = "| Framework Name | Request.Req/Sec |\n|---|---|\n|axum|90000.00|";
+
+        assert!(!pr_comment_body(comparison, &[]).contains("Artifacts"));
+        insta::assert_snapshot!(pr_comment_body(comparison, &links));
+    }
+
+    #[test]
+    fn broken_issue() {
+        let log = (1..=45)
+            .map(|n| format!("error line {}", n))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let breakage = Breakage {
+            framework_name: "tide".to_string(),
+            stage: "build",
+            log,
+        };
+
+        insta::assert_snapshot!(broken_body(
+            &breakage,
+            "- cpu: AMD EPYC 7B13\n- rustc: rustc 1.75.0"
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+use clap::ArgEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::environment::Environment;
+use crate::report::Report;
+
+/// Handling of a run with the fingerprint of a stored one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum DuplicateRuns {
+    /// Leave it out of the history.
+    Skip,
+    /// Store it linked to the original.
+    Replicate,
+}
+
+/// One scenario of one run, stored as a line of `history.jsonl`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunRecord {
+    pub id: String,
+    /// Unix timestamp in seconds.
+    pub timestamp: u64,
+    /// Commit of the benchmarked workspace, if it is a git checkout.
+    pub commit: Option<String>,
+    pub scenario: String,
+    pub reports: Vec<Report>,
+    /// Implementations of the whole run that failed to build or benchmark.
+    #[serde(default)]
+    pub failures: usize,
+    /// Hash of what decides the outcome of a run, see [`fingerprint`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    /// Id of the earlier run this one repeated exactly. Replicates are kept
+    /// for the noise they show but left out of trends.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replicate_of: Option<String>,
+    /// [`Environment::machine_profile`] of the machine the run was measured
+    /// on, unknown for runs of earlier versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine: Option<Environment>,
+}
+
+impl RunRecord {
+    pub fn is_replicate(&self) -> bool {
+        self.replicate_of.is_some()
+    }
+}
+
+/// Hash of the commit and its uncommitted `changes`, environment, config and
+/// seed of a run. Runs of an unknown commit have none, their code may differ.
+pub fn fingerprint(
+    commit: Option<&str>,
+    changes: &str,
+    environment: &Environment,
+    config: &str,
+    seed: u64,
+) -> Option<String> {
+    let environment = serde_json::to_string(environment).ok()?;
+    let seed = seed.to_string();
+
+    Some(hash(&[commit?, changes, &environment, config, &seed]))
+}
+
+/// Hash of `parts` that, unlike `DefaultHasher`, is stable across Rust
+/// releases, for ids kept in the history.
+pub fn hash(parts: &[&str]) -> String {
+    // FNV-1a
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for part in parts {
+        for byte in part.bytes().chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// The first run of `scenario` in `records` with `fingerprint`.
+pub fn find_original<'a>(
+    records: &'a [RunRecord],
+    fingerprint: &str,
+    scenario: &str,
+) -> Option<&'a RunRecord> {
+    records
+        .iter()
+        .filter(|r| !r.is_replicate() && r.scenario == scenario)
+        .find(|r| r.fingerprint.as_deref() == Some(fingerprint))
+}
+
+/// The reports of `reports` measuring `scenario`, those of a record.
+pub fn of_scenario(reports: &[Report], scenario: &str) -> Vec<Report> {
+    reports
+        .iter()
+        .filter(|r| r.scenario() == Some(scenario))
+        .cloned()
+        .collect()
+}
+
+pub fn append(path: &Path, record: &RunRecord) -> Result<(), String> {
+    let mut line = serde_json::to_string(record).map_err(|e| e.to_string())?;
+    line.push('\n');
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("writing {:?}: {}", path, e))
+}
+
+/// All records in chronological order.
+pub fn load(path: &Path) -> Result<Vec<RunRecord>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("reading {:?}: {}", path, e))?;
+
+    let mut records = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| format!("parsing {:?} line {}: {}", path, index + 1, e))
+        })
+        .collect::<Result<Vec<RunRecord>, _>>()?;
+
+    records.sort_by_key(|r| r.timestamp);
+
+    Ok(records)
+}
+
+/// Report frameworks known by an alias in `aliases` under their current
+/// name, so trends see one series across a rename.
+pub fn stitch(records: &mut [RunRecord], aliases: &BTreeMap<String, String>) {
+    for report in records
+        .iter_mut()
+        .flat_map(|record| record.reports.iter_mut())
+    {
+        if let Some(name) = aliases.get(report.framework_name()) {
+            report.rename(name);
+        }
+    }
+}
+
+/// Changed and untracked files below `dir` of its git checkout and the diff
+/// of the changed ones, empty when they're clean or it's no checkout. Files
+/// elsewhere of the checkout, e.g. results, don't change what's measured.
+pub fn uncommitted_changes(dir: &Path) -> String {
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+            .unwrap_or_default()
+    };
+
+    let status = git(&["status", "--porcelain", "--", "."]);
+    if status.is_empty() {
+        return status;
+    }
+
+    status + &git(&["diff", "HEAD", "--", "."])
+}
+
+/// Short commit hash of the git checkout at `dir`.
+pub fn current_commit(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::process::Stdio;
+
+    use super::*;
+
+    fn record(id: &str, fingerprint: &str, replicate_of: Option<&str>) -> RunRecord {
+        RunRecord {
+            id: id.to_string(),
+            timestamp: id.parse().unwrap(),
+            commit: Some("9f3c1a2".to_string()),
+            scenario: "hello-world".to_string(),
+            reports: Vec::new(),
+            failures: 0,
+            fingerprint: Some(fingerprint.to_string()),
+            replicate_of: replicate_of.map(str::to_string),
+            machine: None,
+        }
+    }
+
+    #[test]
+    fn fingerprints() {
+        let environment = Environment::default();
+        let given = fingerprint(Some("9f3c1a2"), "", &environment, "[thresholds]", 1);
+
+        assert_eq!(
+            given,
+            fingerprint(Some("9f3c1a2"), "", &environment, "[thresholds]", 1)
+        );
+        assert_ne!(
+            given,
+            fingerprint(Some("9f3c1a2"), "", &environment, "[thresholds]", 2)
+        );
+        assert_ne!(given, fingerprint(Some("9f3c1a2"), "", &environment, "", 1));
+        assert_ne!(
+            given,
+            fingerprint(
+                Some("9f3c1a2"),
+                " M axum/src/main.rs\n",
+                &environment,
+                "[thresholds]",
+                1
+            )
+        );
+        assert_eq!(fingerprint(None, "", &environment, "[thresholds]", 1), None);
+    }
+
+    #[test]
+    fn changes() {
+        let dir = env::temp_dir().join("bench-bot-test-changes");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=bench-bot",
+                    "-c",
+                    "user.email=bench-bot@example.com",
+                ])
+                .args(args)
+                .current_dir(&dir)
+                .stdout(Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success());
+        };
+        git(&["init", "-q"]);
+        fs::write(dir.join("main.rs"), "fn main() {}\n").unwrap();
+        git(&["add", "main.rs"]);
+        git(&["commit", "-q", "-m", "Add main"]);
+
+        assert_eq!(uncommitted_changes(&dir), "");
+
+        fs::write(dir.join("main.rs"), "fn main() { println!(); }\n").unwrap();
+        let changed = uncommitted_changes(&dir);
+        assert!(changed.starts_with(" M main.rs\n"));
+
+        fs::write(dir.join("main.rs"), "fn main() { panic!(); }\n").unwrap();
+        assert_ne!(uncommitted_changes(&dir), changed);
+    }
+
+    #[test]
+    fn stitched() {
+        let output = "    Latency   814.27us  498.47us   8.42ms   69.23%\n  17275966 requests in \
+            30.09s, 1.95GB read\nRequests/sec: 574184.09\nTransfer/sec:     66.26MB\n";
+        let report =
+            |name: &str| Report::new(name, 13.7, output.parse().expect("parse metric fail"));
+        let mut given = [record("1", "a", None), record("2", "a", None)];
+        given[0].reports = vec![report("actix"), report("axum")];
+        given[1].reports = vec![report("actix-web"), report("axum")];
+
+        stitch(
+            &mut given,
+            &BTreeMap::from([("actix".to_string(), "actix-web".to_string())]),
+        );
+
+        let names: Vec<&str> = given
+            .iter()
+            .flat_map(|r| r.reports.iter().map(Report::framework_name))
+            .collect();
+        assert_eq!(names, ["actix-web", "axum", "actix-web", "axum"]);
+    }
+
+    #[test]
+    fn original() {
+        let given = [
+            record("1", "a", None),
+            record("2", "a", Some("1")),
+            record("3", "b", None),
+        ];
+
+        assert_eq!(
+            find_original(&given, "a", "hello-world").map(|r| r.id.as_str()),
+            Some("1")
+        );
+        assert_eq!(
+            find_original(&given, "b", "json").map(|r| r.id.as_str()),
+            None
+        );
+        assert_eq!(
+            find_original(&given, "c", "hello-world").map(|r| r.id.as_str()),
+            None
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::config::Hooks;
+
+/// Point of a benchmark hooks run at.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    PreBuild,
+    PostStart,
+    PreMeasure,
+    PostTeardown,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::PreBuild => "pre-build",
+            Phase::PostStart => "post-start",
+            Phase::PreMeasure => "pre-measure",
+            Phase::PostTeardown => "post-teardown",
+        }
+    }
+
+    fn command(self, hooks: &Hooks) -> Option<&str> {
+        match self {
+            Phase::PreBuild => hooks.pre_build.as_deref(),
+            Phase::PostStart => hooks.post_start.as_deref(),
+            Phase::PreMeasure => hooks.pre_measure.as_deref(),
+            Phase::PostTeardown => hooks.post_teardown.as_deref(),
+        }
+    }
+}
+
+/// What hooks and collectors are told about the benchmark they run for.
+#[derive(Debug)]
+pub struct Context<'a> {
+    pub framework: &'a str,
+    /// Unset before building, a binary may serve several scenarios.
+    pub scenario: Option<&'a str>,
+    pub url: Option<&'a str>,
+    /// Server process, while it is running.
+    pub server_pid: Option<u32>,
+    /// Round about to be measured, from 1.
+    pub round: Option<usize>,
+    pub workspace_dir: &'a Path,
+    pub output_dir: &'a Path,
+}
+
+impl Context<'_> {
+    /// Environment variables telling about the benchmark, unset values are
+    /// left out.
+    pub fn vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("BENCHBOT_FRAMEWORK", self.framework.to_string()),
+            (
+                "BENCHBOT_WORKSPACE_DIR",
+                self.workspace_dir.display().to_string(),
+            ),
+            ("BENCHBOT_OUTPUT_DIR", self.output_dir.display().to_string()),
+        ];
+
+        let optional = [
+            ("BENCHBOT_SCENARIO", self.scenario.map(str::to_string)),
+            ("BENCHBOT_URL", self.url.map(str::to_string)),
+            (
+                "BENCHBOT_SERVER_PID",
+                self.server_pid.map(|pid| pid.to_string()),
+            ),
+            ("BENCHBOT_ROUND", self.round.map(|round| round.to_string())),
+        ];
+        vars.extend(
+            optional
+                .into_iter()
+                .filter_map(|(name, value)| Some((name, value?))),
+        );
+
+        vars
+    }
+}
+
+/// Run the hook of `phase` through `sh -c` if one is configured, waiting
+/// for it to exit. Its output goes to the terminal, background processes it
+/// starts keep running.
+pub fn run(hooks: &Hooks, phase: Phase, context: &Context) -> Result<(), String> {
+    let Some(command) = phase.command(hooks) else {
+        return Ok(());
+    };
+    log::debug!("Running {} hook for {}.", phase.name(), context.framework);
+
+    let status = Command::new("sh")
+        .args(["-c", command])
+        .envs(context.vars())
+        .env("BENCHBOT_PHASE", phase.name())
+        .current_dir(context.workspace_dir)
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|e| format!("running {} hook: {}", phase.name(), e))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} hook of {} exited with {}",
+            phase.name(),
+            context.framework,
+            status
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vars() {
+        let given = Context {
+            framework: "axum",
+            scenario: Some("json"),
+            url: None,
+            server_pid: Some(4242),
+            round: None,
+            workspace_dir: Path::new("../benchmark"),
+            output_dir: Path::new("../result"),
+        };
+
+        assert_eq!(
+            given.vars(),
+            vec![
+                ("BENCHBOT_FRAMEWORK", "axum".to_string()),
+                ("BENCHBOT_WORKSPACE_DIR", "../benchmark".to_string()),
+                ("BENCHBOT_OUTPUT_DIR", "../result".to_string()),
+                ("BENCHBOT_SCENARIO", "json".to_string()),
+                ("BENCHBOT_SERVER_PID", "4242".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn exit_status() {
+        let hooks = Hooks {
+            pre_build: Some(
+                "test \"$BENCHBOT_FRAMEWORK $BENCHBOT_PHASE\" = \"axum pre-build\"".to_string(),
+            ),
+            post_teardown: Some("exit 3".to_string()),
+            ..Hooks::default()
+        };
+        let context = Context {
+            framework: "axum",
+            scenario: None,
+            url: None,
+            server_pid: None,
+            round: None,
+            workspace_dir: Path::new("."),
+            output_dir: Path::new("."),
+        };
+
+        assert_eq!(run(&hooks, Phase::PreBuild, &context), Ok(()));
+        assert_eq!(run(&hooks, Phase::PreMeasure, &context), Ok(()));
+        assert!(run(&hooks, Phase::PostTeardown, &context).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+use crate::column::Column;
+use crate::environment::Environment;
+use crate::format::NumberFormat;
+use crate::report::{Report, ReportOptions, VariantRow};
+use crate::units;
+
+const STYLE: &str = r#"body { font-family: sans-serif; margin: 2em; }
+input { margin-bottom: 1em; padding: 0.3em; width: 20em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ddd; padding: 0.3em 0.6em; text-align: right; white-space: nowrap; }
+th { background: #f4f4f4; cursor: pointer; user-select: none; }
+th.asc::after { content: " ▲"; }
+th.desc::after { content: " ▼"; }
+td:first-child, th:first-child { text-align: left; }
+tr:hover td { background: #fafafa; }
+.bar { background: #4a90d9; height: 4px; margin-top: 2px; }
+tr.variant td:first-child { padding-left: 1.6em; }"#;
+
+const DETAIL_STYLE: &str = r#"pre { background: #f4f4f4; padding: 1em; overflow-x: auto; }
+img { max-width: 100%; }
+.warning { border-left: 4px solid #e0a030; padding-left: 1em; }"#;
+
+// sorts numerically by `data-value` where every non-empty cell has one,
+// cells without a value go last either way
+const SCRIPT: &str = r#"const table = document.getElementById("results");
+const rows = () => Array.from(table.tBodies[0].rows);
+table.querySelectorAll("th").forEach((th, index) => th.addEventListener("click", () => {
+  const asc = !th.classList.contains("asc");
+  table.querySelectorAll("th").forEach(other => other.classList.remove("asc", "desc"));
+  th.classList.add(asc ? "asc" : "desc");
+  const key = row => row.cells[index].dataset.value;
+  const numeric = rows().every(row => key(row) !== undefined || row.cells[index].textContent === "-");
+  const sorted = rows().sort((a, b) => {
+    if (numeric) {
+      const [x, y] = [key(a), key(b)];
+      if (x === undefined || y === undefined) return (x === undefined) - (y === undefined);
+      return (asc ? 1 : -1) * (Number(x) - Number(y));
+    }
+    return (asc ? 1 : -1) * a.cells[index].textContent.localeCompare(b.cells[index].textContent);
+  });
+  sorted.forEach(row => table.tBodies[0].appendChild(row));
+}));
+document.getElementById("filter").addEventListener("input", event => {
+  const query = event.target.value.toLowerCase();
+  rows().forEach(row => row.hidden = !row.cells[0].textContent.toLowerCase().includes(query));
+});"#;
+
+/// A standalone page of the comparison table of `scenario`, sortable by
+/// every column and filterable by framework, with a bar of Req/Sec per
+/// framework. Names link to detail pages when `options` link them.
+/// `variants` follow in a table of their own, below the implementation they
+/// vary, see [`Report::variants`].
+pub fn report(
+    scenario: &str,
+    reports: &[Report],
+    variants: &[VariantRow],
+    options: &ReportOptions,
+) -> String {
+    // links are added here, the markdown ones would show as text
+    let cell_options = ReportOptions {
+        detail_links: None,
+        ..options.clone()
+    };
+    let (header, rows) = Report::table(reports, &cell_options);
+    let fastest = reports
+        .iter()
+        .map(|r| r.metrics().req_per_sec())
+        .fold(0.0, f64::max);
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{0} - bench-bot</title>\n<style>\n{1}\n</style>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape(scenario),
+        STYLE
+    );
+    html.push_str("<input id=\"filter\" type=\"search\" placeholder=\"Filter frameworks\">\n");
+    html.push_str("<table id=\"results\">\n<thead>\n<tr>");
+    for name in &header {
+        html.push_str(&format!("<th>{}</th>", escape(name)));
+    }
+    html.push_str("</tr>\n</thead>\n<tbody>\n");
+
+    for (report, row) in Report::sorted(reports, options.sort).into_iter().zip(rows) {
+        html.push_str("<tr>");
+        for (column, cell) in options
+            .columns
+            .iter()
+            .map(Some)
+            .chain(std::iter::repeat(None))
+            .zip(row)
+        {
+            let value = sort_value(&cell)
+                .map(|value| format!(" data-value=\"{}\"", value))
+                .unwrap_or_default();
+            let mut content = escape(&cell);
+
+            match column {
+                Some(Column::FrameworkName) => {
+                    if let Some(dir) = &options.detail_links {
+                        content = format!(
+                            "<a href=\"{}/{}.html\">{}</a>",
+                            escape(dir),
+                            escape(report.framework_name()),
+                            content
+                        );
+                    }
+                }
+                Some(Column::RequestReqPerSec) if fastest > 0.0 => {
+                    let width = report.metrics().req_per_sec() / fastest * 100.0;
+                    content.push_str(&format!(
+                        "<div class=\"bar\" style=\"width: {:.1}%\"></div>",
+                        width
+                    ));
+                }
+                _ => {}
+            }
+
+            html.push_str(&format!("<td{}>{}</td>", value, content));
+        }
+        html.push_str("</tr>\n");
+    }
+
+    html.push_str("</tbody>\n</table>\n");
+
+    if !variants.is_empty() {
+        html.push_str(
+            "<h2>Variants</h2>\n<p>Implementations changing one thing of another, e.g. \
+             its allocator or runtime, below it with their change against it.</p>\n\
+             <table>\n<thead>\n<tr>",
+        );
+        for name in Report::variants_header() {
+            html.push_str(&format!("<th>{}</th>", escape(name)));
+        }
+        html.push_str("</tr>\n</thead>\n<tbody>\n");
+        for row in variants {
+            match &row.axis {
+                None => html.push_str(&format!(
+                    "<tr><td><strong>{}</strong></td><td>-</td>",
+                    escape(&row.framework_name)
+                )),
+                Some(axis) => html.push_str(&format!(
+                    "<tr class=\"variant\"><td>↳ {}</td><td>{}</td>",
+                    escape(&row.framework_name),
+                    escape(axis)
+                )),
+            }
+            for cell in &row.cells {
+                html.push_str(&format!("<td>{}</td>", escape(cell)));
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    html.push_str(&format!(
+        "<script>\n{}\n</script>\n</body>\n</html>\n",
+        SCRIPT
+    ));
+    html
+}
+
+/// What the detail page of a framework shows besides its report.
+pub struct Detail<'a> {
+    pub scenario: &'a str,
+    pub environment: &'a Environment,
+    /// Command of the load tool.
+    pub command: &'a str,
+    /// Url measured, when the scenario has a path of its own.
+    pub url: Option<&'a str>,
+    /// (title, file name) of the charts next to the page, e.g. the
+    /// flamegraph.
+    pub charts: &'a [(&'a str, String)],
+    /// Output of the load tool.
+    pub output: &'a str,
+}
+
+/// A standalone page of every metric of `report`, its rounds and charts,
+/// linked from the framework's row of [`report`].
+pub fn detail(report: &Report, detail: &Detail, format: &NumberFormat) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{0} - {1} - bench-bot</title>\n<style>\n{2}\n{3}\n</style>\n</head>\n\
+         <body>\n<h1>{0}</h1>\n<p>Scenario: {1}</p>\n",
+        escape(report.framework_name()),
+        escape(detail.scenario),
+        STYLE,
+        DETAIL_STYLE
+    );
+
+    html.push_str("<h2>Metrics</h2>\n");
+    if report.is_incomplete() {
+        html.push_str(
+            "<p class=\"warning\">The output of the load tool was cut off, these are the \
+            metrics it printed before.</p>\n",
+        );
+    }
+    if report.is_pathological() {
+        html.push_str(
+            "<p class=\"warning\">Throughput fell below the floor of the scenario and \
+            measuring was aborted, these are the metrics measured until then.</p>\n",
+        );
+    }
+    html.push_str("<table>\n<thead>\n<tr><th>Metric</th><th>Value</th></tr>\n</thead>\n<tbody>\n");
+    for (name, value) in report.detail_rows(format) {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td></tr>\n",
+            escape(&name),
+            escape(&value)
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+
+    if let Some(rounds) = report.rounds_rows(format) {
+        html.push_str(
+            "<h2>Rounds</h2>\n<table>\n<thead>\n\
+             <tr><th>Metric</th><th>Mean</th><th>Stdev</th><th>Best</th></tr>\n</thead>\n<tbody>\n",
+        );
+        for row in rounds {
+            let cells: String = row
+                .iter()
+                .map(|cell| format!("<td>{}</td>", escape(cell)))
+                .collect();
+            html.push_str(&format!("<tr>{}</tr>\n", cells));
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    for (title, file) in detail.charts {
+        html.push_str(&format!(
+            "<h2>{0}</h2>\n<img src=\"{1}\" alt=\"{0}\">\n",
+            escape(title),
+            escape(file)
+        ));
+    }
+
+    html.push_str("<h2>Environment</h2>\n<ul>\n");
+    for (key, value) in detail.environment.entries() {
+        html.push_str(&format!("<li>{}: {}</li>\n", escape(key), escape(value)));
+    }
+    html.push_str("</ul>\n");
+
+    html.push_str(&format!(
+        "<h2>Benchmark</h2>\n<pre>{}</pre>\n",
+        escape(detail.command)
+    ));
+    if let Some(url) = detail.url {
+        html.push_str(&format!("<p>Url: <code>{}</code></p>\n", escape(url)));
+    }
+
+    html.push_str(&format!(
+        "<h2>Output</h2>\n<pre>{}</pre>\n</body>\n</html>\n",
+        escape(detail.output)
+    ));
+    html
+}
+
+/// Number a cell sorts by, read back from its text, e.g. seconds of
+/// `0.8143ms` or bytes of `1.95GB`. `None` for text and missing metrics.
+fn sort_value(cell: &str) -> Option<f64> {
+    let first = cell.split_whitespace().next()?;
+
+    units::parse_duration(first)
+        .map(|duration| duration.as_secs_f64())
+        .or_else(|| units::parse_bytes(first).map(|bytes| bytes as f64))
+        .or_else(|| units::parse_number(first.trim_end_matches(['%', 'J'])))
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::NumberFormat;
+    use crate::manifest::Variant;
+
+    #[test]
+    fn sort_values() {
+        assert_eq!(sort_value("0.8143ms"), Some(0.0008143));
+        assert_eq!(sort_value("1.95GB"), Some(2093796557.0));
+        assert_eq!(sort_value("574k ±6k"), Some(574000.0));
+        assert_eq!(sort_value("0 (0.00%)"), Some(0.0));
+        assert_eq!(sort_value("12.5J"), Some(12.5));
+        assert_eq!(sort_value("-"), None);
+        assert_eq!(sort_value("🥇 actix-web"), None);
+    }
+
+    #[test]
+    fn page() {
+        let wrk = |req_per_sec: &str| {
+            format!(
+                "    Latency   814.27us  498.47us   8.42ms   69.23%\n  17275966 requests in \
+                    30.09s, 1.95GB read\nRequests/sec: {}\nTransfer/sec:     66.26MB\n",
+                req_per_sec
+            )
+        };
+        let given = vec![
+            Report::new(
+                "actix-web",
+                13.7,
+                wrk("574184.09").parse().expect("parse metric fail"),
+            ),
+            Report::new(
+                "<axum>",
+                12.4,
+                wrk("287092.05").parse().expect("parse metric fail"),
+            ),
+        ];
+        let options = ReportOptions {
+            columns: vec![
+                Column::FrameworkName,
+                Column::RequestReqPerSec,
+                Column::MaxMemory,
+            ],
+            medals: true,
+            detail_links: Some("hello-world".to_string()),
+            ..ReportOptions::default()
+        };
+
+        let variant_of = |name: &str| {
+            (name == "<axum>").then(|| Variant {
+                of: "actix-web".to_string(),
+                axis: "allocator".to_string(),
+            })
+        };
+        let variants = Report::variants(&given, variant_of, &NumberFormat::default());
+
+        insta::assert_snapshot!(report("hello-world", &given, &variants, &options));
+    }
+
+    #[test]
+    fn detail_page() {
+        let output = "    Latency   814.27us  498.47us   8.42ms   69.23%\n  17275966 requests in \
+            30.09s, 1.95GB read\nRequests/sec: 574184.09\nTransfer/sec:     66.26MB\n";
+        let given = Report::new(
+            "actix-web",
+            13.7,
+            output.parse().expect("parse metric fail"),
+        );
+        let environment: Environment =
+            serde_json::from_str(r#"{"cpu": "AMD EPYC 7B13", "rustc": "rustc 1.75.0"}"#).unwrap();
+        let charts = [
+            ("Req/Sec over time", "actix-web.timeline.svg".to_string()),
+            ("Flamegraph", "actix-web.svg".to_string()),
+        ];
+        let detail_of = Detail {
+            scenario: "hello-world",
+            environment: &environment,
+            command: "wrk --latency --threads 4 --connections 256 --duration 30s",
+            url: None,
+            charts: &charts,
+            output,
+        };
+
+        insta::assert_snapshot!(detail(&given, &detail_of, &NumberFormat::default()));
+    }
+}
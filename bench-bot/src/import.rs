@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+use clap::ArgEnum;
+
+use crate::column::Column;
+use crate::environment::Environment;
+use crate::history::{self, RunRecord};
+use crate::report::{Errors, Latency, Metrics, Report, Request, Transfer};
+use crate::units;
+
+/// Reports of every comparison table in `markdown`, as published by earlier
+/// runs. Columns are told apart by their header, metrics missing from the
+/// table or not parsing stay zero.
+pub fn parse_tables(markdown: &str) -> Vec<Report> {
+    let mut reports = Vec::new();
+    let mut columns: Option<Vec<Option<Column>>> = None;
+
+    for line in markdown.lines().map(str::trim) {
+        if !line.starts_with('|') {
+            columns = None;
+            continue;
+        }
+
+        let cells: Vec<&str> = line.trim_matches('|').split('|').map(str::trim).collect();
+        match &columns {
+            None if cells.first() == Some(&Column::FrameworkName.header()) => {
+                columns = Some(
+                    cells
+                        .iter()
+                        .map(|header| {
+                            Column::value_variants()
+                                .iter()
+                                .copied()
+                                .find(|c| c.header() == *header)
+                        })
+                        .collect(),
+                );
+            }
+            Some(_)
+                if cells
+                    .iter()
+                    .all(|cell| cell.chars().all(|c| c == '-' || c == ':')) => {}
+            Some(columns) => reports.extend(parse_row(columns, &cells)),
+            None => {}
+        }
+    }
+
+    reports
+}
+
+fn parse_row(columns: &[Option<Column>], cells: &[&str]) -> Option<Report> {
+    let cell = |column: Column| {
+        let index = columns.iter().position(|c| *c == Some(column))?;
+        // intervals of repeated runs follow the mean, e.g. `574k ±6k`
+        cells
+            .get(index)
+            .map(|cell| cell.split(" ±").next().unwrap_or(cell))
+    };
+    let duration = |column: Column| {
+        cell(column)
+            .and_then(units::parse_duration)
+            .unwrap_or_default()
+    };
+    let bytes = |column: Column| {
+        cell(column)
+            .and_then(units::parse_bytes)
+            .unwrap_or_default()
+    };
+
+    let framework_name = framework_name(cell(Column::FrameworkName)?);
+    let max_memory = cell(Column::MaxMemory)
+        .and_then(units::parse_megabytes)
+        .unwrap_or_default();
+
+    let metrics = Metrics {
+        latency: Latency {
+            avg: duration(Column::LatencyAvg),
+            std_env: duration(Column::LatencyStdev),
+            min: duration(Column::LatencyMin),
+            max: duration(Column::LatencyMax),
+            p50: duration(Column::LatencyP50),
+            p75: duration(Column::LatencyP75),
+            p90: duration(Column::LatencyP90),
+            p99: duration(Column::LatencyP99),
+        },
+        request: Request {
+            total: cell(Column::RequestTotal)
+                .and_then(units::parse_number)
+                .map_or(0, |total| total.round() as u64),
+            req_per_sec: cell(Column::RequestReqPerSec)
+                .and_then(units::parse_number)
+                .unwrap_or_default(),
+        },
+        transfer: Transfer {
+            total: bytes(Column::TransferTotal),
+            rate: bytes(Column::TransferRate) as f64,
+        },
+        threads: None,
+        errors: Errors::default(),
+        timeline: Vec::new(),
+        status_codes: BTreeMap::new(),
+        phases: None,
+    };
+
+    Some(Report::new(&framework_name, max_memory, metrics))
+}
+
+/// Name of a framework cell without the medal, regression mark or link to
+/// its detail page.
+fn framework_name(cell: &str) -> String {
+    let name = cell
+        .trim_start_matches(|c: char| !c.is_ascii_alphanumeric() && c != '[')
+        .trim();
+
+    match name
+        .strip_prefix('[')
+        .and_then(|linked| linked.split_once("]("))
+    {
+        Some((name, _)) => name.to_string(),
+        None => name.to_string(),
+    }
+}
+
+/// Run record of a published markdown file, dated and attributed to the
+/// commit that last changed it when it is tracked by git. Its id tells
+/// apart files changed by the same commit, hashing their content.
+pub fn import(path: &Path, scenario: &str) -> Result<RunRecord, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("reading {:?}: {}", path, e))?;
+    let reports = parse_tables(&content);
+    if reports.is_empty() {
+        return Err(format!("no comparison table in {:?}", path));
+    }
+
+    let (timestamp, commit) = match last_change(path) {
+        Some((timestamp, commit)) => (timestamp, Some(commit)),
+        None => {
+            let modified = fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .map_err(|e| format!("reading {:?}: {}", path, e))?;
+            (
+                modified
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or(Duration::ZERO)
+                    .as_secs(),
+                None,
+            )
+        }
+    };
+
+    let machine = Environment::from_markdown(&content).machine_profile();
+
+    Ok(RunRecord {
+        id: format!(
+            "imported-{}-{}",
+            timestamp,
+            &history::hash(&[&content])[..8]
+        ),
+        timestamp,
+        commit,
+        scenario: scenario.to_string(),
+        reports,
+        failures: 0,
+        fingerprint: None,
+        replicate_of: None,
+        machine: (!machine.is_empty()).then_some(machine),
+    })
+}
+
+/// Timestamp and hash of the last commit changing `path`.
+fn last_change(path: &Path) -> Option<(u64, String)> {
+    let dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let output = Command::new("git")
+        .args(["log", "-1", "--format=%ct %H", "--"])
+        .arg(path.file_name()?)
+        .current_dir(dir)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let (timestamp, commit) = stdout.trim().split_once(' ')?;
+
+    Some((timestamp.parse().ok()?, commit.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn tables() {
+        let given = r#"
+Generated by bench-bot.
+
+## Comparisons
+
+### Full Frameworks
+
+| Framework Name | Latency.Avg | Latency.99P | Request.Total | Request.Req/Sec | Transfer.Total | Transfer.Rate | Max. Memory Usage |
+|---|---|---|---|---|---|---|---|
+|🥇 [actix-web](hello-world/actix-web.md)|1.0000ms|4.4900ms|15992965|531324.93|1.94GB|65.87MB|12.4MB|
+|🔻 rocket|2.1400ms|-|7045807|234082.63 ±1.2k|1.63GB|55.36MB|1.2GB|
+
+### Raw Servers
+
+| Framework Name | Request.Req/Sec |
+|---|---|
+|hyper|635430.27|
+        "#;
+        let actual = parse_tables(given);
+
+        let names: Vec<&str> = actual.iter().map(Report::framework_name).collect();
+        assert_eq!(names, ["actix-web", "rocket", "hyper"]);
+
+        let actix = actual[0].metrics();
+        assert_eq!(actix.latency.p99, Duration::from_micros(4_490));
+        assert_eq!(actix.request.total, 15992965);
+        assert_eq!(actix.transfer.total, 2_083_059_139);
+        assert_eq!(actual[0].max_memory(), 12.4);
+
+        assert_eq!(actual[1].metrics().latency.p99, Duration::ZERO);
+        assert_eq!(actual[1].max_memory(), 1228.8);
+        assert_eq!(actual[1].metrics().req_per_sec(), 234082.63);
+        assert_eq!(actual[2].metrics().req_per_sec(), 635430.27);
+    }
+
+    #[test]
+    fn records() {
+        let dir = env::temp_dir().join("bench-bot-test-import");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let table = "| Framework Name | Request.Req/Sec |\n|---|---|\n";
+        fs::write(
+            dir.join("axum.md"),
+            format!(
+                "# Environment\n\n- cpu: AMD EPYC 7B13\n- hostname: ci-4\n\n{}|axum|574184.09|\n",
+                table
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("tide.md"), format!("{}|tide|80000|\n", table)).unwrap();
+
+        let axum = import(&dir.join("axum.md"), "hello-world").unwrap();
+        let tide = import(&dir.join("tide.md"), "hello-world").unwrap();
+
+        assert_ne!(axum.id, tide.id);
+        assert_eq!(
+            axum.id,
+            import(&dir.join("axum.md"), "hello-world").unwrap().id
+        );
+        let machine = axum.machine.unwrap();
+        assert_eq!(
+            (machine.get("cpu"), machine.get("hostname")),
+            (Some("AMD EPYC 7B13"), None)
+        );
+        assert_eq!(tide.machine, None);
+    }
+}
@@ -0,0 +1,94 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::parser;
+use crate::report::{Metrics, Report};
+
+/// Output of a load tool saved for a framework, `<framework>=<file>` on the
+/// command line.
+#[derive(Debug, PartialEq)]
+pub struct SavedOutput {
+    pub framework_name: String,
+    pub path: PathBuf,
+}
+
+impl std::str::FromStr for SavedOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, path)) if !name.is_empty() && !path.is_empty() => Ok(SavedOutput {
+                framework_name: name.to_string(),
+                path: PathBuf::from(path),
+            }),
+            _ => Err(format!("expected <framework>=<file>, got {:?}", s)),
+        }
+    }
+}
+
+/// Report of the framework of `saved`, from the output of whichever
+/// supported tool wrote it. Load tools don't measure the server, so its
+/// memory is unknown and reported as zero.
+pub fn ingest(saved: &SavedOutput, strict: bool) -> Result<Report, String> {
+    let content =
+        fs::read_to_string(&saved.path).map_err(|e| format!("reading {:?}: {}", saved.path, e))?;
+
+    report(&saved.framework_name, &content, strict)
+        .map_err(|e| format!("parsing {:?}: {}", saved.path, e))
+}
+
+fn report(framework_name: &str, output: &str, strict: bool) -> Result<Report, String> {
+    let mut rounds: Vec<Metrics> = Vec::new();
+    let mut incomplete = false;
+    for round in parser::split_rounds(output) {
+        let (metrics, complete) = parser::parse_round(round, strict).map_err(|e| e.to_string())?;
+        rounds.push(metrics);
+        incomplete |= !complete;
+    }
+
+    let report = Report::from_rounds(framework_name, 0.0, rounds).ok_or("no rounds")?;
+
+    Ok(if incomplete {
+        report.with_incomplete()
+    } else {
+        report
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_outputs() {
+        assert_eq!(
+            "express=autocannon.json".parse(),
+            Ok(SavedOutput {
+                framework_name: "express".to_string(),
+                path: PathBuf::from("autocannon.json"),
+            })
+        );
+        assert!("autocannon.json".parse::<SavedOutput>().is_err());
+    }
+
+    #[test]
+    fn any_tool() {
+        let oha = r#"{
+            "summary": {"successRate": 1.0, "total": 30.0, "slowest": 0.00842, "fastest": 0.00002,
+                "average": 0.00081427, "requestsPerSec": 574184.09, "totalData": 2093796557,
+                "sizePerRequest": 121, "sizePerSec": 69478646.0},
+            "latencyPercentiles": {"p50": 0.000707, "p75": 0.00107, "p90": 0.0015, "p99": 0.00256},
+            "statusCodeDistribution": {"200": 17275966},
+            "errorDistribution": {}
+        }"#;
+        let wrk = "    Latency   814.27us  498.47us   8.42ms   69.23%\n  17275966 requests in \
+            30.09s, 1.95GB read\nRequests/sec: 574184.09\nTransfer/sec:     66.26MB\n";
+
+        for output in [oha, wrk] {
+            let actual = report("express", output, false).expect("parse metric fail");
+            assert_eq!(actual.metrics().req_per_sec(), 574184.09);
+            assert!(!actual.is_incomplete());
+        }
+        assert!(report("express", "connection refused", false).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{env, process, thread};
+
+use sysinfo::{Pid, PidExt, System, SystemExt};
+
+/// How often a queued run checks whether it's its turn.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Machine wide guard against concurrent benchmark runs, which would skew
+/// each other's results. Released when dropped.
+#[derive(Debug)]
+pub struct RunLock {
+    path: PathBuf,
+}
+
+/// The lock of the machine, in the temporary directory.
+pub fn machine_lock() -> PathBuf {
+    env::temp_dir().join("bench-bot.lock")
+}
+
+impl RunLock {
+    /// Take the lock at `path`, failing with the holder's pid while another
+    /// run is in progress. Locks of exited processes are taken over.
+    pub fn acquire(path: PathBuf) -> Result<Self, String> {
+        match Self::try_acquire(path.clone())? {
+            Ok(lock) => Ok(lock),
+            Err(pid) => Err(format!(
+                "a benchmark is already in progress (pid {}, lock {:?})",
+                pid, path
+            )),
+        }
+    }
+
+    /// Take the lock at `path` once the runs queued before this one are
+    /// done, logging the position in the queue while waiting.
+    pub fn wait(path: PathBuf) -> Result<Self, String> {
+        let queue_path = queue_path(&path);
+        let pid = process::id();
+        let mut position = 0;
+
+        loop {
+            let mut waiting = queue(&path);
+            // a run leaving rewrites the queue, and may drop an entry
+            // appended meanwhile, the run simply queues up again
+            if !waiting.contains(&pid) {
+                let mut file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&queue_path)
+                    .map_err(|e| format!("opening {:?}: {}", queue_path, e))?;
+                writeln!(file, "{}", pid)
+                    .map_err(|e| format!("writing {:?}: {}", queue_path, e))?;
+                waiting.push(pid);
+            }
+
+            let ahead = waiting
+                .iter()
+                .position(|waiting| *waiting == pid)
+                .expect("run is queued");
+            if ahead == 0 {
+                if let Ok(lock) = Self::try_acquire(path.clone())? {
+                    leave(&queue_path, pid)?;
+                    return Ok(lock);
+                }
+            }
+
+            if position != ahead + 1 {
+                position = ahead + 1;
+                log::info!(
+                    "Waiting for the benchmark in progress, position {} in the queue.",
+                    position
+                );
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// The lock, or the pid of the run in progress holding it.
+    fn try_acquire(path: PathBuf) -> Result<Result<Self, u32>, String> {
+        // the pid is linked into place complete, a lock created empty and
+        // written after would look stale to a run reading it in between
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(format!(".{}", process::id()));
+        let staged = path.with_file_name(name);
+        fs::write(&staged, process::id().to_string())
+            .map_err(|e| format!("writing {:?}: {}", staged, e))?;
+
+        let acquired = Self::link(&staged, path);
+        let _ = fs::remove_file(&staged);
+
+        acquired
+    }
+
+    fn link(staged: &Path, path: PathBuf) -> Result<Result<Self, u32>, String> {
+        for _ in 0..2 {
+            match fs::hard_link(staged, &path) {
+                Ok(()) => return Ok(Ok(RunLock { path })),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let holder = fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|pid| pid.trim().parse::<u32>().ok());
+
+                    if let Some(pid) = holder.filter(|pid| is_running(*pid)) {
+                        return Ok(Err(pid));
+                    }
+
+                    log::warn!("Removing stale lock {:?}.", path);
+                    fs::remove_file(&path).map_err(|e| format!("removing {:?}: {}", path, e))?;
+                }
+                Err(e) => return Err(format!("creating {:?}: {}", path, e)),
+            }
+        }
+
+        Err(format!("could not take lock {:?}", path))
+    }
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Pids of the runs waiting for the lock at `path`, next first. Runs that
+/// exited while waiting are left out.
+pub fn queue(path: &Path) -> Vec<u32> {
+    let content = fs::read_to_string(queue_path(path)).unwrap_or_default();
+
+    let mut pids: Vec<u32> = Vec::new();
+    for pid in content.lines().filter_map(|line| line.trim().parse().ok()) {
+        if !pids.contains(&pid) && is_running(pid) {
+            pids.push(pid);
+        }
+    }
+
+    pids
+}
+
+fn queue_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".queue");
+
+    path.with_file_name(name)
+}
+
+// dropping the runs that exited on the way
+fn leave(queue_path: &Path, pid: u32) -> Result<(), String> {
+    let content = fs::read_to_string(queue_path).unwrap_or_default();
+    let rest: String = content
+        .lines()
+        .filter(|line| {
+            line.trim()
+                .parse()
+                .is_ok_and(|queued: u32| queued != pid && is_running(queued))
+        })
+        .map(|line| format!("{}\n", line))
+        .collect();
+
+    fs::write(queue_path, rest).map_err(|e| format!("writing {:?}: {}", queue_path, e))
+}
+
+fn is_running(pid: u32) -> bool {
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+
+    sys.refresh_process(pid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exclusive() {
+        let path = env::temp_dir().join("bench-bot-test-exclusive.lock");
+        let _ = fs::remove_file(&path);
+
+        let lock = RunLock::acquire(path.clone()).expect("acquire lock fail");
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            process::id().to_string()
+        );
+        assert!(!path
+            .with_file_name(format!("bench-bot-test-exclusive.lock.{}", process::id()))
+            .exists());
+        assert!(RunLock::acquire(path.clone()).is_err());
+
+        drop(lock);
+        assert!(RunLock::acquire(path).is_ok());
+    }
+
+    #[test]
+    fn stale() {
+        let path = env::temp_dir().join("bench-bot-test-stale.lock");
+        fs::write(&path, u32::MAX.to_string()).unwrap();
+
+        assert!(RunLock::acquire(path).is_ok());
+    }
+
+    #[test]
+    fn queued() {
+        let path = env::temp_dir().join("bench-bot-test-queued.lock");
+        let _ = fs::remove_file(&path);
+        fs::write(
+            queue_path(&path),
+            format!("{}\n{}\n", u32::MAX, process::id()),
+        )
+        .unwrap();
+
+        assert_eq!(queue(&path), vec![process::id()]);
+
+        let lock = RunLock::wait(path.clone()).expect("wait for lock fail");
+        assert_eq!(queue(&path), Vec::<u32>::new());
+        assert!(RunLock::acquire(path).is_err());
+        drop(lock);
+    }
+}
@@ -1,50 +1,495 @@
+use self::column::{Column, ColumnPreset};
+use self::compare::{Regression, Results};
+use self::config::{Config, Override, Percent};
+use self::control::Control;
+use self::environment::Environment;
+use self::events::{Anomaly, Event, EventStream};
+use self::format::{NumberFormat, UnitDisplay};
+use self::history::{DuplicateRuns, RunRecord};
+use self::manifest::{Benchmark, Manifest};
 use self::markdown::Markdown;
-use clap::Parser;
+use self::report::{Metrics, OutputFormat, Report, ReportOptions, SortKey, SKEWED_THREAD_BALANCE};
+use self::status::Status;
+use self::submission::Submission;
+use self::summary::RunSummary;
+use self::timings::PhaseTimings;
+use clap::{ArgEnum, ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueSource};
 use log::LevelFilter;
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    env,
     ffi::OsStr,
     fs,
-    io::Write,
+    io::{self, IsTerminal, Write},
     path::{Component, Path, PathBuf},
-    process::Command,
+    process::{self, Command},
     sync::mpsc,
     thread,
-    time::Duration,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use sysinfo::{CpuExt, PidExt, ProcessExt, System, SystemExt};
-use self::report::{Metrics, Report};
 
+mod analysis;
+mod artifacts;
+mod audit;
+mod budget;
+mod capacity;
+mod collector;
+mod column;
+mod compare;
+mod config;
+mod control;
+mod cpu;
+mod crash;
+mod dashboard;
+mod digest;
+mod energy;
+mod environment;
+mod events;
+mod flamegraph;
+mod format;
+mod github;
+mod history;
+mod hooks;
+mod html;
+mod import;
+mod ingest;
+mod lock;
+mod manifest;
 mod markdown;
+mod parser;
+mod port;
+mod ratelimit;
+mod release;
 mod report;
+mod repro;
+mod scenario;
+mod schema;
+mod score;
+mod script;
+mod slo;
+mod stats;
+mod status;
+mod submission;
+mod summary;
+mod terminal;
+mod timeline;
+mod timings;
+mod toolchain;
+mod trends;
+mod units;
+mod updates;
+mod validate;
+
+/// Interval the memory usage of a server is sampled at.
+pub const MEMORY_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Duration of the probe checking a framework against the floor of a
+/// scenario before it is measured.
+const PROBE_DURATION: &str = "2s";
+
+/// Variable telling a server which scenario it is benchmarked in, binaries
+/// serving several can leave out work the others shouldn't measure.
+const SCENARIO_ENV: &str = "BENCHBOT_SCENARIO";
 
 #[derive(Debug, Parser)]
-#[clap(version)]
+#[clap(
+    version,
+    subcommand_negates_reqs = true,
+    args_conflicts_with_subcommands = true
+)]
 struct Args {
+    #[clap(subcommand)]
+    command: Option<SubCommand>,
+
     /// Path to workspace directory.
-    #[clap(short)]
-    workspace_dir: PathBuf,
+    #[clap(short, required = true)]
+    workspace_dir: Option<PathBuf>,
 
     /// Path to output file.
-    #[clap(short)]
-    output_dir: PathBuf,
+    #[clap(short, required = true)]
+    output_dir: Option<PathBuf>,
 
     /// Connection count of each benchmark.
-    #[clap(short, default_value = "500")]
+    #[clap(short, default_value = "500", env = "BENCHBOT_CONNECTIONS")]
     connections: usize,
 
     /// Duration of each benchmark in seconds.
-    #[clap(short, default_value = "30")]
+    #[clap(short, default_value = "30", env = "BENCHBOT_DURATION")]
     duration: usize,
 
     /// Url for each benchmark.
-    #[clap(short, default_value = "http://127.0.0.1:3000")]
+    #[clap(short, default_value = "http://127.0.0.1:3000", env = "BENCHBOT_URL")]
     url: String,
 
     /// Cooling down for each benchmark.
-    #[clap(long, default_value = "5")]
+    #[clap(long, default_value = "5", env = "BENCHBOT_CD")]
     cd: u64,
+
+    /// Rounds of each benchmark against the same server, confidence
+    /// intervals are reported from two rounds on.
+    #[clap(long, default_value = "1", env = "BENCHBOT_ROUNDS")]
+    rounds: usize,
+
+    /// Keep adding rounds, from `--rounds` up to `--max-rounds`, until the
+    /// 95% confidence interval of Req/Sec is within this share of the mean,
+    /// e.g. `2%`.
+    #[clap(long, value_name = "PERCENT", env = "BENCHBOT_TARGET_CI")]
+    target_ci: Option<Percent>,
+
+    /// Most rounds of each benchmark with `--target-ci`.
+    #[clap(long, default_value = "10", env = "BENCHBOT_MAX_ROUNDS")]
+    max_rounds: usize,
+
+    /// After the rounds, double the connections from one per thread up to
+    /// this limit until requests fail, reporting the most served without
+    /// errors as Max. Connections.
+    #[clap(long, value_name = "LIMIT", env = "BENCHBOT_PROBE_CONNECTIONS")]
+    probe_connections: Option<u32>,
+
+    /// Columns of the comparison table.
+    #[clap(long, arg_enum, default_value = "full")]
+    columns: ColumnPreset,
+
+    /// Add the 50th, 75th, 90th and 99th percentile of latency to the
+    /// columns of the preset.
+    #[clap(long)]
+    latency_percentiles: bool,
+
+    /// Leave the bytes transferred and the transfer rate out of the columns
+    /// of the preset.
+    #[clap(long)]
+    without_transfer: bool,
+
+    /// Add a column to the ones of the preset, repeatable.
+    #[clap(long, arg_enum, value_name = "COLUMN")]
+    with_column: Vec<Column>,
+
+    /// Leave a column of the preset out, repeatable.
+    #[clap(long, arg_enum, value_name = "COLUMN")]
+    without_column: Vec<Column>,
+
+    /// Decimal places of numbers in the comparison table.
+    #[clap(long)]
+    decimals: Option<usize>,
+
+    /// Unit display of numbers in the comparison table.
+    #[clap(long, arg_enum, default_value = "raw")]
+    units: UnitDisplay,
+
+    /// Thousands separator of numbers in the comparison table.
+    #[clap(long)]
+    thousands_separator: Option<char>,
+
+    /// Mark the three fastest frameworks with medals, frameworks showing the
+    /// same Req/Sec share one.
+    #[clap(long)]
+    medals: bool,
+
+    /// Latency objectives, each adding a column with the share of requests
+    /// faster than it, e.g. `--slo 1ms,5ms,10ms`.
+    #[clap(
+        long,
+        value_name = "DURATION",
+        value_parser = slo::parse,
+        use_value_delimiter = true,
+        env = "BENCHBOT_SLO"
+    )]
+    slo: Vec<Duration>,
+
+    /// Add an Apdex column of this target latency: requests within it are
+    /// satisfied, within four times it tolerated.
+    #[clap(long, value_name = "DURATION", value_parser = slo::parse, env = "BENCHBOT_APDEX")]
+    apdex: Option<Duration>,
+
+    /// Write events of the run as JSON Lines to this file, or a
+    /// `tcp://host:port` socket, as it progresses.
+    #[clap(long, value_name = "TARGET", env = "BENCHBOT_EVENTS")]
+    events: Option<String>,
+
+    /// Sample the stacks of each server with `perf` while it's measured and
+    /// render them as flamegraphs, requires `inferno`. With `--baseline` of
+    /// a run profiled too, also renders how they changed.
+    #[clap(long)]
+    flamegraphs: bool,
+
+    /// Order of the rows of comparison tables.
+    #[clap(long, arg_enum, default_value = "req-per-sec")]
+    sort: SortKey,
+
+    /// How the comparison of each scenario is printed, the markdown output
+    /// and results files are written either way.
+    #[clap(long, arg_enum, default_value = "table")]
+    format: OutputFormat,
+
+    /// Record and chart Req/Sec for every second of each benchmark, wrk
+    /// handles every response in Lua then which costs some throughput.
+    #[clap(long)]
+    timeline: bool,
+
+    /// Fail benchmarks whose output lacks a metric the load tool always
+    /// prints, instead of reporting it as zero.
+    #[clap(long)]
+    strict_parsing: bool,
+
+    /// Path to config file, defaults to `bench.toml` in the workspace directory.
+    #[clap(long, env = "BENCHBOT_CONFIG")]
+    config: Option<PathBuf>,
+
+    /// Profile of the config to take run parameters from, flags given on
+    /// the command line take precedence.
+    #[clap(long, env = "BENCHBOT_PROFILE")]
+    profile: Option<String>,
+
+    /// Override a config key, e.g. `--set thresholds.rps=5%`. Takes
+    /// precedence over the file and `BENCHBOT_<TABLE>__<KEY>` variables.
+    #[clap(long = "set", value_name = "KEY=VALUE")]
+    overrides: Vec<Override>,
+
+    /// Results file of a previous run to check for regressions against.
+    #[clap(long, env = "BENCHBOT_BASELINE")]
+    baseline: Option<PathBuf>,
+
+    /// Open or update a GitHub issue for every regressed framework,
+    /// mentioning its maintainers. Meant for scheduled runs, requires `gh`.
+    #[clap(long)]
+    file_issues: bool,
+
+    /// Comment the results on this pull request, with presigned links to
+    /// the uploaded artifacts. Requires `gh`.
+    #[clap(long, value_name = "NUMBER", env = "BENCHBOT_PR_COMMENT")]
+    pr_comment: Option<u64>,
+
+    /// Attach the results files, pages and charts to the GitHub Release of
+    /// this tag, created when missing, for permalinks. Requires `gh`.
+    #[clap(long, value_name = "TAG", env = "BENCHBOT_RELEASE_TAG")]
+    release_tag: Option<String>,
+
+    /// What to do with a run repeating a stored one exactly: same commit,
+    /// environment, config and seed.
+    #[clap(long, arg_enum, default_value = "replicate")]
+    duplicates: DuplicateRuns,
+
+    /// Refuse to start when the estimated wall-clock time of the run
+    /// exceeds this, in seconds or with a unit like `90m` or `6h`.
+    #[clap(
+        long,
+        value_name = "DURATION",
+        value_parser = budget::parse,
+        env = "BENCHBOT_MAX_DURATION"
+    )]
+    max_duration: Option<Duration>,
+
+    /// Lower the rounds until the run fits `--max-duration` instead of
+    /// refusing to start.
+    #[clap(long, requires = "max-duration")]
+    fit_rounds: bool,
+
+    /// Queue up behind a run in progress on this machine instead of
+    /// refusing to start.
+    #[clap(long)]
+    wait: bool,
+
+    /// Repeat the run of a bundle written by `export-repro`: its config,
+    /// lockfile, toolchain and parameters replace the given ones.
+    #[clap(long, value_name = "TARBALL")]
+    from_repro: Option<PathBuf>,
+}
+
+#[derive(Debug, Subcommand)]
+enum SubCommand {
+    /// List suspected performance cliffs in the run history.
+    Trends {
+        /// Path to the history file written by previous runs.
+        #[clap(long)]
+        history: PathBuf,
+
+        /// Number of preceding runs each point is compared against.
+        #[clap(long, default_value = "5")]
+        window: usize,
+
+        /// Deviation in standard deviations that counts as a cliff.
+        #[clap(long, default_value = "3")]
+        z_score: f64,
+
+        /// Workspace whose implementations list the names they had before,
+        /// to see renamed frameworks as one series.
+        #[clap(short)]
+        workspace_dir: Option<PathBuf>,
+    },
+    /// Import the comparison tables of markdown reports published before
+    /// runs kept a history, so trends reach back further.
+    ImportMarkdown {
+        /// Markdown reports, each imported as one run.
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+
+        /// Scenario the reports are of.
+        #[clap(long)]
+        scenario: String,
+
+        /// Path to the history file to append to.
+        #[clap(long)]
+        history: PathBuf,
+    },
+    /// Summarize the runs of the last days: top movers, new frameworks,
+    /// failures and Req/Sec trends.
+    Digest {
+        /// Path to the history file written by previous runs.
+        #[clap(long)]
+        history: PathBuf,
+
+        /// Length of the period in days.
+        #[clap(long, default_value = "7")]
+        days: u64,
+
+        /// Mail the digest to the recipients configured in this file instead
+        /// of printing it.
+        #[clap(long)]
+        send: Option<PathBuf>,
+
+        /// Workspace whose implementations list the names they had before,
+        /// to see renamed frameworks as one series.
+        #[clap(short)]
+        workspace_dir: Option<PathBuf>,
+    },
+    /// List frameworks with new releases their implementations can't use yet.
+    CheckUpdates {
+        /// Path to workspace directory.
+        #[clap(short)]
+        workspace_dir: PathBuf,
+
+        /// Commit the updated requirements and lockfile to a new branch of
+        /// this name, to benchmark against the current results.
+        #[clap(long)]
+        branch: Option<String>,
+    },
+    /// Stop the run writing to an output directory, keeping the results of
+    /// the frameworks benchmarked so far.
+    Cancel {
+        /// Output directory of the run.
+        #[clap(short)]
+        output_dir: PathBuf,
+    },
+    /// Abandon the framework currently benchmarked by the run writing to an
+    /// output directory and continue with the next one.
+    Skip {
+        /// Output directory of the run.
+        #[clap(short)]
+        output_dir: PathBuf,
+    },
+    /// Check the config for unknown keys, invalid values and frameworks or
+    /// scenarios missing in the workspace. Runs check it as well.
+    Validate {
+        /// Path to workspace directory.
+        #[clap(short)]
+        workspace_dir: PathBuf,
+
+        /// Path to config file, defaults to `bench.toml` in the workspace directory.
+        #[clap(long)]
+        config: Option<PathBuf>,
+    },
+    /// Turn output of any supported load tool, saved per framework, into a
+    /// results file, e.g. of a Node.js baseline measured with autocannon,
+    /// to compare, submit or pass as `--baseline`.
+    Ingest {
+        /// Output of a load tool per framework, as `<framework>=<file>`.
+        #[clap(required = true)]
+        outputs: Vec<ingest::SavedOutput>,
+
+        /// Results file to write.
+        #[clap(short, long)]
+        output: PathBuf,
+
+        /// Fail on output missing a metric the tool always prints.
+        #[clap(long)]
+        strict_parsing: bool,
+    },
+    /// Print the change of Req/Sec, latency and memory of every framework
+    /// between two results files, e.g. before and after an upgrade.
+    Compare {
+        /// Results file to compare against.
+        baseline: PathBuf,
+
+        /// Results file of the run to check.
+        current: PathBuf,
+    },
+    /// Describe how a metric of the comparison table is defined and
+    /// collected, all metrics without an argument.
+    Explain {
+        #[clap(arg_enum)]
+        metric: Option<Column>,
+    },
+    /// Print the JSON Schema of a file format, to validate files or generate
+    /// types in other tools.
+    Schema {
+        #[clap(arg_enum)]
+        format: schema::Format,
+    },
+    /// Print a completion script for a shell, e.g.
+    /// `bench-bot completions bash > /etc/bash_completion.d/bench-bot`.
+    Completions {
+        #[clap(arg_enum)]
+        shell: clap_complete::Shell,
+    },
+    /// Pack the config, lockfile, toolchain and parameters of a run into a
+    /// tarball, to repeat it elsewhere with `--from-repro`.
+    ExportRepro {
+        /// Id of the run as in the history file.
+        run_id: String,
+
+        /// Output directory of the run.
+        #[clap(short)]
+        output_dir: PathBuf,
+
+        /// Path of the tarball, defaults to `repro-<run id>.tar.gz`.
+        #[clap(long)]
+        to: Option<PathBuf>,
+    },
+    /// Print the description of every scenario as markdown, the sections
+    /// reports include for theirs.
+    Scenarios,
+    /// Print the man page.
+    Man,
+    /// Serve a web page showing the progress and log of the run writing to
+    /// an output directory, with links to its reports.
+    Dashboard {
+        /// Output directory of the run.
+        #[clap(short)]
+        output_dir: PathBuf,
+
+        /// Address to listen on.
+        #[clap(long, default_value = "127.0.0.1:8080")]
+        addr: String,
+
+        /// Store results posted by `submit` and show their distribution per
+        /// framework, apart from the run's own.
+        #[clap(long)]
+        accept_submissions: bool,
+
+        /// Require `?token=<TOKEN>` to skip or cancel the run from the
+        /// dashboard. Without it anyone can, unless submissions are accepted,
+        /// then no one can.
+        #[clap(long, env = "BENCHBOT_CONTROL_TOKEN")]
+        control_token: Option<String>,
+    },
+    /// Contribute a results file measured on your own hardware to the
+    /// community results of a dashboard. Only the machine profile is sent
+    /// along, CPU, OS and tool versions.
+    Submit {
+        /// Results file of the scenario, `<scenario>.json` of an output
+        /// directory.
+        results: PathBuf,
+
+        /// Url of the dashboard accepting submissions.
+        #[clap(long)]
+        to: String,
+
+        /// Scenario of the results, defaults to the file name.
+        #[clap(long)]
+        scenario: Option<String>,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -58,36 +503,771 @@ struct Workspace {
 }
 
 fn main() {
-    let args = Args::parse();
+    let matches = Args::command().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-    env_logger::builder()
+    let mut logger = env_logger::builder();
+    logger
         .format(|buf, record| {
             let ts = buf.timestamp();
             let level = buf.default_styled_level(record.level());
             writeln!(buf, "[{} {}] {}", ts, level, record.args())
         })
-        .filter_module("bench_bot", LevelFilter::Info)
-        .init();
+        .filter_module("bench_bot", LevelFilter::Info);
+
+    // keep the log of a run next to its results for the dashboard
+    if let (None, Some(output_dir)) = (&args.command, &args.output_dir) {
+        if let Ok(file) = fs::create_dir_all(output_dir)
+            .and_then(|_| fs::File::create(output_dir.join(status::LOG_FILE)))
+        {
+            logger.target(env_logger::Target::Pipe(Box::new(status::Tee(file))));
+        }
+    }
+
+    logger.init();
+
+    match &args.command {
+        Some(SubCommand::Trends {
+            history,
+            window,
+            z_score,
+            workspace_dir,
+        }) => {
+            let records = load_history(history, workspace_dir.as_deref());
+            let cliffs = trends::find_cliffs(&records, *window, *z_score);
+
+            if cliffs.is_empty() {
+                log::info!("No performance cliffs found in {} runs.", records.len());
+            } else {
+                println!("{}", trends::cliffs_table(&cliffs));
+            }
+        }
+        Some(SubCommand::ImportMarkdown {
+            files,
+            scenario,
+            history,
+        }) => {
+            let records = if history.exists() {
+                history::load(history).unwrap()
+            } else {
+                Vec::new()
+            };
+
+            for file in files {
+                let record = import::import(file, scenario).unwrap_or_else(|e| {
+                    log::error!("{}", e);
+                    process::exit(1);
+                });
+
+                if records
+                    .iter()
+                    .any(|r| r.id == record.id && r.scenario == record.scenario)
+                {
+                    log::info!("Skipping {:?}, it was imported before.", file);
+                    continue;
+                }
+
+                history::append(history, &record).unwrap();
+                log::info!(
+                    "Imported {} frameworks of {:?} as run {}.",
+                    record.reports.len(),
+                    file,
+                    record.id
+                );
+            }
+        }
+        Some(SubCommand::Digest {
+            history,
+            days,
+            send,
+            workspace_dir,
+        }) => {
+            let records = load_history(history, workspace_dir.as_deref());
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs();
+            let html =
+                digest::build(&records, now.saturating_sub(days * 24 * 60 * 60)).to_html(*days);
+
+            match send {
+                Some(config) => {
+                    let config = Config::load(config).unwrap();
+                    let mail = config.digest.expect("no [digest] section in config");
+                    digest::send(&mail, "bench-bot digest", &html).unwrap();
+                    log::info!("Mailed digest to {}.", mail.to.join(", "));
+                }
+                None => println!("{}", html),
+            }
+        }
+        Some(SubCommand::CheckUpdates {
+            workspace_dir,
+            branch,
+        }) => {
+            let members = workspace_members(workspace_dir);
+
+            let updates = updates::check(workspace_dir, &members);
+            if updates.is_empty() {
+                log::info!("All {} frameworks are up to date.", members.len());
+                return;
+            }
+
+            println!("{}", updates::updates_table(&updates));
+
+            if let Some(branch) = branch {
+                if let Err(e) = updates::commit_branch(workspace_dir, branch, &updates) {
+                    log::error!("Committing the updates failed: {}", e);
+                    process::exit(1);
+                }
+                log::info!(
+                    "Committed updates to {}, benchmark it with `--baseline` pointing at the \
+                        current results to see what changed.",
+                    branch
+                );
+            }
+        }
+        Some(SubCommand::Cancel { output_dir }) => Control::Cancel.send(output_dir).unwrap(),
+        Some(SubCommand::Skip { output_dir }) => Control::Skip.send(output_dir).unwrap(),
+        Some(SubCommand::Validate {
+            workspace_dir,
+            config,
+        }) => {
+            let path = config_path(workspace_dir, config.as_deref());
 
+            let benchmarks = manifest::benchmarks(workspace_dir, &workspace_members(workspace_dir))
+                .unwrap_or_else(|problems| {
+                    for problem in problems {
+                        log::error!("{}", problem);
+                    }
+                    process::exit(1);
+                });
+
+            match validate::load(path.as_deref(), &config::env_overrides(), &benchmarks) {
+                Ok(_) => log::info!(
+                    "{} is valid.",
+                    path.map_or("The default config".to_string(), |p| format!("{:?}", p))
+                ),
+                Err(problems) => {
+                    for problem in problems {
+                        log::error!("{}", problem);
+                    }
+                    process::exit(1);
+                }
+            }
+        }
+        Some(SubCommand::Ingest {
+            outputs,
+            output,
+            strict_parsing,
+        }) => {
+            let reports = outputs
+                .iter()
+                .map(|saved| ingest::ingest(saved, *strict_parsing))
+                .collect::<Result<Vec<Report>, String>>()
+                .unwrap_or_else(|e| {
+                    log::error!("{}", e);
+                    process::exit(1);
+                });
+
+            let results = Results {
+                environment: Environment::default(),
+                reports,
+                timings: BTreeMap::new(),
+                script: None,
+                command: None,
+            };
+            compare::save_results(output, &results).unwrap_or_else(|e| {
+                log::error!("{}", e);
+                process::exit(1);
+            });
+            log::info!("Wrote {} reports to {:?}.", results.reports.len(), output);
+        }
+        Some(SubCommand::Compare { baseline, current }) => {
+            let load = |path: &Path| {
+                compare::load_results(path).unwrap_or_else(|e| {
+                    log::error!("{}", e);
+                    process::exit(1);
+                })
+            };
+            let (baseline, current) = (load(baseline), load(current));
+
+            if !baseline.environment.is_empty() {
+                for change in baseline.environment.diff(&current.environment) {
+                    log::warn!(
+                        "Environment differs from baseline: {} was {:?}, is {:?}.",
+                        change.key,
+                        change.baseline.as_deref().unwrap_or("-"),
+                        change.current.as_deref().unwrap_or("-")
+                    );
+                }
+            }
+
+            println!(
+                "{}",
+                Report::generate_deltas(
+                    &baseline.reports,
+                    &current.reports,
+                    &NumberFormat::default()
+                )
+            );
+        }
+        Some(SubCommand::Explain { metric }) => {
+            let columns = match metric {
+                Some(column) => vec![*column],
+                None => Column::value_variants().to_vec(),
+            };
+            let text: Vec<String> = columns
+                .iter()
+                .map(|column| column.explain().to_text(column.header()))
+                .collect();
+
+            println!("{}", text.join("\n\n"));
+        }
+        Some(SubCommand::Schema { format }) => println!("{}", schema::json_schema(*format)),
+        Some(SubCommand::Completions { shell }) => {
+            clap_complete::generate(*shell, &mut Args::command(), "bench-bot", &mut io::stdout());
+        }
+        Some(SubCommand::ExportRepro {
+            run_id,
+            output_dir,
+            to,
+        }) => {
+            let to = to
+                .clone()
+                .unwrap_or_else(|| PathBuf::from(format!("repro-{}.tar.gz", run_id)));
+
+            match repro::export(output_dir, run_id, &to) {
+                Ok(()) => log::info!("Exported run {} to {:?}.", run_id, to),
+                Err(e) => {
+                    log::error!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        Some(SubCommand::Scenarios) => {
+            let scenarios: Vec<&scenario::Scenario> = scenario::SCENARIOS.iter().collect();
+
+            println!("{}", scenario::document(&scenarios));
+        }
+        Some(SubCommand::Man) => {
+            clap_mangen::Man::new(Args::command())
+                .render(&mut io::stdout())
+                .unwrap();
+        }
+        Some(SubCommand::Dashboard {
+            output_dir,
+            addr,
+            accept_submissions,
+            control_token,
+        }) => {
+            let access = dashboard::Access {
+                accept_submissions: *accept_submissions,
+                control_token: control_token.as_deref(),
+            };
+            dashboard::serve(addr, output_dir, &access).unwrap();
+        }
+        Some(SubCommand::Submit {
+            results,
+            to,
+            scenario,
+        }) => {
+            let scenario = scenario
+                .clone()
+                .or_else(|| Some(results.file_stem()?.to_string_lossy().to_string()))
+                .expect("results file has a name");
+            let loaded = compare::load_results(results).unwrap_or_else(|e| {
+                log::error!("{}", e);
+                process::exit(1);
+            });
+            let submission = Submission::from_results(&scenario, loaded);
+            let url = format!("{}/submissions", to.trim_end_matches('/'));
+
+            match submission::send(&url, &submission) {
+                Ok(()) => log::info!(
+                    "Submitted {} frameworks of {} measured on {}.",
+                    submission.reports.len(),
+                    scenario,
+                    submission.machine.get("cpu").unwrap_or("an unknown CPU")
+                ),
+                Err(e) => {
+                    log::error!("{}", e);
+                    process::exit(1);
+                }
+            }
+        }
+        None => run(args, &matches),
+    }
+}
+
+/// Records of the history file at `path`, with frameworks renamed in the
+/// workspace at `workspace_dir` under their current name.
+fn load_history(path: &Path, workspace_dir: Option<&Path>) -> Vec<RunRecord> {
+    let mut records = history::load(path).unwrap();
+    if let Some(workspace_dir) = workspace_dir {
+        history::stitch(
+            &mut records,
+            &manifest::aliases(workspace_dir, &workspace_members(workspace_dir)),
+        );
+    }
+
+    records
+}
+
+/// Rounds of each benchmark at most, adaptive runs stop earlier once
+/// confident.
+fn most_rounds(args: &Args) -> usize {
+    match args.target_ci {
+        Some(_) => args.max_rounds.max(args.rounds).max(1),
+        None => args.rounds.max(1),
+    }
+}
+
+/// Whether the confidence interval of Req/Sec across `outputs` is within
+/// `target`. Output failing to parse won't get there with more rounds.
+fn confident(outputs: &[process::Output], target: Percent) -> bool {
+    let mut samples = Vec::new();
+    for output in outputs {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        samples.extend(
+            parser::split_rounds(&stdout)
+                .into_iter()
+                .map(|round| parser::parse(round, false).map(|metrics| metrics.req_per_sec())),
+        );
+    }
+    let samples: Result<Vec<f64>, _> = samples.into_iter().collect();
+
+    match samples {
+        Ok(samples) => {
+            stats::relative_half_width(&samples).is_some_and(|width| width * 100.0 <= target.0)
+        }
+        Err(_) => true,
+    }
+}
+
+/// `wrk` with `args` against `url`, as recorded with the results.
+fn command_line(args: &[&str], url: &str) -> String {
+    let mut command = "wrk".to_owned();
+    for arg in args.iter().chain([&url]) {
+        command.push(' ');
+        command.push_str(arg);
+    }
+
+    command
+}
+
+/// Print `text` meant for people, to stderr when stdout carries `format`
+/// for machines.
+fn print_aside(format: OutputFormat, text: &str) {
+    match format {
+        OutputFormat::Table => println!("{}", text),
+        OutputFormat::Json => eprintln!("{}", text),
+    }
+}
+
+/// Req/Sec of the first round of `output` measuring less than `floor`.
+/// Output failing to parse is left to the parsing after measuring.
+fn below_floor(output: &process::Output, floor: f64) -> Option<f64> {
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    parser::split_rounds(&stdout)
+        .into_iter()
+        .filter_map(|round| parser::parse(round, false).ok())
+        .map(|metrics| metrics.req_per_sec())
+        .find(|req_per_sec| *req_per_sec < floor)
+}
+
+fn run(mut args: Args, matches: &ArgMatches) {
     log::info!("Bench Bot started.");
 
-    let ws_toml_path = args.workspace_dir.join("Cargo.toml");
-    let ws_toml = fs::read(&ws_toml_path).unwrap();
+    let started = Instant::now();
+    let run_started = SystemTime::now();
+    let workspace_dir = args.workspace_dir.clone().expect("required by clap");
+    let output_dir = args.output_dir.clone().expect("required by clap");
 
-    let cargo: Cargo = toml::from_slice(&ws_toml).unwrap();
-    let members = expand_members(cargo.workspace.members, &args.workspace_dir);
+    let bundle = args.from_repro.as_ref().map(|path| {
+        repro::import(path).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            process::exit(1);
+        })
+    });
+
+    // file < environment < command line
+    let mut overrides = config::env_overrides();
+    overrides.extend(args.overrides.iter().cloned());
+
+    if let Some(bundle) = &bundle {
+        let (repro, parameters) = (&bundle.repro, &bundle.repro.parameters);
+        log::info!(
+            "Reproducing run {} of commit {}.",
+            repro.id,
+            repro.commit.as_deref().unwrap_or("unknown")
+        );
+
+        let replaced = [
+            ("connections", "-c"),
+            ("duration", "-d"),
+            ("url", "-u"),
+            ("cd", "--cd"),
+            ("rounds", "--rounds"),
+            ("target-ci", "--target-ci"),
+            ("max-rounds", "--max-rounds"),
+            ("timeline", "--timeline"),
+            ("profile", "--profile"),
+            ("config", "--config"),
+            ("overrides", "--set"),
+        ];
+        // the bundle of a run without a target interval keeps the given most
+        for (id, flag) in replaced
+            .into_iter()
+            .filter(|(id, _)| *id != "max-rounds" || parameters.max_rounds.is_some())
+        {
+            if matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            ) {
+                log::warn!("Ignoring {}, the bundle replaces it.", flag);
+            }
+        }
+
+        args.connections = parameters.connections;
+        args.duration = parameters.duration;
+        args.url = parameters.url.clone();
+        args.cd = parameters.cooldown;
+        args.rounds = parameters.rounds;
+        args.target_ci = parameters.target_ci.map(Percent);
+        args.max_rounds = parameters.max_rounds.unwrap_or(args.max_rounds);
+        args.timeline = parameters.timeline;
+        args.profile = repro.profile.clone();
+        args.config = bundle.config_file();
+        overrides = repro
+            .overrides
+            .iter()
+            .map(|o| o.parse().map_err(|e| format!("{} in the bundle", e)))
+            .collect::<Result<_, String>>()
+            .unwrap_or_else(|e| {
+                log::error!("{}", e);
+                process::exit(1);
+            });
+    }
+
+    let mut members = workspace_members(&workspace_dir);
+    let mut benchmarks =
+        manifest::benchmarks(&workspace_dir, &members).unwrap_or_else(|problems| {
+            for problem in problems {
+                log::error!("{}", problem);
+            }
+            process::exit(1);
+        });
+
+    let undefined: BTreeSet<&str> = benchmarks
+        .iter()
+        .map(|benchmark| benchmark.scenario.as_str())
+        .filter(|name| scenario::find(name).is_none())
+        .collect();
+    for name in undefined {
+        log::warn!(
+            "Scenario {:?} has no definition, its reports won't describe it.",
+            name
+        );
+    }
+
+    let config_file = config_path(&workspace_dir, args.config.as_deref());
+    let mut config = validate::load(config_file.as_deref(), &overrides, &benchmarks)
+        .unwrap_or_else(|problems| {
+            for problem in problems {
+                log::error!("{}", problem);
+            }
+            process::exit(1);
+        });
+
+    if let Some(name) = &args.profile {
+        let Some(profile) = config.profiles.get(name) else {
+            let names: Vec<&String> = config.profiles.keys().collect();
+            log::error!("Unknown profile {:?}, configured are {:?}.", name, names);
+            process::exit(1);
+        };
+
+        let given = |id: &str| {
+            matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        };
+        if let Some(duration) = profile.duration.filter(|_| !given("duration")) {
+            args.duration = duration;
+        }
+        if let Some(connections) = profile.connections.filter(|_| !given("connections")) {
+            args.connections = connections;
+        }
+        if let Some(rounds) = profile.rounds.filter(|_| !given("rounds")) {
+            args.rounds = rounds;
+        }
+        if let Some(cd) = profile.cooldown.filter(|_| !given("cd")) {
+            args.cd = cd;
+        }
+        if let Some(scenarios) = &profile.scenarios {
+            benchmarks.retain(|benchmark| scenarios.contains(&benchmark.scenario));
+            members.retain(|member| {
+                benchmarks
+                    .iter()
+                    .any(|benchmark| &benchmark.member == member)
+            });
+        }
+        if let Some(toolchain) = &profile.toolchain {
+            config.toolchain = Some(toolchain.clone());
+        }
+
+        log::info!("Using profile {:?}.", name);
+    }
+
+    // checked before building, a missing script fails the run right away
+    let scripts =
+        script::install(&config.scripts, &workspace_dir, &output_dir).unwrap_or_else(|e| {
+            log::error!("{}.", e);
+            process::exit(1);
+        });
+    if args.timeline && !scripts.is_empty() {
+        let scenarios: Vec<&String> = scripts.keys().collect();
+        log::warn!(
+            "wrk runs a single script, scenarios {:?} get no timeline.",
+            scenarios
+        );
+    }
+
+    // an unpinned run is reproduced with the stable release it was built with
+    if let Some(bundle) = bundle.as_ref().filter(|_| config.toolchain.is_none()) {
+        config.toolchain = bundle
+            .repro
+            .rustc
+            .as_deref()
+            .and_then(|rustc| rustc.split_whitespace().nth(1))
+            .filter(|version| !version.contains('-'))
+            .map(str::to_string);
+    }
+
+    let baseline = args.baseline.as_ref().map(|path| {
+        let mut baseline = compare::load_results(path).unwrap();
+        // reports of earlier versions don't know their scenario, results
+        // files are named after it
+        if let Some(scenario) = path.file_stem().map(|s| s.to_string_lossy()) {
+            baseline.reports = baseline
+                .reports
+                .into_iter()
+                .map(|r| match r.scenario() {
+                    Some(_) => r,
+                    None => r.with_scenario(&scenario),
+                })
+                .collect();
+        }
+
+        baseline
+    });
+
+    // timings of the baseline are the best guess of how long building and
+    // starting servers takes on this machine
+    // adaptive runs are planned for the worst case
+    let planned_rounds = most_rounds(&args);
+    let plan = budget::Plan {
+        builds: members.len(),
+        benchmarks: benchmarks.len(),
+        rounds: planned_rounds,
+        duration: args.duration as f64,
+        cooldown: args.cd as f64,
+    };
+    let overheads = baseline
+        .as_ref()
+        .map_or_else(budget::Overheads::default, |baseline| {
+            budget::Overheads::from_timings(&baseline.timings)
+        });
+    let estimate = plan.estimate(&overheads);
+    log::info!(
+        "Estimated duration of the run: {}.",
+        budget::human(estimate)
+    );
+
+    if let Some(max_duration) = args.max_duration.filter(|max| estimate > max.as_secs_f64()) {
+        let budget = max_duration.as_secs_f64();
+
+        match plan
+            .fit_rounds(&overheads, budget)
+            .filter(|_| args.fit_rounds)
+        {
+            Some(rounds) => {
+                log::warn!(
+                    "Lowering rounds from {} to {} to fit the budget of {}, the run takes about {} \
+                        then.",
+                    planned_rounds,
+                    rounds,
+                    budget::human(budget),
+                    budget::human(budget::Plan { rounds, ..plan }.estimate(&overheads))
+                );
+                args.rounds = args.rounds.min(rounds);
+                args.max_rounds = rounds;
+            }
+            None => {
+                log::error!(
+                    "Refusing to run: the estimated {} exceed the budget of {}.",
+                    budget::human(estimate),
+                    budget::human(budget)
+                );
+                process::exit(1);
+            }
+        }
+    }
+
+    if args.target_ci.is_some() && most_rounds(&args) < stats::MIN_CI_SAMPLES {
+        log::warn!(
+            "Up to {} rounds won't reach --target-ci, confidence intervals take at least {} \
+                rounds.",
+            most_rounds(&args),
+            stats::MIN_CI_SAMPLES
+        );
+    }
+
+    // every cargo and rustc invocation below inherits the pinned toolchain
+    if let Some(channel) = &config.toolchain {
+        match toolchain::install(channel) {
+            Ok(version) => log::info!("Using toolchain {} ({}).", channel, version),
+            Err(e) => {
+                log::error!("Refusing to run: {}", e);
+                process::exit(1);
+            }
+        }
+        env::set_var("RUSTUP_TOOLCHAIN", channel);
+    }
+
+    let acquired = if args.wait {
+        lock::RunLock::wait(lock::machine_lock())
+    } else {
+        lock::RunLock::acquire(lock::machine_lock())
+    };
+    let lock = match acquired {
+        Ok(lock) => lock,
+        Err(e) => {
+            log::error!("Refusing to run: {}", e);
+            process::exit(1);
+        }
+    };
 
     let mut exclude = Vec::new();
+    let mut failures = 0;
+    let mut breakages = Vec::new();
+    // by scenario and framework, a build serving several scenarios counts
+    // with the first of them benchmarked
+    let mut timings: BTreeMap<(String, String), PhaseTimings> = BTreeMap::new();
+    let mut build_times: BTreeMap<String, f64> = BTreeMap::new();
+    // framework version each member was built against
+    let mut versions: BTreeMap<PathBuf, String> = BTreeMap::new();
+
+    fs::create_dir_all(&output_dir).unwrap();
+    // a request left over from an earlier run must not cancel this one
+    let _ = Control::take(&output_dir);
+    let mut status = Status {
+        queue: members.iter().map(|m| m.display().to_string()).collect(),
+        ..Status::default()
+    };
+    let mut events = match &args.events {
+        Some(target) => EventStream::open(target).unwrap_or_else(|e| {
+            log::error!("{}", e);
+            process::exit(1);
+        }),
+        None => EventStream::none(),
+    };
+    // the workspace's own is back once the run is over
+    let lock_file = bundle
+        .as_ref()
+        .map(|bundle| bundle.replace_lock_file(&workspace_dir))
+        .transpose()
+        .unwrap_or_else(|e| {
+            log::error!("{}", e);
+            process::exit(1);
+        });
+    events.emit(Event::RunStarted {
+        frameworks: members
+            .iter()
+            .filter_map(|m| Some(m.file_name()?.to_string_lossy().to_string()))
+            .collect(),
+        benchmarks: benchmarks.iter().map(ToString::to_string).collect(),
+        rounds: args.rounds,
+    });
 
+    // a failing hook is the user's instrumentation, not the benchmark's
+    let hook = |phase: hooks::Phase, context: hooks::Context| {
+        if let Err(e) = hooks::run(&config.hooks, phase, &context) {
+            log::warn!("{}.", e);
+        }
+    };
+
+    // a binary serving several scenarios is built once for all of them
     for member in &members {
-        log::info!("Building {:?}", member);
+        let served: Vec<&str> = benchmarks
+            .iter()
+            .filter(|benchmark| &benchmark.member == member)
+            .map(|benchmark| benchmark.scenario.as_str())
+            .collect();
+        if served.len() > 1 {
+            log::info!("Building {:?} for scenarios {}", member, served.join(", "));
+        } else {
+            log::info!("Building {:?}", member);
+        }
+        status.update(&output_dir, "building", Some(&member.display().to_string()));
+        hook(
+            hooks::Phase::PreBuild,
+            hooks::Context {
+                framework: &member.file_name().unwrap().to_string_lossy(),
+                scenario: None,
+                url: None,
+                server_pid: None,
+                round: None,
+                workspace_dir: &workspace_dir,
+                output_dir: &output_dir,
+            },
+        );
+
+        let member_manifest = Manifest::load(&workspace_dir.join(member)).unwrap_or_default();
+        if let Some(git) = &member_manifest.git {
+            log::info!(
+                "Pinning {} to {} of {}.",
+                member_manifest.framework_crate(member),
+                git.rev,
+                git.url
+            );
+        }
 
+        let build_started = Instant::now();
         let output = Command::new("cargo")
             .args(["build", "--release"])
-            .current_dir(args.workspace_dir.join(member))
+            .args(member_manifest.cargo_args(member))
+            .current_dir(workspace_dir.join(member))
             .output()
             .unwrap();
 
+        let framework_name = member.file_name().unwrap().to_string_lossy().to_string();
+        events.emit(Event::Built {
+            framework: framework_name.clone(),
+            success: output.status.success(),
+            seconds: timings::secs(build_started.elapsed()),
+        });
+        build_times.insert(framework_name, timings::secs(build_started.elapsed()));
+
+        // every build resolves the shared lockfile anew, with or without the
+        // pinned revision of this member, so it's read right after
+        let framework_crate = member_manifest.framework_crate(member);
+        let lockfile = fs::read_to_string(workspace_dir.join("Cargo.lock")).unwrap_or_default();
+        if let Some(version) = manifest::resolved_version(&lockfile, &framework_crate)
+            .filter(|_| output.status.success())
+        {
+            if member_manifest.git.is_some() && !version.contains("(git ") {
+                log::warn!(
+                    "{:?} doesn't use the pinned {}, cargo ignores patches of another version than \
+                        the one required.",
+                    member,
+                    framework_crate
+                );
+            }
+            versions.insert(member.clone(), version);
+        }
+
         if !output.status.success() {
             log::error!(
                 "Building {:?} failed: \n{}",
@@ -95,19 +1275,30 @@ fn main() {
                 String::from_utf8_lossy(&output.stderr)
             );
             exclude.push(member.clone());
+            failures += 1;
+            breakages.push(github::Breakage {
+                framework_name: member.file_name().unwrap().to_string_lossy().to_string(),
+                stage: "build",
+                log: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
         }
     }
 
     let sys = System::new_all();
+    let environment = Environment::capture(&sys);
 
     let cpu_name = sys.global_cpu_info().brand();
     let cpu_count = sys.cpus().len().to_string();
     let conn_count = args.connections.to_string();
     let duration = format!("{}s", args.duration);
     let cd = args.cd;
-    let members_len = members.len();
+    let benchmarks_len = benchmarks.len();
+
+    let server_pidfile = env::temp_dir().join("bench-bot-server.pid");
+    let timeline_script = env::temp_dir().join("bench-bot-timeline.lua");
+    let timeline_script = timeline_script.to_str().unwrap();
 
-    let wrk_args = [
+    let mut wrk_args = vec![
         "--threads",
         &cpu_count,
         "--connections",
@@ -115,57 +1306,160 @@ fn main() {
         "--duration",
         &duration,
         "--latency",
-        &args.url
     ];
 
-    let mut bench_command = "wrk".to_owned();
-    for arg in wrk_args {
-        bench_command.push(' ');
-        bench_command.push_str(arg);
+    if args.timeline {
+        fs::write(timeline_script, timeline::SCRIPT).unwrap();
+        wrk_args.extend(["--script", timeline_script]);
     }
 
-    println!("Benchmark Command: {}", bench_command);
+    // scenarios add their script and headers, see `scenario_commands`
+    let bench_command = command_line(&wrk_args, &args.url);
+
+    print_aside(
+        args.format,
+        &format!("Benchmark Command: {}", bench_command),
+    );
 
-    let mut base_md = Markdown::new();
+    // what each scenario was measured with, and probed with against its floor
+    let mut scenario_commands: BTreeMap<String, String> = BTreeMap::new();
+    let mut probe_commands: BTreeMap<String, String> = BTreeMap::new();
 
-    base_md.add_item("Generated by bench-bot.");
-    base_md.add_item("# Hardware");
-    base_md.add_item("## Cpu");
-    base_md.add_item(cpu_name);
-    base_md.add_item("# Benchmark");
-    base_md.add_item("Command:");
-    base_md.add_item(format!("```\n{}\n```", bench_command));
+    let base_md = |command: &str, probe: Option<&String>| {
+        let mut base_md = Markdown::new();
+
+        base_md.add_item("# Environment");
+        base_md.add_item(environment.to_markdown());
+        base_md.add_item("# Benchmark");
+        base_md.add_item("Command:");
+        base_md.add_item(format!("```\n{}\n```", command));
+        if let Some(probe) = probe {
+            base_md.add_item("Probed first against the floor of Req/Sec with:");
+            base_md.add_item(format!("```\n{}\n```", probe));
+        }
+
+        if let Some(target) = args.target_ci {
+            base_md.add_item(format!(
+                "Rounds: {} to {}, added until the confidence interval of Req/Sec is within ±{}% \
+                    of the mean. Intervals are bootstrap 95% confidence intervals of the mean.",
+                args.rounds.max(1),
+                most_rounds(&args),
+                target.0
+            ));
+        } else if args.rounds > 1 {
+            base_md.add_item(format!(
+                "Rounds: {}, intervals are bootstrap 95% confidence intervals of the mean.",
+                args.rounds
+            ));
+        }
+
+        base_md
+    };
 
     let mut output_map = HashMap::new();
     let mut reports = Vec::with_capacity(members.len());
+    let mut scenario_reports: BTreeMap<String, Vec<Report>> = BTreeMap::new();
+
+    status.queue = benchmarks
+        .iter()
+        .filter(|benchmark| !exclude.contains(&benchmark.member))
+        .map(Benchmark::to_string)
+        .collect();
+
+    for (index, benchmark) in benchmarks.iter().enumerate() {
+        let member = &benchmark.member;
+
+        // requests sent while cooling down
+        match Control::take(&output_dir) {
+            Some(Control::Cancel) => {
+                log::warn!("Run cancelled before {}.", benchmark);
+                break;
+            }
+            Some(Control::Skip) => {
+                log::warn!("Skipping {} as requested.", benchmark);
+                status.done.push(format!("{} (skipped)", benchmark));
+                continue;
+            }
+            None => {}
+        }
 
-    for (index, member) in members.iter().enumerate() {
         if exclude.contains(member) {
-            log::warn!("Skipping {:?} because build was failed.", member);
+            log::warn!("Skipping {} because build was failed.", benchmark);
         } else {
-            let bench_type = member
-                .parent()
-                .unwrap()
-                .file_name()
-                .unwrap()
-                .to_str()
-                .unwrap();
+            let bench_type = benchmark.scenario.as_str();
 
             let framework_name = member.file_name().unwrap().to_str().unwrap();
+            let member_manifest = Manifest::load(&workspace_dir.join(member)).unwrap_or_default();
 
             let result_md = output_map.entry(bench_type).or_insert(Markdown::new());
 
-            log::info!("Benchmarking {:?}", member);
+            let url = benchmark.url(&args.url);
+
+            log::info!("Benchmarking {} at {}", benchmark, url);
+            status.update(&output_dir, "benchmarking", Some(&benchmark.to_string()));
+
+            if let Err(e) = port::ensure_free(&url, &server_pidfile, framework_name) {
+                log::error!("Not benchmarking {}: {}.", benchmark, e);
+                events.emit(Event::anomaly(benchmark, Anomaly::PortInUse, e.clone()));
+                failures += 1;
+                breakages.push(github::Breakage {
+                    framework_name: framework_name.to_string(),
+                    stage: "benchmark",
+                    log: e,
+                });
+                status.done.push(format!("{} (port in use)", benchmark));
+                continue;
+            }
 
+            let detail_dir = output_dir.join(bench_type);
+            fs::create_dir_all(&detail_dir).unwrap();
+            let stderr_path = crash::stderr_path(&detail_dir, framework_name);
+
+            let provision_started = Instant::now();
             let mut server = Command::new("cargo")
                 .args(["run", "--release", "-q"])
-                .current_dir(args.workspace_dir.join(member))
+                .args(member_manifest.cargo_args(member))
+                .current_dir(workspace_dir.join(member))
+                .envs(crash::BACKTRACE_ENV)
+                .env(SCENARIO_ENV, bench_type)
+                .stderr(fs::File::create(&stderr_path).unwrap())
                 .spawn()
                 .unwrap();
 
             thread::sleep(Duration::from_secs(1));
+            // gone once crashed, looking for their core dumps needs them
+            let server_pids = cpu::tree_pids(server.id());
+            crash::enable_core_dumps(&server_pids);
+            // `server` is cargo, the server binary is its child
+            let server_process = server_pids.get(1).copied().unwrap_or(server.id());
+            let server_pid =
+                port::ServerPid::record(server_pidfile.clone(), server_process, framework_name)
+                    .map_err(|e| log::warn!("{}.", e))
+                    .ok();
+            let timing = timings
+                .entry((bench_type.to_string(), framework_name.to_string()))
+                .or_insert_with(|| PhaseTimings {
+                    build: build_times.remove(framework_name).unwrap_or_default(),
+                    ..PhaseTimings::default()
+                });
+            timing.provision += timings::secs(provision_started.elapsed());
+
+            let context = |round: Option<usize>, server_pid: Option<u32>| hooks::Context {
+                framework: framework_name,
+                scenario: Some(bench_type),
+                url: Some(&url),
+                server_pid,
+                round,
+                workspace_dir: &workspace_dir,
+                output_dir: &output_dir,
+            };
+            hook(hooks::Phase::PostStart, context(None, Some(server_process)));
+            let collectors = collector::start_all(
+                collector::configured(&config.collectors),
+                &context(None, Some(server_process)),
+            );
 
-            let pid = PidExt::from_u32(server.id());
+            let pid = PidExt::from_u32(server_process);
             let (tx, rx) = mpsc::channel::<()>();
 
             let mem_usage_thread = thread::spawn(move || {
@@ -176,69 +1470,1151 @@ fn main() {
                     max_memory =
                         max_memory.max(sys.process(pid).map(ProcessExt::memory).unwrap_or(0));
 
-                    thread::sleep(Duration::from_millis(100));
+                    thread::sleep(MEMORY_SAMPLE_INTERVAL);
                 }
                 max_memory
             });
 
-            let output = Command::new("wrk").args(wrk_args).output().unwrap();
+            // the timeline script is always the last argument, replaced by
+            // the one of the scenario
+            let mut scenario_args: Vec<&str> = match scripts.get(bench_type) {
+                Some(script) => wrk_args
+                    .iter()
+                    .copied()
+                    .take_while(|arg| *arg != "--script")
+                    .chain(["--script", script.as_str()])
+                    .collect(),
+                None => wrk_args.clone(),
+            };
+            let header_args = scenario::find(bench_type)
+                .map(|scenario| scenario.header_args(&url))
+                .unwrap_or_default();
+            scenario_args.extend(header_args.iter().map(String::as_str));
+            // binaries serving several scenarios get the url of each appended
+            let command = command_line(&scenario_args, &url);
+            scenario_commands
+                .entry(bench_type.to_string())
+                .or_insert_with(|| command.clone());
 
+            let folded = detail_dir.join(format!("{}.folded", framework_name));
+            let recording = if args.flamegraphs {
+                flamegraph::record(
+                    &cpu::tree_pids(server.id()),
+                    &detail_dir.join(format!("{}.perf.data", framework_name)),
+                    &folded,
+                )
+                .map_err(|e| log::warn!("Profiling {} failed: {}.", benchmark, e))
+                .ok()
+            } else {
+                None
+            };
+
+            let measure_started = Instant::now();
+            let mut outputs = Vec::with_capacity(args.rounds);
+            let mut interrupted = None;
+            let floor = config.floors.get(bench_type).copied();
+            let mut pathological = None;
+            let mut probe = None;
+            let mut crashed = None;
+
+            // a short probe spares the full duration to frameworks far too slow
+            if let Some(floor) = floor {
+                let mut probe_args = scenario_args.clone();
+                if let Some(index) = probe_args.iter().position(|arg| *arg == "--duration") {
+                    probe_args[index + 1] = PROBE_DURATION;
+                }
+                probe_commands
+                    .entry(bench_type.to_string())
+                    .or_insert_with(|| command_line(&probe_args, &url));
+                match control::run_interruptible(
+                    Command::new("wrk").args(&probe_args).arg(&url),
+                    &output_dir,
+                )
+                .unwrap()
+                {
+                    Ok(output) => {
+                        pathological = below_floor(&output, floor);
+                        if pathological.is_some() {
+                            probe = Some(output);
+                        }
+                    }
+                    Err(control) => interrupted = Some(control),
+                }
+            }
+
+            // CPU time and energy are of the measured rounds, not the probe
+            let cpu_started = cpu::tree_time(server.id());
+            let energy_started = energy::sample();
+            let rounds = if pathological.is_some() || interrupted.is_some() {
+                0
+            } else {
+                most_rounds(&args)
+            };
+            for round in 1..=rounds {
+                hook(
+                    hooks::Phase::PreMeasure,
+                    context(Some(round), Some(server_process)),
+                );
+                match control::run_interruptible(
+                    Command::new("wrk").args(&scenario_args).arg(&url),
+                    &output_dir,
+                )
+                .unwrap()
+                {
+                    Ok(output) => outputs.push(output),
+                    Err(control) => {
+                        interrupted = Some(control);
+                        break;
+                    }
+                }
+
+                // the remaining rounds would only measure a dead port
+                crashed = server.try_wait().ok().flatten();
+                if crashed.is_some() {
+                    break;
+                }
+
+                pathological = floor.and_then(|floor| below_floor(outputs.last()?, floor));
+                if pathological.is_some() {
+                    break;
+                }
+
+                if round >= args.rounds
+                    && args
+                        .target_ci
+                        .is_some_and(|target| confident(&outputs, target))
+                {
+                    break;
+                }
+            }
+
+            if let (Some(req_per_sec), Some(floor)) = (pathological, floor) {
+                log::warn!(
+                    "{} measured {:.0} Req/Sec, below the floor of {}, skipping its remaining \
+                        rounds.",
+                    benchmark,
+                    req_per_sec,
+                    floor
+                );
+                events.emit(Event::anomaly(
+                    benchmark,
+                    Anomaly::Pathological,
+                    format!("{:.0} Req/Sec below the floor of {}", req_per_sec, floor),
+                ));
+            }
+
+            if let Some(target) = args.target_ci.filter(|target| {
+                interrupted.is_none()
+                    && pathological.is_none()
+                    && crashed.is_none()
+                    && !confident(&outputs, *target)
+            }) {
+                log::warn!(
+                    "{} didn't reach a confidence interval of ±{}% within {} rounds.",
+                    benchmark,
+                    target.0,
+                    outputs.len()
+                );
+            }
+
+            timing.measure += timings::secs(measure_started.elapsed());
+            let collected = collector::stop_all(collectors);
+            let profiled = recording.is_some_and(|recording| {
+                recording
+                    .finish()
+                    .map_err(|e| log::warn!("Profiling {} failed: {}.", benchmark, e))
+                    .is_ok()
+            });
+            let cpu_time = cpu_started
+                .zip(cpu::tree_time(server.id()))
+                .map(|(started, ended)| ended - started);
+            let joules = energy_started
+                .zip(energy::sample())
+                .map(|(started, ended)| energy::joules(&started, &ended));
+
+            // peak memory is of the measured load, a crash while probing is
+            // where the probe ends rather than a failed benchmark
             tx.send(()).unwrap();
-            let _ = server.kill();
+            let crashed = crashed.or_else(|| server.try_wait().ok().flatten());
+            let capacity = match args.probe_connections {
+                Some(limit)
+                    if interrupted.is_none() && pathological.is_none() && crashed.is_none() =>
+                {
+                    let probe_started = Instant::now();
+                    let capacity = capacity::probe(
+                        &capacity::steps(cpu_count.parse().unwrap(), limit),
+                        |connections| {
+                            let connections = connections.to_string();
+                            let mut probe_args = scenario_args.clone();
+                            for (flag, value) in [
+                                ("--connections", connections.as_str()),
+                                ("--duration", PROBE_DURATION),
+                            ] {
+                                if let Some(index) = probe_args.iter().position(|arg| *arg == flag)
+                                {
+                                    probe_args[index + 1] = value;
+                                }
+                            }
+
+                            let output = Command::new("wrk")
+                                .args(&probe_args)
+                                .arg(&url)
+                                .output()
+                                .ok()?;
+                            let stdout = String::from_utf8_lossy(&output.stdout);
+                            let (metrics, _) = parser::parse_round(&stdout, false).ok()?;
+                            Some(metrics.errors.total() == 0)
+                        },
+                    );
+                    timing.measure += timings::secs(probe_started.elapsed());
+
+                    if capacity.is_none() {
+                        log::warn!("Probing the connections {} serves failed.", benchmark);
+                    }
+                    capacity
+                }
+                _ => None,
+            };
+
+            let teardown_started = Instant::now();
+            port::kill_tree(&cpu::tree_pids(server.id()));
+            let _ = server.wait();
+            drop(server_pid);
             let max_memory = mem_usage_thread.join().unwrap();
             let max_memory =
                 f64::from(u32::try_from(max_memory).expect("mem usage too high")) / 1024.0;
+            timing.teardown += timings::secs(teardown_started.elapsed());
+            hook(hooks::Phase::PostTeardown, context(None, None));
+            let parse_started = Instant::now();
+            // a framework aborted by the probe has no rounds, what it
+            // measured is all there is to report
+            let measured = if outputs.is_empty() {
+                probe.as_slice()
+            } else {
+                outputs.as_slice()
+            };
+
+            if let Some(control) = interrupted {
+                log::warn!(
+                    "Benchmarking {} interrupted by {} request.",
+                    benchmark,
+                    control.name()
+                );
+
+                if control == Control::Cancel {
+                    status.done.push(format!("{} (cancelled)", benchmark));
+                    break;
+                }
+                status.done.push(format!("{} (skipped)", benchmark));
+                continue;
+            } else if let Some(exit) = crashed {
+                let stderr = fs::read_to_string(&stderr_path).unwrap_or_default();
+                let backtrace = crash::backtrace(&stderr, &server_pids);
+                let mut log = format!("server exited with {}", exit);
+                if let Some(backtrace) = &backtrace {
+                    log.push_str(&format!("\n\n{}", backtrace));
+
+                    let crash_md = format!(
+                        "# {} crashed\n\nThe server exited during the benchmark with \
+                            {}.\n\n```\n{}\n```\n\nFull output: [{output}]({output}).\n",
+                        benchmark,
+                        exit,
+                        backtrace,
+                        output = stderr_path.file_name().unwrap().to_string_lossy()
+                    );
+                    fs::write(
+                        detail_dir.join(format!("{}.crash.md", framework_name)),
+                        crash_md,
+                    )
+                    .unwrap();
+                }
 
-            if output.stderr.len() > 0 {
                 log::error!(
-                    "Benchmarking {:?} failed: \n{}",
-                    member,
+                    "Server of {} exited during the benchmark with {}.{}",
+                    benchmark,
+                    exit,
+                    backtrace.map(|b| format!("\n{}", b)).unwrap_or_default()
+                );
+                events.emit(Event::anomaly(
+                    benchmark,
+                    Anomaly::ServerExited,
+                    log.clone(),
+                ));
+                failures += 1;
+                breakages.push(github::Breakage {
+                    framework_name: framework_name.to_string(),
+                    stage: "benchmark",
+                    log,
+                });
+            } else if let Some(output) = measured.iter().find(|output| !output.stderr.is_empty()) {
+                log::error!(
+                    "Benchmarking {} failed: \n{}",
+                    benchmark,
                     String::from_utf8_lossy(&output.stderr)
                 );
+                events.emit(Event::anomaly(
+                    benchmark,
+                    Anomaly::LoadToolFailed,
+                    String::from_utf8_lossy(&output.stderr),
+                ));
+                failures += 1;
+                breakages.push(github::Breakage {
+                    framework_name: framework_name.to_string(),
+                    stage: "benchmark",
+                    log: String::from_utf8_lossy(&output.stderr).to_string(),
+                });
             } else {
-                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stdout = measured
+                    .iter()
+                    .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
 
                 result_md.add_item(format!("## {}", framework_name));
+                if outputs.is_empty() {
+                    result_md.add_item(format!(
+                        "Aborted by the {} probe below the floor of \
+                        Req/Sec, no round was measured.",
+                        PROBE_DURATION
+                    ));
+                }
                 result_md.add_item(format!("Maximum Memory Usage: {:.1} MB", max_memory));
-                result_md.add_item(format!("```\n{}\n```", stdout.trim()));
+                result_md.add_item(format!("```\n{}\n```", stdout));
 
-                if let Ok(metrics) = stdout.parse::<Metrics>() {
-                    reports.push(Report::new(
-                        framework_name,
-                        max_memory,
-                        metrics,
-                    ));
+                // a tool killed by a signal may still have printed a complete
+                // looking summary of the requests it did so far
+                let mut rounds = Vec::new();
+                for output in measured {
+                    let stdout = String::from_utf8_lossy(&output.stdout);
+                    rounds.extend(parser::split_rounds(&stdout).into_iter().map(|round| {
+                        parser::parse_round(round, args.strict_parsing).map(
+                            |(metrics, complete)| (metrics, complete && output.status.success()),
+                        )
+                    }));
+                }
+                let rounds: Result<Vec<(Metrics, bool)>, _> = rounds.into_iter().collect();
+                let rounds = rounds
+                    .map_err(|error| {
+                        log::error!("Could not parse the output of {}: {}.", benchmark, error)
+                    })
+                    .ok();
+                let cut_off = rounds
+                    .iter()
+                    .flatten()
+                    .filter(|(_, complete)| !complete)
+                    .count();
+                let round_count = rounds.as_ref().map_or(0, Vec::len);
+                let rounds =
+                    rounds.map(|rounds| rounds.into_iter().map(|(metrics, _)| metrics).collect());
+
+                if let Some(mut report) = rounds
+                    .and_then(|rounds| Report::from_rounds(framework_name, max_memory, rounds))
+                {
+                    if cut_off > 0 {
+                        log::warn!(
+                            "Output of {} was cut off in {} of {} rounds, reporting it as \
+                                incomplete.",
+                            benchmark,
+                            cut_off,
+                            round_count
+                        );
+                        events.emit(Event::anomaly(
+                            benchmark,
+                            Anomaly::Incomplete,
+                            format!("output cut off in {} of {} rounds", cut_off, round_count),
+                        ));
+                        report = report.with_incomplete();
+                    }
+                    if pathological.is_some() {
+                        report = report.with_pathological();
+                    }
+                    if let Some(cpu_time) = cpu_time {
+                        report = report.with_cpu_time(cpu_time);
+                    }
+                    if let Some(joules) = joules {
+                        report = report.with_energy(joules);
+                    }
+                    if let Some(capacity) = capacity {
+                        report = report.with_capacity(capacity);
+                    }
+                    report = report.with_collected(collected).with_scenario(bench_type);
+                    if let Some(version) = versions.get(member) {
+                        report = report.with_version(version.clone());
+                    }
+
+                    let metrics = report.metrics();
+                    if let Some(balance) = metrics
+                        .thread_balance()
+                        .filter(|b| *b > SKEWED_THREAD_BALANCE)
+                    {
+                        log::warn!(
+                            "{} has skewed per-thread load (stdev/avg of Req/Sec is {:.2}).",
+                            benchmark,
+                            balance
+                        );
+                        events.emit(Event::anomaly(
+                            benchmark,
+                            Anomaly::SkewedThreads,
+                            format!("stdev/avg of per-thread Req/Sec is {:.2}", balance),
+                        ));
+                    }
+
+                    let mut detail_md = Markdown::new();
+                    detail_md.add_item("Generated by bench-bot.");
+                    detail_md.extend(base_md(&command, probe_commands.get(bench_type)));
+                    detail_md.add_item(format!("# {}", framework_name));
+                    if !benchmark.path.is_empty() {
+                        detail_md.add_item(format!("Url: `{}`", url));
+                    }
+                    if scripts.contains_key(bench_type) {
+                        detail_md.add_item(format!(
+                            "Script: [`{0}`](../{1}/{0})",
+                            script::file_name(bench_type),
+                            script::SCRIPTS_DIR
+                        ));
+                    }
+                    detail_md.add_item("## Metrics");
+                    if report.is_incomplete() {
+                        detail_md.add_item(
+                            "> **Warning:** the output of the load tool was cut \
+                            off, these are the metrics it printed before.",
+                        );
+                    }
+                    if report.is_pathological() {
+                        detail_md.add_item(
+                            "> **Warning:** throughput fell below the floor of the \
+                            scenario and measuring was aborted, these are the metrics measured \
+                            until then.",
+                        );
+                    }
+                    detail_md.add_item(report.detail_table(&NumberFormat::default()));
+                    if let Some(rounds_table) = report.rounds_table(&NumberFormat::default()) {
+                        detail_md.add_item("## Rounds");
+                        detail_md.add_item(rounds_table);
+                    }
+
+                    let mut charts = Vec::new();
+                    if !metrics.timeline().is_empty() {
+                        let chart = format!("{}.timeline.svg", framework_name);
+                        fs::write(detail_dir.join(&chart), timeline::svg(metrics.timeline()))
+                            .unwrap();
+
+                        detail_md.add_item("## Throughput");
+                        detail_md.add_item(format!("![Req/Sec over time]({})", chart));
+                        charts.push(("Req/Sec over time", chart));
+                    }
+
+                    if profiled {
+                        detail_md.add_item("## Profile");
+
+                        let svg = format!("{}.svg", framework_name);
+                        match flamegraph::render(
+                            &folded,
+                            &benchmark.to_string(),
+                            &detail_dir.join(&svg),
+                        ) {
+                            Ok(()) => {
+                                detail_md.add_item(format!("![Flamegraph]({})", svg));
+                                charts.push(("Flamegraph", svg));
+                            }
+                            Err(e) => log::warn!(
+                                "Rendering the flamegraph of {} failed: {}.",
+                                benchmark,
+                                e
+                            ),
+                        }
+
+                        let baseline_folded = args
+                            .baseline
+                            .as_deref()
+                            .map(|baseline| {
+                                flamegraph::folded_path(baseline, bench_type, framework_name)
+                            })
+                            .filter(|path| path.exists());
+                        if let Some(baseline_folded) = baseline_folded {
+                            let diff = format!("{}.diff.svg", framework_name);
+                            let title = format!("{} against the baseline", benchmark);
+                            match flamegraph::differential(
+                                &baseline_folded,
+                                &folded,
+                                &title,
+                                &detail_dir.join(&diff),
+                            ) {
+                                Ok(()) => {
+                                    detail_md.add_item(
+                                        "Against the baseline, frames in red grew \
+                                        and in blue shrank:",
+                                    );
+                                    detail_md
+                                        .add_item(format!("![Differential flamegraph]({})", diff));
+                                    charts.push(("Differential flamegraph", diff));
+                                }
+                                Err(e) => log::warn!(
+                                    "Rendering the differential flamegraph of {} failed: {}.",
+                                    benchmark,
+                                    e
+                                ),
+                            }
+                        }
+                    }
+
+                    detail_md.add_item("## Output");
+                    detail_md.add_item(format!("```\n{}\n```", stdout));
+                    fs::write(
+                        detail_dir.join(format!("{}.md", framework_name)),
+                        detail_md.finish(),
+                    )
+                    .unwrap();
+
+                    let detail = html::Detail {
+                        scenario: bench_type,
+                        environment: &environment,
+                        command: &command,
+                        url: (!benchmark.path.is_empty()).then_some(url.as_str()),
+                        charts: &charts,
+                        output: &stdout,
+                    };
+                    fs::write(
+                        detail_dir.join(format!("{}.html", framework_name)),
+                        html::detail(&report, &detail, &NumberFormat::default()),
+                    )
+                    .unwrap();
+
+                    events.emit(Event::Measured {
+                        scenario: bench_type.to_string(),
+                        report: Box::new(report.flatten()),
+                    });
+                    scenario_reports
+                        .entry(bench_type.to_string())
+                        .or_default()
+                        .push(report.clone());
+                    reports.push(report);
+                    timing.parse += timings::secs(parse_started.elapsed());
                 } else {
                     log::warn!("Could not parse benchmark result: {}", stdout);
+                    events.emit(Event::anomaly(
+                        benchmark,
+                        Anomaly::Unparsable,
+                        "could not parse the output of the load tool",
+                    ));
+                    failures += 1;
                 }
             }
 
-            // lets CPU cooling down, ignore last member.
-            if index != members_len - 1 {
+            status.done.push(benchmark.to_string());
+
+            // lets CPU cooling down, ignore last benchmark.
+            if index != benchmarks_len - 1 {
                 thread::sleep(Duration::from_secs(cd));
             }
         }
     }
 
+    status.update(&output_dir, "reporting", None);
+
+    // builds left are of members never benchmarked
+    let unbenchmarked: f64 = build_times.values().sum();
+    let overhead: f64 = timings.values().map(PhaseTimings::overhead).sum::<f64>() + unbenchmarked;
+    let total: f64 = timings.values().map(PhaseTimings::total).sum::<f64>() + unbenchmarked;
+    log::info!(
+        "Harness overhead: {:.1}s of {:.1}s spent outside of measuring.",
+        overhead,
+        total
+    );
+
+    let mut columns = ReportOptions::new().with_columns(args.columns);
+    if args.latency_percentiles {
+        columns = columns.with_latency_percentiles();
+    }
+    if args.without_transfer {
+        columns = columns.without_transfer();
+    }
+    columns = args
+        .with_column
+        .iter()
+        .copied()
+        .fold(columns, ReportOptions::with_column);
+    if args.probe_connections.is_some() {
+        columns = columns.with_column(Column::MaxConnections);
+    }
+    columns = args
+        .without_column
+        .iter()
+        .copied()
+        .fold(columns, ReportOptions::without_column);
+
+    let report_options = ReportOptions {
+        format: NumberFormat {
+            decimals: args.decimals,
+            units: args.units,
+            thousands_separator: args.thousands_separator,
+        },
+        medals: args.medals,
+        detail_links: None,
+        regressions: Vec::new(),
+        slo: args.slo.clone(),
+        apdex: args.apdex,
+        sort: args.sort,
+        ..columns
+    };
+
+    let regressions = baseline
+        .as_ref()
+        .map(|baseline| compare::find_regressions(&baseline.reports, &reports, &config.thresholds))
+        .unwrap_or_default();
+
+    if args.file_issues {
+        let comparison = Report::generate_from(&reports, &report_options);
+        let mut framework_names: Vec<&str> = regressions
+            .iter()
+            .map(|r| r.framework_name.as_str())
+            .collect();
+        framework_names.sort_unstable();
+        framework_names.dedup();
+
+        for framework_name in framework_names {
+            let maintainers = members
+                .iter()
+                .find(|member| member.file_name() == Some(OsStr::new(framework_name)))
+                .and_then(|member| Manifest::load(&workspace_dir.join(member)).ok())
+                .map(|manifest| manifest.maintainers)
+                .unwrap_or_default();
+            let framework_regressions: Vec<_> = regressions
+                .iter()
+                .filter(|r| r.framework_name == framework_name)
+                .cloned()
+                .collect();
+
+            let title = github::regression_title(framework_name);
+            let body = github::regression_body(&maintainers, &framework_regressions, &comparison);
+            match github::upsert_issue(&title, &body, github::REGRESSION_LABEL) {
+                Ok(()) => log::info!("Filed issue {:?}.", title),
+                Err(e) => log::error!("Filing issue {:?} failed: {}", title, e),
+            }
+        }
+    }
+
+    if args.file_issues {
+        let environment_md = environment.to_markdown();
+
+        for breakage in &breakages {
+            let title = github::broken_title(&breakage.framework_name);
+            let body = github::broken_body(breakage, &environment_md);
+            match github::upsert_issue(&title, &body, github::BROKEN_LABEL) {
+                Ok(()) => log::info!("Filed issue {:?}.", title),
+                Err(e) => log::error!("Filing issue {:?} failed: {}", title, e),
+            }
+        }
+
+        // a framework broken in one scenario isn't fixed by passing another
+        let passed: BTreeSet<&str> = reports
+            .iter()
+            .map(Report::framework_name)
+            .filter(|name| !breakages.iter().any(|b| b.framework_name == *name))
+            .collect();
+        for framework_name in passed {
+            let title = github::broken_title(framework_name);
+            match github::close_issue(&title, "Passed on the latest scheduled run.") {
+                Ok(true) => log::info!("Closed issue {:?}.", title),
+                Ok(false) => {}
+                Err(e) => log::error!("Closing issue {:?} failed: {}", title, e),
+            }
+        }
+    }
+
+    // "regressions" are often just a rustc or kernel upgrade
+    let environment_changes = baseline
+        .as_ref()
+        .filter(|baseline| !baseline.environment.is_empty())
+        .map(|baseline| baseline.environment.diff(&environment))
+        .unwrap_or_default();
+
+    for change in &environment_changes {
+        log::warn!(
+            "Environment differs from baseline: {} was {:?}, is {:?}.",
+            change.key,
+            change.baseline.as_deref().unwrap_or("-"),
+            change.current.as_deref().unwrap_or("-")
+        );
+    }
+
+    for regression in &regressions {
+        log::error!(
+            "{} regressed: {} changed by {:.1}% (threshold {:.1}%).",
+            regression.framework_name,
+            regression.metric.name(),
+            regression.change,
+            regression.threshold
+        );
+    }
+
+    // humanized numbers are easier to read in an aligned table
+    let interactive = io::stdout().is_terminal();
+    let terminal_options = ReportOptions {
+        format: NumberFormat {
+            units: UnitDisplay::Humanized,
+            ..report_options.format
+        },
+        ..report_options.clone()
+    };
+
+    let mut scenarios: Vec<String> = output_map.keys().map(|s| s.to_string()).collect();
+    scenarios.sort();
+
+    let summary = RunSummary {
+        frameworks: members.len(),
+        scenarios,
+        failures,
+        duration: started.elapsed(),
+        environment: format!(
+            "{} ({} CPUs), {}",
+            cpu_name,
+            cpu_count,
+            sys.long_os_version().unwrap_or_default()
+        ),
+        command_line: env::args().collect::<Vec<_>>().join(" "),
+    };
+
+    let manifests: BTreeMap<String, Manifest> = members
+        .iter()
+        .filter_map(|member| {
+            let name = member.file_name()?.to_string_lossy().to_string();
+            Some((
+                name,
+                Manifest::load(&workspace_dir.join(member)).unwrap_or_default(),
+            ))
+        })
+        .collect();
+    let category_of = |name: &str| {
+        manifests
+            .get(name)
+            .map(|manifest| manifest.category)
+            .unwrap_or_default()
+    };
+    let is_experimental = |report: &Report| {
+        manifests
+            .get(report.framework_name())
+            .is_some_and(|manifest| manifest.experimental)
+    };
+    let variant_of = |name: &str| {
+        manifests
+            .get(name)
+            .and_then(|manifest| manifest.variant.clone())
+    };
+
+    // experimental implementations stay out of the headline until promoted,
+    // incomplete and pathological results until measured again
+    let (reliable, incomplete): (Vec<Report>, Vec<Report>) =
+        reports.iter().cloned().partition(Report::is_reliable);
+    let (experimental, headline): (Vec<Report>, Vec<Report>) =
+        reliable.into_iter().partition(is_experimental);
+    let headline_reports: BTreeMap<String, Vec<Report>> = scenario_reports
+        .iter()
+        .map(|(scenario, reports)| {
+            let headline = reports
+                .iter()
+                .filter(|r| !is_experimental(r) && r.is_reliable())
+                .cloned();
+            (scenario.clone(), headline.collect())
+        })
+        .collect();
+
+    let (release_profile, audits) = audit::audit(&workspace_dir, &members);
+    let audit_md = audit::to_markdown(&release_profile, &audits);
+
+    let scores = score::composite_scores(&headline_reports, &config.weights);
+    let scores_md = score::scores_markdown(&scores, &summary.scenarios, &config.weights);
+
     for (bench_type, result_md) in output_map {
-        let mut output_md = base_md.clone();
+        // every output of a scenario compares its own reports only
+        let of_scenario = |reports: &[Report]| -> Vec<Report> {
+            reports
+                .iter()
+                .filter(|r| r.scenario() == Some(bench_type))
+                .cloned()
+                .collect()
+        };
+        let (reports, headline, experimental, incomplete) = (
+            of_scenario(&reports),
+            of_scenario(&headline),
+            of_scenario(&experimental),
+            of_scenario(&incomplete),
+        );
+        let regressions: Vec<Regression> = regressions
+            .iter()
+            .filter(|r| r.scenario.as_deref() == Some(bench_type))
+            .cloned()
+            .collect();
+
+        let mut output_md = Markdown::new();
+        output_md.add_item("Generated by bench-bot.");
+        output_md.add_item("# Summary");
+        output_md.add_item(summary.to_markdown());
+        let command = scenario_commands.get(bench_type).unwrap_or(&bench_command);
+        output_md.extend(base_md(command, probe_commands.get(bench_type)));
+
+        if let Some(scenario) = scenario::find(bench_type) {
+            output_md.add_item("## Scenario");
+            output_md.add_item(scenario.to_markdown());
+        }
+        if scripts.contains_key(bench_type) {
+            output_md.add_item(format!(
+                "Measured with the wrk script [`{0}`]({1}/{0}).",
+                script::file_name(bench_type),
+                script::SCRIPTS_DIR
+            ));
+        }
+
+        let linked_options = ReportOptions {
+            detail_links: Some(bench_type.to_string()),
+            regressions: regressions
+                .iter()
+                .map(|r| r.framework_name.clone())
+                .collect(),
+            ..report_options.clone()
+        };
 
         output_md.add_item("## Comparisons");
-        output_md.add_item(Report::generate_from(&reports));
+        output_md.add_item(Report::generate_by_category(
+            &headline,
+            category_of,
+            &linked_options,
+        ));
+
+        if !experimental.is_empty() {
+            output_md.add_item("### Experimental");
+            output_md.add_item(
+                "Not optimized or not idiomatic yet, these implementations are left \
+                out of medals and the overall score.",
+            );
+            output_md.add_item(Report::generate_from(
+                &experimental,
+                &ReportOptions {
+                    medals: false,
+                    ..linked_options.clone()
+                },
+            ));
+        }
+
+        let measured: Vec<Report> = headline.iter().chain(&experimental).cloned().collect();
+        if let Some(variants) =
+            Report::generate_variants(&measured, variant_of, &report_options.format)
+        {
+            output_md.add_item("### Variants");
+            output_md.add_item(
+                "Implementations changing one thing of another, e.g. its allocator \
+                or runtime, below it with their change against it.",
+            );
+            output_md.add_item(variants);
+        }
+
+        if !incomplete.is_empty() {
+            output_md.add_item("### Incomplete");
+            output_md.add_item(
+                "The output of the load tool was cut off (⚠️) or measuring was \
+                aborted below the floor of Req/Sec (🐌), these metrics are what was measured until \
+                then and are left out of medals, regressions and the overall score.",
+            );
+            output_md.add_item(Report::generate_from(&incomplete, &linked_options));
+        }
+
+        output_md.add_item("## Overall Score");
+        output_md.add_item(scores_md.clone());
+
+        if !environment_changes.is_empty() {
+            output_md.add_item("## Environment Changes");
+            output_md.add_item(
+                "> **Warning:** this run was measured in a different environment \
+                than the baseline, differences may not be caused by the frameworks.",
+            );
+            output_md.add_item(environment::differences_table(&environment_changes));
+        }
+
+        if !regressions.is_empty() {
+            output_md.add_item("## Regressions");
+            output_md.add_item(compare::regressions_table(&regressions));
+
+            let mut regressed: Vec<&str> = regressions
+                .iter()
+                .map(|r| r.framework_name.as_str())
+                .collect();
+            regressed.sort_unstable();
+            regressed.dedup();
+            let diffs: Vec<String> = regressed
+                .into_iter()
+                .filter(|name| {
+                    output_dir
+                        .join(bench_type)
+                        .join(format!("{}.diff.svg", name))
+                        .exists()
+                })
+                .map(|name| format!("[{0}]({1}/{0}.diff.svg)", name, bench_type))
+                .collect();
+            if !diffs.is_empty() {
+                output_md.add_item(format!(
+                    "Where the time went, as differential flamegraphs: {}.",
+                    diffs.join(", ")
+                ));
+            }
+        }
+
+        if args.format == OutputFormat::Json {
+            println!(
+                "{}",
+                Report::to_json(bench_type, &environment, command, &reports)
+            );
+        } else if interactive {
+            println!("{}", bench_type);
+            println!("{}", terminal::render(&reports, &terminal_options, true));
+        }
 
         output_md.add_item(result_md.finish());
 
-        let output_path = args.output_dir.join(format!("{}.md", bench_type));
+        output_md.add_item("## Fairness Audit");
+        output_md.add_item(audit_md.clone());
+
+        let output_path = output_dir.join(format!("{}.md", bench_type));
 
         log::info!("Writing output to {:?}.", output_path);
         fs::write(output_path, output_md.finish()).unwrap();
+
+        let html_path = output_dir.join(format!("{}.html", bench_type));
+
+        log::info!("Writing page to {:?}.", html_path);
+        let variants = Report::variants(&measured, variant_of, &report_options.format);
+        fs::write(
+            html_path,
+            html::report(
+                bench_type,
+                &reports,
+                &variants,
+                &ReportOptions {
+                    detail_links: Some(bench_type.to_string()),
+                    regressions: regressions
+                        .iter()
+                        .map(|r| r.framework_name.clone())
+                        .collect(),
+                    ..report_options.clone()
+                },
+            ),
+        )
+        .unwrap();
+
+        let results_path = output_dir.join(format!("{}.json", bench_type));
+
+        log::info!("Writing results to {:?}.", results_path);
+        let results = Results {
+            environment: environment.clone(),
+            reports: reports.clone(),
+            timings: timings
+                .iter()
+                .filter(|((scenario, _), _)| scenario == bench_type)
+                .map(|((_, framework_name), timing)| (framework_name.clone(), timing.clone()))
+                .collect(),
+            script: scripts
+                .get(bench_type)
+                .and_then(|path| fs::read_to_string(path).ok()),
+            command: Some(command.clone()),
+        };
+        compare::save_results(&results_path, &results).unwrap();
+
+        let csv_path = output_dir.join(format!("{}.csv", bench_type));
+
+        log::info!("Writing spreadsheet to {:?}.", csv_path);
+        fs::write(csv_path, Report::to_csv(&reports)).unwrap();
+    }
+
+    // one table with a heading per scenario once several were measured
+    if args.format == OutputFormat::Table && !interactive && !summary.scenarios.is_empty() {
+        if let [scenario] = summary.scenarios.as_slice() {
+            println!("{}", scenario);
+        }
+        println!("{}", Report::generate_from(&reports, &report_options));
+    }
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let commit = history::current_commit(&workspace_dir);
+    let history_path = output_dir.join("history.jsonl");
+
+    let repro = repro::Repro {
+        id: timestamp.to_string(),
+        commit: commit.clone(),
+        toolchain: config.toolchain.clone(),
+        rustc: environment.get("rustc").map(str::to_string),
+        profile: args.profile.clone(),
+        overrides: overrides.iter().map(Override::to_string).collect(),
+        parameters: repro::Parameters {
+            connections: args.connections,
+            duration: args.duration,
+            url: args.url.clone(),
+            cooldown: args.cd,
+            rounds: args.rounds,
+            timeline: args.timeline,
+            target_ci: args.target_ci.map(|target| target.0),
+            max_rounds: args.target_ci.map(|_| args.max_rounds),
+        },
+        seed: stats::BOOTSTRAP_SEED,
+        bench_command,
+        command_line: summary.command_line.clone(),
+    };
+    // run parameters decide the outcome as much as the config file does
+    let config_content = config_file
+        .as_deref()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .unwrap_or_default();
+    let run_config = format!(
+        "{}\n{}",
+        config_content,
+        serde_json::to_string(&(&repro.profile, &repro.overrides, &repro.parameters)).unwrap()
+    );
+    let fingerprint = history::fingerprint(
+        commit.as_deref(),
+        &history::uncommitted_changes(&workspace_dir),
+        &environment,
+        &run_config,
+        repro.seed,
+    );
+    let previous = if history_path.exists() {
+        history::load(&history_path).unwrap()
+    } else {
+        Vec::new()
+    };
+
+    log::info!("Appending run to {:?}.", history_path);
+    for scenario in &summary.scenarios {
+        let original = fingerprint
+            .as_deref()
+            .and_then(|fingerprint| history::find_original(&previous, fingerprint, scenario));
+
+        if let (Some(original), DuplicateRuns::Skip) = (original, args.duplicates) {
+            log::info!(
+                "{} repeats run {} exactly, not storing it again.",
+                scenario,
+                original.id
+            );
+            continue;
+        }
+
+        let record = RunRecord {
+            id: timestamp.to_string(),
+            timestamp,
+            commit: commit.clone(),
+            scenario: scenario.clone(),
+            reports: history::of_scenario(&reports, scenario),
+            failures,
+            fingerprint: fingerprint.clone(),
+            replicate_of: original.map(|original| original.id.clone()),
+            machine: Some(environment.machine_profile()),
+        };
+        history::append(&history_path, &record).unwrap();
+    }
+
+    if let Err(e) = repro::save(&output_dir, &repro, config_file.as_deref(), &workspace_dir) {
+        log::error!("Saving reproduction data failed: {}", e);
+    }
+
+    // the bench machine's disk is neither durable nor shareable
+    let mut artifact_links = Vec::new();
+    if let Some(artifacts) = &config.artifacts {
+        status.update(&output_dir, "uploading", None);
+
+        match artifacts::upload(
+            &output_dir,
+            &summary.scenarios,
+            artifacts,
+            &timestamp.to_string(),
+        ) {
+            Ok(links) => {
+                let links_md = artifacts::links_markdown(&links);
+                log::info!(
+                    "Uploaded {} artifacts to {}.",
+                    links.len(),
+                    artifacts.bucket
+                );
+                print_aside(args.format, &links_md);
+                fs::write(output_dir.join("artifacts.md"), links_md).unwrap();
+                artifact_links = links;
+            }
+            Err(e) => log::error!("Uploading artifacts failed: {}", e),
+        }
+    }
+
+    if let Some(tag) = &args.release_tag {
+        status.update(&output_dir, "publishing", None);
+
+        match release::publish(&output_dir, tag, run_started) {
+            Ok(links) => {
+                let links_md = artifacts::links_markdown(&links);
+                log::info!("Attached {} files to the release {}.", links.len(), tag);
+                print_aside(args.format, &links_md);
+                fs::write(output_dir.join("release.md"), links_md).unwrap();
+            }
+            Err(e) => log::error!("Publishing the release {} failed: {}", tag, e),
+        }
+    }
+
+    if let Some(number) = args.pr_comment {
+        let body = github::pr_comment_body(
+            &Report::generate_from(&reports, &report_options),
+            &artifact_links,
+        );
+        match github::comment_pr(number, &body) {
+            Ok(()) => log::info!("Commented on pull request #{}.", number),
+            Err(e) => log::error!("Commenting on pull request #{} failed: {}", number, e),
+        }
+    }
+
+    status.update(&output_dir, "finished", None);
+    events.emit(Event::RunFinished {
+        failures,
+        regressions: regressions
+            .iter()
+            .map(|r| r.framework_name.clone())
+            .collect(),
+    });
+    drop(lock_file);
+    drop(lock);
+
+    // fail CI gating when the baseline comparison found regressions, the
+    // summary ends the log where CI shows it
+    if !regressions.is_empty() {
+        let mut framework_names: Vec<&str> = regressions
+            .iter()
+            .map(|r| r.framework_name.as_str())
+            .collect();
+        framework_names.sort_unstable();
+        framework_names.dedup();
+        log::error!(
+            "{} regressed beyond their thresholds:\n{}",
+            framework_names.join(", "),
+            compare::regressions_table(&regressions)
+        );
+        process::exit(1);
+    }
+}
+
+fn workspace_members(workspace_dir: &Path) -> Vec<PathBuf> {
+    let ws_toml = fs::read(workspace_dir.join("Cargo.toml")).unwrap();
+    let cargo: Cargo = toml::from_slice(&ws_toml).unwrap();
+
+    expand_members(cargo.workspace.members, workspace_dir)
+}
+
+/// The explicitly given config, or `bench.toml` of the workspace if it exists.
+fn config_path(workspace_dir: &Path, explicit: Option<&Path>) -> Option<PathBuf> {
+    match explicit {
+        Some(path) => Some(path.to_path_buf()),
+        None => Some(workspace_dir.join("bench.toml")).filter(|path| path.exists()),
     }
 }
 
 fn expand_members(members: Vec<PathBuf>, ws_dir: &Path) -> Vec<PathBuf> {
     let mut new_members = Vec::new();
     for member in members {
-        if member.components().last() == Some(Component::Normal(&OsStr::new("*"))) {
+        if member.components().next_back() == Some(Component::Normal(OsStr::new("*"))) {
             let parent_dir = member.parent().unwrap();
             for entry in fs::read_dir(ws_dir.join(parent_dir)).unwrap() {
                 let entry = entry.unwrap();
@@ -253,3 +2629,14 @@ fn expand_members(members: Vec<PathBuf>, ws_dir: &Path) -> Vec<PathBuf> {
     new_members.sort();
     new_members
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // completions and the man page are generated from this definition
+    #[test]
+    fn cli() {
+        Args::command().debug_assert();
+    }
+}
@@ -0,0 +1,506 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+/// Benchmark metadata of an implementation, read from the
+/// `[package.metadata.bench]` table of its `Cargo.toml`.
+#[derive(Debug, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Manifest {
+    /// Crate of the benchmarked framework, defaults to the directory name.
+    pub framework: Option<String>,
+    /// GitHub handles mentioned when the implementation regresses.
+    pub maintainers: Vec<String>,
+    /// Scenarios the binary serves, it is built once and benchmarked in
+    /// each. Defaults to the directory the implementation is in.
+    pub scenarios: Vec<String>,
+    /// Path the scenario routes of a binary listing `scenarios` are mounted
+    /// at, for frameworks that keep application routes below a fixed path.
+    pub prefix: String,
+    pub category: Category,
+    /// New, unoptimized or non-idiomatic implementations are reported in a
+    /// table of their own until maintainers promote them.
+    pub experimental: bool,
+    /// Unreleased revision of the framework crate to benchmark, e.g. hyper's
+    /// master branch.
+    pub git: Option<GitPin>,
+    /// Implementation this one changes along a single axis, e.g. axum built
+    /// with mimalloc is a variant of axum.
+    pub variant: Option<Variant>,
+    /// Names the implementation was benchmarked under before, e.g. its
+    /// directory name before a rename, so its history stays one series.
+    pub aliases: Vec<String>,
+}
+
+/// Implementation a variant is compared with, and what it changes.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Variant {
+    /// Directory name of the implementation, e.g. `axum`.
+    pub of: String,
+    /// What the variant changes, e.g. `allocator`, `runtime` or `profile`.
+    pub axis: String,
+}
+
+/// Git revision the framework crate is patched to when building, as by a
+/// `[patch.crates-io]` section.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GitPin {
+    pub url: String,
+    /// Commit, tag or branch.
+    pub rev: String,
+}
+
+/// Kind of implementation, reports compare implementations of one kind with
+/// each other only.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Category {
+    /// Routing, extractors, middleware and the like included.
+    #[default]
+    Framework,
+    /// A thin layer of routing over a server.
+    MicroFramework,
+    /// An HTTP server without routing, e.g. hyper.
+    RawServer,
+    /// A server written in another language, for reference.
+    Baseline,
+}
+
+impl Category {
+    pub fn title(self) -> &'static str {
+        match self {
+            Category::Framework => "Full Frameworks",
+            Category::MicroFramework => "Micro-Frameworks",
+            Category::RawServer => "Raw Servers",
+            Category::Baseline => "Non-Rust Baselines",
+        }
+    }
+}
+
+/// One scenario of one implementation. Members serving several scenarios
+/// appear once per scenario.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Benchmark {
+    pub scenario: String,
+    pub member: PathBuf,
+    /// Path of the scenario, empty when the binary serves it at `/`.
+    pub path: String,
+}
+
+impl Benchmark {
+    pub fn framework_name(&self) -> String {
+        self.member
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Url of the scenario on a server listening at `base`.
+    pub fn url(&self, base: &str) -> String {
+        if self.path.is_empty() {
+            base.to_string()
+        } else {
+            format!("{}{}", base.trim_end_matches('/'), self.path)
+        }
+    }
+}
+
+impl fmt::Display for Benchmark {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.scenario, self.framework_name())
+    }
+}
+
+/// Current directory name of the implementation of every alias of the
+/// workspace `members`.
+pub fn aliases(workspace_dir: &Path, members: &[PathBuf]) -> BTreeMap<String, String> {
+    let mut aliases = BTreeMap::new();
+    for member in members {
+        let Some(name) = member
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+        else {
+            continue;
+        };
+        let manifest = match Manifest::load(&workspace_dir.join(member)) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                log::warn!("Skipping the aliases of {:?}: {}.", member, e);
+                continue;
+            }
+        };
+        for alias in manifest.aliases {
+            aliases.insert(alias, name.clone());
+        }
+    }
+
+    aliases
+}
+
+/// Benchmarks of the workspace `members`, ordered by scenario. Fails with
+/// every manifest that doesn't load, e.g. of an unknown category, rather
+/// than benchmarking its member with the defaults.
+pub fn benchmarks(
+    workspace_dir: &Path,
+    members: &[PathBuf],
+) -> Result<Vec<Benchmark>, Vec<String>> {
+    let mut benchmarks = Vec::new();
+    let mut problems = Vec::new();
+    for member in members {
+        match Manifest::load(&workspace_dir.join(member)) {
+            Ok(manifest) => {
+                benchmarks.extend(manifest.scenarios_of(member).into_iter().map(|scenario| {
+                    Benchmark {
+                        path: manifest.path_of(&scenario),
+                        scenario,
+                        member: member.clone(),
+                    }
+                }))
+            }
+            Err(e) => problems.push(e),
+        }
+    }
+    if !problems.is_empty() {
+        return Err(problems);
+    }
+
+    benchmarks.sort();
+    Ok(benchmarks)
+}
+
+#[derive(Deserialize)]
+struct CargoToml {
+    #[serde(default)]
+    package: Package,
+}
+
+#[derive(Default, Deserialize)]
+struct Package {
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+#[derive(Default, Deserialize)]
+struct Metadata {
+    #[serde(default)]
+    bench: Manifest,
+}
+
+impl Manifest {
+    pub fn load(member_dir: &Path) -> Result<Self, String> {
+        let path = member_dir.join("Cargo.toml");
+        let content =
+            fs::read_to_string(&path).map_err(|e| format!("reading {:?}: {}", path, e))?;
+
+        Self::parse(&content).map_err(|e| format!("parsing {:?}: {}", path, e))
+    }
+
+    fn parse(content: &str) -> Result<Self, toml::de::Error> {
+        toml::from_str::<CargoToml>(content).map(|cargo| cargo.package.metadata.bench)
+    }
+
+    /// Crate name of the framework of the implementation at `member_dir`.
+    pub fn framework_crate(&self, member_dir: &Path) -> String {
+        self.framework.clone().unwrap_or_else(|| {
+            member_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Arguments of `cargo build` and `cargo run` patching the framework of
+    /// the implementation at `member_dir` to its pinned revision.
+    pub fn cargo_args(&self, member_dir: &Path) -> Vec<String> {
+        let Some(git) = &self.git else {
+            return Vec::new();
+        };
+        let framework = self.framework_crate(member_dir);
+
+        vec![
+            "--config".to_string(),
+            format!("patch.crates-io.{}.git={:?}", framework, git.url),
+            "--config".to_string(),
+            format!("patch.crates-io.{}.rev={:?}", framework, git.rev),
+        ]
+    }
+
+    /// Scenarios served by the implementation at `member_dir`.
+    pub fn scenarios_of(&self, member_dir: &Path) -> Vec<String> {
+        if !self.scenarios.is_empty() {
+            return self.scenarios.clone();
+        }
+
+        member_dir
+            .parent()
+            .and_then(Path::file_name)
+            .map(|name| vec![name.to_string_lossy().to_string()])
+            .unwrap_or_default()
+    }
+
+    /// Path a binary listing several scenarios serves `scenario` at, empty
+    /// for implementations of a single scenario serving it at `/`.
+    pub fn path_of(&self, scenario: &str) -> String {
+        if self.scenarios.is_empty() {
+            String::new()
+        } else {
+            format!("{}/{}", self.prefix.trim_end_matches('/'), scenario)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+}
+
+/// Version of `name` resolved in the lockfile `content`, with the short
+/// commit when it comes from git, e.g. `1.0.0-rc.4 (git 3a1b6f2)`. A git
+/// checkout wins over releases, the newest release over older ones.
+pub fn resolved_version(content: &str, name: &str) -> Option<String> {
+    let lock: CargoLock = toml::from_str(content).ok()?;
+    let packages: Vec<&LockedPackage> = lock
+        .package
+        .iter()
+        .filter(|package| package.name == name)
+        .collect();
+
+    let commit = |package: &LockedPackage| {
+        let source = package.source.as_deref()?.strip_prefix("git+")?;
+        source
+            .split_once('#')
+            .map(|(_, commit)| commit.chars().take(7).collect::<String>())
+    };
+
+    if let Some((package, commit)) = packages
+        .iter()
+        .find_map(|package| Some((package, commit(package)?)))
+    {
+        return Some(format!("{} (git {})", package.version, commit));
+    }
+
+    packages
+        .iter()
+        .max_by(|a, b| {
+            match (
+                semver::Version::parse(&a.version),
+                semver::Version::parse(&b.version),
+            ) {
+                (Ok(a), Ok(b)) => a.cmp(&b),
+                _ => a.version.cmp(&b.version),
+            }
+        })
+        .map(|package| package.version.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn invalid_manifest() {
+        let dir = env::temp_dir().join("bench-bot-test-manifests");
+        let _ = fs::remove_dir_all(&dir);
+        for (member, bench) in [
+            ("axum", "scenarios = [\"hello-world\"]"),
+            ("tide", "category = \"micro\""),
+        ] {
+            fs::create_dir_all(dir.join(member)).unwrap();
+            fs::write(
+                dir.join(member).join("Cargo.toml"),
+                format!(
+                    "[package]\nname = \"{}\"\n\n[package.metadata.bench]\n{}\n",
+                    member, bench
+                ),
+            )
+            .unwrap();
+        }
+
+        let members = [PathBuf::from("axum"), PathBuf::from("tide")];
+        let problems = benchmarks(&dir, &members).unwrap_err();
+
+        assert_eq!(problems.len(), 1);
+        assert!(
+            problems[0].contains("tide") && problems[0].contains("unknown variant `micro`"),
+            "{}",
+            problems[0]
+        );
+        assert_eq!(
+            benchmarks(&dir, &members[..1]).map(|benchmarks| benchmarks.len()),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn variant() {
+        let given = r#"
+            [package]
+            name = "hello-world-axum-mimalloc"
+
+            [package.metadata.bench]
+            framework = "axum"
+            variant = { of = "axum", axis = "allocator" }
+        "#;
+
+        let actual = Manifest::parse(given).expect("parse manifest fail");
+
+        assert_eq!(
+            actual.variant,
+            Some(Variant {
+                of: "axum".to_string(),
+                axis: "allocator".to_string(),
+            })
+        );
+        assert!(Manifest::parse("[package.metadata.bench]\nvariant = { of = \"axum\" }").is_err());
+    }
+
+    #[test]
+    fn aliases() {
+        let given = r#"
+            [package]
+            name = "hello-world-actix-web"
+
+            [package.metadata.bench]
+            aliases = ["actix"]
+        "#;
+
+        assert_eq!(
+            Manifest::parse(given).expect("parse manifest fail").aliases,
+            vec!["actix".to_string()]
+        );
+    }
+
+    #[test]
+    fn metadata() {
+        let given = r#"
+            [package]
+            name = "hello-world-hyper"
+
+            [package.metadata.bench]
+            framework = "hyper"
+            maintainers = ["seanmonstar"]
+        "#;
+
+        let actual = Manifest::parse(given).expect("parse manifest fail");
+
+        assert_eq!(
+            actual.framework_crate(Path::new("hello-world/hyper-server")),
+            "hyper"
+        );
+        assert_eq!(actual.maintainers, vec!["seanmonstar".to_string()]);
+    }
+
+    #[test]
+    fn scenarios() {
+        let given = r#"
+            [package]
+            name = "hello-world-hyper-fast"
+
+            [package.metadata.bench]
+            scenarios = ["hello-world", "json"]
+            prefix = "/api"
+            category = "micro-framework"
+            experimental = true
+        "#;
+
+        let actual = Manifest::parse(given).expect("parse manifest fail");
+
+        assert_eq!(
+            actual.scenarios_of(Path::new("hello-world/hyper-fast")),
+            vec!["hello-world", "json"]
+        );
+        assert_eq!(actual.path_of("json"), "/api/json");
+        assert_eq!(actual.category, Category::MicroFramework);
+        assert!(actual.experimental);
+        assert_eq!(
+            Manifest::default().scenarios_of(Path::new("hello-world/axum")),
+            vec!["hello-world"]
+        );
+        assert_eq!(Manifest::default().path_of("hello-world"), "");
+    }
+
+    #[test]
+    fn git_pin() {
+        let given = r#"
+            [package]
+            name = "hello-world-hyper"
+
+            [package.metadata.bench]
+            framework = "hyper"
+            git = { url = "https://github.com/hyperium/hyper", rev = "master" }
+        "#;
+
+        let actual = Manifest::parse(given).expect("parse manifest fail");
+
+        assert_eq!(
+            actual.cargo_args(Path::new("hello-world/hyper")),
+            [
+                "--config",
+                r#"patch.crates-io.hyper.git="https://github.com/hyperium/hyper""#,
+                "--config",
+                r#"patch.crates-io.hyper.rev="master""#,
+            ]
+        );
+        assert!(Manifest::default()
+            .cargo_args(Path::new("hello-world/axum"))
+            .is_empty());
+    }
+
+    #[test]
+    fn versions() {
+        let given = r#"
+            [[package]]
+            name = "hyper"
+            version = "0.14.28"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+
+            [[package]]
+            name = "hyper"
+            version = "1.0.0-rc.4"
+            source = "git+https://github.com/hyperium/hyper?rev=master#3a1b6f2c9d8e7f6a5b4c3d2e1f0a9b8c7d6e5f4a"
+
+            [[package]]
+            name = "axum"
+            version = "0.6.20"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+
+            [[package]]
+            name = "axum"
+            version = "0.7.4"
+            source = "registry+https://github.com/rust-lang/crates.io-index"
+        "#;
+
+        assert_eq!(
+            resolved_version(given, "hyper").as_deref(),
+            Some("1.0.0-rc.4 (git 3a1b6f2)")
+        );
+        assert_eq!(resolved_version(given, "axum").as_deref(), Some("0.7.4"));
+        assert_eq!(resolved_version(given, "tide"), None);
+    }
+
+    #[test]
+    fn defaults() {
+        let actual = Manifest::parse("[package]\nname = \"axum\"").expect("parse manifest fail");
+
+        assert_eq!(actual, Manifest::default());
+        assert_eq!(
+            actual.framework_crate(Path::new("hello-world/axum")),
+            "axum"
+        );
+    }
+}
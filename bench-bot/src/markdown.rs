@@ -14,6 +14,11 @@ impl Markdown {
         self.string.push_str(s.as_ref());
     }
 
+    /// Append all items of `other`.
+    pub fn extend(&mut self, other: Markdown) {
+        self.string.push_str(&other.string);
+    }
+
     pub fn finish(self) -> String {
         let mut string = self.string.trim().to_owned();
         string.push('\n');
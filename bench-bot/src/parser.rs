@@ -0,0 +1,248 @@
+mod autocannon;
+mod bombardier;
+mod k6;
+mod oha;
+mod rewrk;
+mod vegeta;
+mod wrk;
+
+use std::collections::BTreeMap;
+
+use crate::report::{Errors, Metrics, MetricsError};
+
+/// Parser of the output of one load tool.
+pub trait MetricsParser {
+    /// Name of the tool, for logs.
+    fn name(&self) -> &'static str;
+
+    /// Whether `input` looks like output of the tool.
+    fn detect(&self, input: &str) -> bool;
+
+    /// Whether `input` is the whole output of a finished run, not cut off by
+    /// the tool being killed or its output being truncated.
+    fn complete(&self, input: &str) -> bool;
+
+    /// Metrics of `input`. Fields the tool always prints default to zero
+    /// when missing, unless `strict`.
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError>;
+}
+
+/// Pattern of a count or rate, with thousands separators or a magnitude
+/// suffix, e.g. `17,275,966` or `36.10k`.
+const NUMBER: &str = r"\d[\d,.]*[kKmM]?";
+
+/// Pattern of a duration in any unit load tools print, e.g. `2s`, `1.07ms`
+/// or `814.27us`.
+const DURATION: &str = r"\d[\d.]*(?:ns|us|µs|ms|s|m|h)";
+
+/// Pattern of a byte size in any unit, e.g. `860.5KB`, `1.95GB` or `512 B`.
+const BYTES: &str = r"\d[\d,.]*\s?[KMGT]?i?B";
+
+/// Values a parser expects, remembering the first one missing when parsing
+/// strictly.
+struct Fields {
+    tool: &'static str,
+    strict: bool,
+    missing: Option<MetricsError>,
+}
+
+impl Fields {
+    fn new(tool: &'static str, strict: bool) -> Self {
+        Self {
+            tool,
+            strict,
+            missing: None,
+        }
+    }
+
+    /// `value` of `field` as found by `pattern`, e.g. a regex, the default
+    /// when missing.
+    fn require<T: Default>(&mut self, field: &'static str, pattern: &str, value: Option<T>) -> T {
+        if value.is_none() && self.strict && self.missing.is_none() {
+            self.missing = Some(MetricsError::MissingField {
+                tool: self.tool,
+                field,
+                pattern: pattern.to_string(),
+            });
+        }
+
+        value.unwrap_or_default()
+    }
+
+    fn finish(self, metrics: Metrics) -> Result<Metrics, MetricsError> {
+        match self.missing {
+            Some(error) => Err(error),
+            None => Ok(metrics),
+        }
+    }
+}
+
+/// Whether `input` is a whole JSON document, for tools printing one.
+fn complete_json(input: &str) -> bool {
+    serde_json::from_str::<serde::de::IgnoredAny>(input.trim()).is_ok()
+}
+
+/// Responses by status code of a tool counting them by the code's text,
+/// leaving out vegeta's `0` of requests that got no response.
+fn status_codes(counts: &BTreeMap<String, u64>) -> BTreeMap<u16, u64> {
+    counts
+        .iter()
+        .filter_map(|(code, count)| {
+            Some((code.parse::<u16>().ok().filter(|code| *code > 0)?, *count))
+        })
+        .collect()
+}
+
+/// Add `count` errors of a tool counting them by message, sorted into wrk's
+/// kinds by what the message mentions.
+fn count_error(errors: &mut Errors, message: &str, count: u64) {
+    let message = message.to_lowercase();
+
+    if message.contains("timeout") || message.contains("timed out") {
+        errors.timeout += count;
+    } else if message.contains("connect") {
+        errors.connect += count;
+    } else {
+        errors.read += count;
+    }
+}
+
+/// Parsers of every supported tool, the first detecting an output parses
+/// it. Tools with looser detection go last.
+const PARSERS: &[&dyn MetricsParser] = &[
+    &oha::Oha,
+    &vegeta::Vegeta,
+    &autocannon::Autocannon,
+    &k6::K6,
+    &bombardier::Bombardier,
+    &rewrk::Rewrk,
+    &wrk::Wrk,
+];
+
+/// Parser of the tool that printed `input`.
+pub fn detect(input: &str) -> Option<&'static dyn MetricsParser> {
+    PARSERS.iter().copied().find(|parser| parser.detect(input))
+}
+
+/// Metrics of `input`, whichever supported tool printed it. `strict` fails
+/// on missing fields instead of reporting them as zero.
+pub fn parse(input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+    let parser = detect(input).ok_or(MetricsError::ParseError)?;
+    log::debug!("Parsing {} output.", parser.name());
+
+    parser.parse(input, strict)
+}
+
+/// Outputs of each round of `input`, rewrk run with `--rounds` prints one
+/// after another below `Beginning round <n>...`. Output of other tools is a
+/// single round.
+pub fn split_rounds(input: &str) -> Vec<&str> {
+    let starts: Vec<usize> = input
+        .match_indices("Beginning round ")
+        .map(|(start, _)| start)
+        .collect();
+    if starts.len() < 2 {
+        return vec![input];
+    }
+
+    starts
+        .iter()
+        .zip(starts.iter().skip(1).copied().chain([input.len()]))
+        .map(|(start, end)| &input[*start..end])
+        .collect()
+}
+
+/// Metrics of the output of one round and whether it was complete. Output
+/// cut off early is parsed leniently, keeping whatever it printed before.
+pub fn parse_round(input: &str, strict: bool) -> Result<(Metrics, bool), MetricsError> {
+    let parser = detect(input).ok_or(MetricsError::ParseError)?;
+    let complete = parser.complete(input);
+
+    match parser.parse(input, strict && complete) {
+        Ok(metrics) => Ok((metrics, complete)),
+        Err(_) if !complete => Err(MetricsError::Truncated {
+            tool: parser.name(),
+        }),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_tool() {
+        let wrk = "  Thread Stats   Avg      Stdev     Max   +/- Stdev\n    Latency   814.27us  \
+            498.47us   8.42ms   69.23%";
+        let rewrk = "  Latencies:\n    Avg      Stdev    Min      Max\n  Requests:\n    Total: 1 \
+            Req/Sec: 1.00";
+        let oha = r#"{"summary": {"requestsPerSec": 1.0}, "latencyPercentiles": {}}"#;
+        let bombardier = "Statistics        Avg      Stdev        Max\n  Reqs/sec    574184.09   \
+            36100.12  748300.00";
+        let vegeta = r#"{"latencies": {"mean": 1}, "bytes_in": {"total": 1}}"#;
+        let autocannon = r#"{"latency": {"average": 1}, "2xx": 1}"#;
+        let k6 = r#"{"metrics": {"http_reqs": {"count": 1, "rate": 1.0}}}"#;
+        let bombardier_json = r#"{"spec": {"numberOfConnections": 500}, "result": {"req2xx": 1}}"#;
+
+        assert_eq!(detect(wrk).map(|parser| parser.name()), Some("wrk"));
+        assert_eq!(detect(rewrk).map(|parser| parser.name()), Some("rewrk"));
+        assert_eq!(detect(oha).map(|parser| parser.name()), Some("oha"));
+        assert_eq!(detect(vegeta).map(|parser| parser.name()), Some("vegeta"));
+        assert_eq!(
+            detect(autocannon).map(|parser| parser.name()),
+            Some("autocannon")
+        );
+        assert_eq!(detect(k6).map(|parser| parser.name()), Some("k6"));
+        assert_eq!(
+            detect(bombardier).map(|parser| parser.name()),
+            Some("bombardier")
+        );
+        assert_eq!(
+            detect(bombardier_json).map(|parser| parser.name()),
+            Some("bombardier")
+        );
+        assert_eq!(
+            detect("connection refused").map(|parser| parser.name()),
+            None
+        );
+    }
+
+    #[test]
+    fn rewrk_rounds() {
+        let round = |rps: &str| {
+            format!(
+                "Beginning round 1...\nBenchmarking 256 connections @ http://127.0.0.1:3000 for 30 \
+                    second(s)\n  Latencies:\n    Avg      Stdev    Min      Max\n    0.69ms   \
+                    0.37ms   0.03ms   16.72ms\n  Requests:\n    Total: 11110818 Req/Sec: {}\n  \
+                    Transfer:\n    Total: 942.98 MB Transfer Rate: 31.43 MB/Sec\n",
+                rps
+            )
+        };
+        let given = format!("{}{}", round("370360.59"), round("365000.00"));
+
+        let actual: Vec<f64> = split_rounds(&given)
+            .iter()
+            .map(|round| parse(round, true).unwrap().req_per_sec())
+            .collect();
+        assert_eq!(actual, [370360.59, 365000.0]);
+        assert_eq!(split_rounds("Requests/sec: 1.00"), ["Requests/sec: 1.00"]);
+    }
+
+    #[test]
+    fn cut_off() {
+        let wrk = "  Thread Stats   Avg      Stdev     Max   +/- Stdev\n    Latency   814.27us  \
+            498.47us   8.42ms   69.23%\n  17275966 requests in 30.09s, 2.04GB read";
+        let oha = r#"{"summary": {"requestsPerSec": 1.0}, "latencyPercentiles": {"p50": 0.1"#;
+
+        let (metrics, complete) = parse_round(wrk, true).expect("parse metric fail");
+        assert!(!complete);
+        assert_eq!(metrics.total_requests(), 17275966.0);
+        assert_eq!(metrics.req_per_sec(), 0.0);
+
+        assert_eq!(
+            parse_round(oha, false).map(|(_, complete)| complete),
+            Err(MetricsError::Truncated { tool: "oha" })
+        );
+    }
+}
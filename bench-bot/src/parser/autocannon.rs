@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::{complete_json, status_codes, Fields, MetricsParser};
+use crate::report::{Errors, Latency, Metrics, MetricsError, Request, Transfer};
+
+/// Output of autocannon run with `--json`, latencies are in milliseconds.
+pub struct Autocannon;
+
+#[derive(Deserialize)]
+struct Output {
+    latency: Stats,
+    requests: Stats,
+    throughput: Stats,
+    /// Failed requests, timeouts included.
+    #[serde(default)]
+    errors: u64,
+    #[serde(default)]
+    timeouts: u64,
+    #[serde(default, rename = "1xx")]
+    status_1xx: u64,
+    #[serde(default, rename = "4xx")]
+    status_4xx: u64,
+    #[serde(default, rename = "5xx")]
+    status_5xx: u64,
+    /// Responses by status code, printed by autocannon 7.10 and later.
+    #[serde(default, rename = "statusCodeStats")]
+    status_code_stats: BTreeMap<String, StatusCount>,
+}
+
+#[derive(Deserialize)]
+struct StatusCount {
+    count: u64,
+}
+
+/// Per second samples of requests and throughput, every response of
+/// latencies.
+#[derive(Deserialize)]
+struct Stats {
+    average: Option<f64>,
+    stddev: Option<f64>,
+    min: Option<f64>,
+    max: Option<f64>,
+    total: Option<f64>,
+    p50: Option<f64>,
+    p75: Option<f64>,
+    p90: Option<f64>,
+    p99: Option<f64>,
+}
+
+impl MetricsParser for Autocannon {
+    fn name(&self) -> &'static str {
+        "autocannon"
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.trim_start().starts_with('{')
+            && input.contains("\"latency\"")
+            && input.contains("\"2xx\"")
+    }
+
+    fn complete(&self, input: &str) -> bool {
+        complete_json(input)
+    }
+
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let output: Output =
+            serde_json::from_str(input.trim()).map_err(|_| MetricsError::ParseError)?;
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let millis = |ms: Option<f64>| {
+            ms.map_or(Duration::ZERO, |ms| {
+                Duration::from_secs_f64(ms.max(0.0) / 1000.0)
+            })
+        };
+        let latency = &output.latency;
+        let average = fields.require("average latency", "latency.average", latency.average);
+
+        let request = Request {
+            total: fields
+                .require("total requests", "requests.total", output.requests.total)
+                .round() as u64,
+            req_per_sec: fields.require(
+                "requests per second",
+                "requests.average",
+                output.requests.average,
+            ),
+        };
+        let transfer = Transfer {
+            total: fields
+                .require("total bytes", "throughput.total", output.throughput.total)
+                .round() as u64,
+            rate: fields.require(
+                "bytes per second",
+                "throughput.average",
+                output.throughput.average,
+            ),
+        };
+
+        fields.finish(Metrics {
+            latency: Latency {
+                avg: millis(Some(average)),
+                std_env: millis(latency.stddev),
+                min: millis(latency.min),
+                max: millis(latency.max),
+                p50: millis(latency.p50),
+                p75: millis(latency.p75),
+                p90: millis(latency.p90),
+                p99: millis(latency.p99),
+            },
+            request,
+            transfer,
+            threads: None,
+            errors: Errors {
+                read: output.errors.saturating_sub(output.timeouts),
+                timeout: output.timeouts,
+                status: output.status_1xx + output.status_4xx + output.status_5xx,
+                ..Errors::default()
+            },
+            timeline: Vec::new(),
+            status_codes: status_codes(
+                &output
+                    .status_code_stats
+                    .iter()
+                    .map(|(code, stats)| (code.clone(), stats.count))
+                    .collect(),
+            ),
+            phases: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn autocannon() {
+        let given = r#"
+{
+  "url": "http://127.0.0.1:3000",
+  "connections": 500,
+  "duration": 30.04,
+  "errors": 7,
+  "timeouts": 2,
+  "non2xx": 66,
+  "1xx": 0,
+  "2xx": 3300000,
+  "3xx": 0,
+  "4xx": 0,
+  "5xx": 66,
+  "statusCodeStats": {"200": {"count": 3300000}, "503": {"count": 66}},
+  "latency": {"average": 4.42, "mean": 4.42, "stddev": 1.25, "min": 1, "max": 84, "p50": 4,
+      "p75": 5, "p90": 6, "p97_5": 8, "p99": 9, "totalCount": 3300066},
+  "requests": {"average": 110002.2, "mean": 110002.2, "stddev": 2400.5, "min": 101200,
+      "max": 114500, "total": 3300066, "sent": 3300566},
+  "throughput": {"average": 19360387.2, "mean": 19360387.2, "stddev": 422488, "min": 17811200,
+      "max": 20152000, "total": 580811616}
+}
+        "#;
+        let actual = Autocannon.parse(given, true).expect("parse metric fail");
+
+        assert_eq!(actual.latency.avg, Duration::from_micros(4_420));
+        assert_eq!(actual.latency.p99, Duration::from_millis(9));
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 3300066,
+                req_per_sec: 110002.2,
+            }
+        );
+        assert_eq!(
+            actual.transfer,
+            Transfer {
+                total: 580811616,
+                rate: 19360387.2,
+            }
+        );
+        assert_eq!(
+            actual.errors,
+            Errors {
+                read: 5,
+                timeout: 2,
+                status: 66,
+                ..Errors::default()
+            }
+        );
+        assert_eq!(
+            actual.status_codes,
+            BTreeMap::from([(200, 3300000), (503, 66)])
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use regex::Regex;
+use serde::Deserialize;
+
+use super::{complete_json, count_error, Fields, MetricsParser, BYTES, DURATION, NUMBER};
+use crate::report::{Errors, Latency, Metrics, MetricsError, Request, Transfer};
+use crate::units;
+
+/// Output of bombardier, the text layout or `--format json`, with the
+/// distribution when run with `--latencies`.
+pub struct Bombardier;
+
+#[derive(Deserialize)]
+struct Output {
+    result: Results,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Results {
+    bytes_read: Option<u64>,
+    time_taken_seconds: Option<f64>,
+    #[serde(default)]
+    req1xx: u64,
+    #[serde(default)]
+    req2xx: u64,
+    #[serde(default)]
+    req3xx: u64,
+    #[serde(default)]
+    req4xx: u64,
+    #[serde(default)]
+    req5xx: u64,
+    #[serde(default)]
+    errors: Vec<Error>,
+    latency: Option<Stats>,
+    rps: Option<Stats>,
+}
+
+#[derive(Deserialize)]
+struct Error {
+    description: String,
+    count: u64,
+}
+
+/// Latencies are in microseconds.
+#[derive(Deserialize)]
+struct Stats {
+    mean: f64,
+    stddev: f64,
+    max: f64,
+    #[serde(default)]
+    percentiles: BTreeMap<String, f64>,
+}
+
+impl MetricsParser for Bombardier {
+    fn name(&self) -> &'static str {
+        "bombardier"
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.contains("Reqs/sec")
+            || (input.trim_start().starts_with('{')
+                && input.contains("\"spec\"")
+                && input.contains("\"result\""))
+    }
+
+    fn complete(&self, input: &str) -> bool {
+        if input.trim_start().starts_with('{') {
+            complete_json(input)
+        } else {
+            // the last line bombardier prints
+            input.contains("Throughput:")
+        }
+    }
+
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        if input.trim_start().starts_with('{') {
+            self.parse_json(input, strict)
+        } else {
+            self.parse_text(input, strict)
+        }
+    }
+}
+
+impl Bombardier {
+    fn parse_json(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let output: Output =
+            serde_json::from_str(input.trim()).map_err(|_| MetricsError::ParseError)?;
+        let result = output.result;
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let latency = result.latency.ok_or(MetricsError::ParseError)?;
+        let micros = |micros: f64| Duration::from_secs_f64(micros.max(0.0) / 1e6);
+        let percentile = |key: &str| {
+            latency
+                .percentiles
+                .get(key)
+                .copied()
+                .map_or(Duration::ZERO, micros)
+        };
+
+        let bytes_read = fields.require("bytes read", "result.bytesRead", result.bytes_read);
+        let time_taken = fields.require(
+            "time taken",
+            "result.timeTakenSeconds",
+            result.time_taken_seconds,
+        );
+        let req_per_sec = fields.require(
+            "requests per second",
+            "result.rps.mean",
+            result.rps.map(|rps| rps.mean),
+        );
+
+        let mut errors = Errors {
+            status: result.req1xx + result.req4xx + result.req5xx,
+            ..Errors::default()
+        };
+        for error in &result.errors {
+            count_error(&mut errors, &error.description, error.count);
+        }
+
+        fields.finish(Metrics {
+            latency: Latency {
+                avg: micros(latency.mean),
+                std_env: micros(latency.stddev),
+                // bombardier reports no minimum
+                min: Duration::ZERO,
+                max: micros(latency.max),
+                p50: percentile("50"),
+                p75: percentile("75"),
+                p90: percentile("90"),
+                p99: percentile("99"),
+            },
+            request: Request {
+                total: result.req1xx
+                    + result.req2xx
+                    + result.req3xx
+                    + result.req4xx
+                    + result.req5xx,
+                req_per_sec,
+            },
+            transfer: Transfer {
+                total: bytes_read,
+                rate: if time_taken > 0.0 {
+                    bytes_read as f64 / time_taken
+                } else {
+                    0.0
+                },
+            },
+            threads: None,
+            errors,
+            timeline: Vec::new(),
+            status_codes: BTreeMap::new(),
+            phases: None,
+        })
+    }
+
+    fn parse_text(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let latency_regex =
+            Regex::new(&format!(r"Latency\s+({0})\s+({0})\s+({0})", DURATION)).unwrap();
+        let req_per_sec_regex = Regex::new(&format!(r"Reqs/sec\s+({})", NUMBER)).unwrap();
+        let codes_regex =
+            Regex::new(r"1xx - (\d+), 2xx - (\d+), 3xx - (\d+), 4xx - (\d+), 5xx - (\d+)").unwrap();
+        let throughput_regex = Regex::new(&format!(r"Throughput:\s+({})/s", BYTES)).unwrap();
+        let error_regex = Regex::new(r"(?m)^\s+(.+) - (\d+)$").unwrap();
+
+        // without the latency line this is not bombardier output at all
+        let latency_captures = latency_regex
+            .captures(input)
+            .ok_or(MetricsError::ParseError)?;
+        let duration = |index: usize| {
+            latency_captures
+                .get(index)
+                .and_then(|m| units::parse_duration(m.as_str()))
+                .unwrap_or_default()
+        };
+
+        let percentile = |percent: u32| {
+            Regex::new(&format!(r"\s{}%\s+({})", percent, DURATION))
+                .unwrap()
+                .captures(input)
+                .and_then(|cap| units::parse_duration(&cap[1]))
+                .unwrap_or_default()
+        };
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let req_per_sec = req_per_sec_regex
+            .captures(input)
+            .and_then(|cap| units::parse_number(&cap[1]));
+        let req_per_sec = fields.require("Reqs/sec", req_per_sec_regex.as_str(), req_per_sec);
+        let throughput = throughput_regex
+            .captures(input)
+            .and_then(|cap| units::parse_bytes(&cap[1]));
+        let throughput = fields.require("Throughput", throughput_regex.as_str(), throughput);
+        let codes: Option<Vec<u64>> = codes_regex.captures(input).map(|cap| {
+            (1..=5)
+                .map(|index| cap[index].parse().unwrap_or_default())
+                .collect()
+        });
+        let codes = fields.require("HTTP codes", codes_regex.as_str(), codes);
+
+        // errors are listed by message between their heading and the throughput
+        let mut errors = Errors {
+            status: [0, 3, 4].iter().filter_map(|index| codes.get(*index)).sum(),
+            ..Errors::default()
+        };
+        if let Some(listed) = input
+            .split_once("Errors:")
+            .map(|(_, rest)| rest.split("Throughput:").next().unwrap_or(rest))
+        {
+            for cap in error_regex.captures_iter(listed) {
+                count_error(&mut errors, &cap[1], cap[2].parse().unwrap_or_default());
+            }
+        }
+
+        fields.finish(Metrics {
+            latency: Latency {
+                avg: duration(1),
+                std_env: duration(2),
+                min: Duration::ZERO,
+                max: duration(3),
+                p50: percentile(50),
+                p75: percentile(75),
+                p90: percentile(90),
+                p99: percentile(99),
+            },
+            request: Request {
+                total: codes.iter().sum(),
+                req_per_sec,
+            },
+            // bombardier prints no total of bytes read
+            transfer: Transfer {
+                total: 0,
+                rate: throughput as f64,
+            },
+            threads: None,
+            errors,
+            timeline: Vec::new(),
+            status_codes: BTreeMap::new(),
+            phases: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text() {
+        let given = r#"
+Bombarding http://127.0.0.1:3000 for 30s using 500 connection(s)
+[==========================================================================] 30s
+Done!
+Statistics        Avg      Stdev        Max
+  Reqs/sec    574184.09   36100.12  748300.00
+  Latency      814.27us   498.47us     8.42ms
+  Latency Distribution
+     50%   707.00us
+     75%     1.07ms
+     90%     1.50ms
+     95%     1.90ms
+     99%     2.56ms
+  HTTP codes:
+    1xx - 0, 2xx - 17275900, 3xx - 0, 4xx - 0, 5xx - 66
+    others - 7
+  Errors:
+    dial tcp 127.0.0.1:3000: connect: connection refused - 5
+          timeout - 2
+  Throughput:    66.26MB/s
+        "#;
+        let actual = Bombardier.parse(given, true).expect("parse metric fail");
+
+        assert_eq!(
+            actual.latency,
+            Latency {
+                avg: Duration::from_nanos(814_270),
+                std_env: Duration::from_nanos(498_470),
+                min: Duration::ZERO,
+                max: Duration::from_micros(8_420),
+                p50: Duration::from_micros(707),
+                p75: Duration::from_micros(1_070),
+                p90: Duration::from_micros(1_500),
+                p99: Duration::from_micros(2_560),
+            }
+        );
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 17275966,
+                req_per_sec: 574184.09,
+            }
+        );
+        assert_eq!(actual.transfer.rate, 69478646.0);
+        assert_eq!(
+            actual.errors,
+            Errors {
+                connect: 5,
+                timeout: 2,
+                status: 66,
+                ..Errors::default()
+            }
+        );
+    }
+
+    #[test]
+    fn json() {
+        let given = r#"
+{
+  "spec": {"numberOfConnections": 500, "testType": "timed", "testDurationSeconds": 30,
+      "method": "GET", "url": "http://127.0.0.1:3000"},
+  "result": {
+    "bytesRead": 2093796557,
+    "bytesWritten": 1036557960,
+    "timeTakenSeconds": 30.0,
+    "req1xx": 0,
+    "req2xx": 17275900,
+    "req3xx": 0,
+    "req4xx": 0,
+    "req5xx": 66,
+    "others": 7,
+    "errors": [{"description": "timeout", "count": 7}],
+    "latency": {"mean": 814.27, "stddev": 498.47, "max": 8420, "percentiles": {"50": 707,
+        "75": 1070, "90": 1500, "95": 1900, "99": 2560}},
+    "rps": {"mean": 574184.09, "stddev": 36100.12, "max": 748300, "percentiles": {"50": 574000}}
+  }
+}
+        "#;
+        let actual = Bombardier.parse(given, true).expect("parse metric fail");
+
+        assert_eq!(actual.latency.p99, Duration::from_micros(2_560));
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 17275966,
+                req_per_sec: 574184.09,
+            }
+        );
+        assert_eq!(
+            actual.transfer,
+            Transfer {
+                total: 2093796557,
+                rate: 2093796557.0 / 30.0,
+            }
+        );
+        assert_eq!(actual.errors.total(), 73);
+    }
+}
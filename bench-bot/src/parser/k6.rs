@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use super::{complete_json, Fields, MetricsParser};
+use crate::report::{Errors, Latency, Metrics, MetricsError, Phases, Request, Transfer};
+
+/// Output of k6's `--summary-export`, or of a `handleSummary` writing the
+/// summary as JSON. Durations are in milliseconds.
+pub struct K6;
+
+impl MetricsParser for K6 {
+    fn name(&self) -> &'static str {
+        "k6"
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.trim_start().starts_with('{')
+            && input.contains("\"metrics\"")
+            && input.contains("\"http_reqs\"")
+    }
+
+    fn complete(&self, input: &str) -> bool {
+        complete_json(input)
+    }
+
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let output: Value =
+            serde_json::from_str(input.trim()).map_err(|_| MetricsError::ParseError)?;
+        let metrics = output.get("metrics").ok_or(MetricsError::ParseError)?;
+
+        // `--summary-export` keeps values next to each other, `handleSummary`
+        // below `values`
+        let value = |metric: &str, key: &str| {
+            let metric = metrics.get(metric)?;
+            metric.get("values").unwrap_or(metric).get(key)?.as_f64()
+        };
+        let duration = |metric: &str, key: &str| {
+            value(metric, key).map(|ms| Duration::from_secs_f64(ms.max(0.0) / 1000.0))
+        };
+        let millis = |key: &str| duration("http_req_duration", key).unwrap_or(Duration::ZERO);
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let request = Request {
+            total: fields
+                .require(
+                    "total requests",
+                    "metrics.http_reqs.count",
+                    value("http_reqs", "count"),
+                )
+                .round() as u64,
+            req_per_sec: fields.require(
+                "requests per second",
+                "metrics.http_reqs.rate",
+                value("http_reqs", "rate"),
+            ),
+        };
+        let transfer = Transfer {
+            total: fields
+                .require(
+                    "data received",
+                    "metrics.data_received.count",
+                    value("data_received", "count"),
+                )
+                .round() as u64,
+            rate: fields.require(
+                "data received per second",
+                "metrics.data_received.rate",
+                value("data_received", "rate"),
+            ),
+        };
+        let average = fields.require(
+            "average duration",
+            "metrics.http_req_duration.avg",
+            value("http_req_duration", "avg"),
+        );
+
+        fields.finish(Metrics {
+            // k6 reports no deviation, percentiles beyond p(90) and p(95)
+            // only when configured with `summaryTrendStats`
+            latency: Latency {
+                avg: Duration::from_secs_f64(average.max(0.0) / 1000.0),
+                std_env: Duration::ZERO,
+                min: millis("min"),
+                max: millis("max"),
+                p50: millis("med"),
+                p75: millis("p(75)"),
+                p90: millis("p(90)"),
+                p99: millis("p(99)"),
+            },
+            request,
+            transfer,
+            threads: None,
+            // failed requests count error statuses and transport errors alike
+            errors: Errors {
+                status: value("http_req_failed", "passes")
+                    .map_or(0, |failed| failed.round() as u64),
+                ..Errors::default()
+            },
+            timeline: Vec::new(),
+            status_codes: BTreeMap::new(),
+            // their p(99) too is only there with `summaryTrendStats` asking
+            phases: duration("http_req_waiting", "p(99)").map(|waiting| Phases {
+                connecting: duration("http_req_connecting", "p(99)").unwrap_or(Duration::ZERO),
+                tls_handshaking: duration("http_req_tls_handshaking", "p(99)")
+                    .unwrap_or(Duration::ZERO),
+                waiting,
+            }),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summary_export() {
+        let given = r#"
+{
+  "root_group": {"name": "", "path": "", "groups": [], "checks": []},
+  "metrics": {
+    "http_req_duration": {"avg": 0.81427, "min": 0.02, "med": 0.707, "max": 8.42, "p(90)": 1.5,
+        "p(95)": 1.9, "p(99)": 2.56},
+    "http_reqs": {"count": 17275966, "rate": 574184.09},
+    "data_received": {"count": 2093796557, "rate": 69478646},
+    "http_req_failed": {"passes": 66, "fails": 17275900, "value": 0.0000038}
+  }
+}
+        "#;
+        let actual = K6.parse(given, true).expect("parse metric fail");
+
+        assert_eq!(actual.latency.p50, Duration::from_micros(707));
+        assert_eq!(actual.latency.p75, Duration::ZERO);
+        assert_eq!(actual.latency.p99, Duration::from_micros(2_560));
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 17275966,
+                req_per_sec: 574184.09,
+            }
+        );
+        assert_eq!(
+            actual.transfer,
+            Transfer {
+                total: 2093796557,
+                rate: 69478646.0,
+            }
+        );
+        assert_eq!(actual.errors.total(), 66);
+        assert_eq!(actual.phases, None);
+
+        let with_phases = given.replace(
+            r#""http_reqs""#,
+            r#""http_req_connecting": {"p(99)": 0.012},
+    "http_req_tls_handshaking": {"p(99)": 0},
+    "http_req_waiting": {"p(99)": 2.41},
+    "http_reqs""#,
+        );
+        assert_eq!(
+            K6.parse(&with_phases, true)
+                .expect("parse metric fail")
+                .phases,
+            Some(Phases {
+                connecting: Duration::from_micros(12),
+                tls_handshaking: Duration::ZERO,
+                waiting: Duration::from_micros(2_410),
+            })
+        );
+    }
+
+    #[test]
+    fn handle_summary() {
+        let given = r#"{"metrics": {
+            "http_req_duration": {"type": "trend", "contains": "time", "values": {"avg": 0.8,
+                "med": 0.7, "max": 8.4, "p(90)": 1.5}},
+            "http_reqs": {"type": "counter", "contains": "default", "values": {"count": 100,
+                "rate": 10.5}}
+        }}"#;
+
+        assert_eq!(
+            K6.parse(given, false).map(|metrics| metrics.req_per_sec()),
+            Ok(10.5)
+        );
+        assert_eq!(
+            K6.parse(given, true),
+            Err(MetricsError::MissingField {
+                tool: "k6",
+                field: "data received",
+                pattern: "metrics.data_received.count".to_string(),
+            })
+        );
+    }
+}
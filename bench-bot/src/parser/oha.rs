@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::{complete_json, count_error, status_codes, Fields, MetricsParser};
+use crate::report::{Errors, Latency, Metrics, MetricsError, Request, Transfer};
+
+/// Output of oha run with `--json`, times are in seconds.
+pub struct Oha;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Output {
+    summary: Summary,
+    #[serde(default)]
+    response_time_histogram: BTreeMap<String, u64>,
+    latency_percentiles: BTreeMap<String, Option<f64>>,
+    #[serde(default)]
+    status_code_distribution: BTreeMap<String, u64>,
+    #[serde(default)]
+    error_distribution: BTreeMap<String, u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Summary {
+    average: Option<f64>,
+    slowest: Option<f64>,
+    fastest: Option<f64>,
+    requests_per_sec: Option<f64>,
+    total_data: Option<u64>,
+    size_per_sec: Option<f64>,
+}
+
+impl MetricsParser for Oha {
+    fn name(&self) -> &'static str {
+        "oha"
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.trim_start().starts_with('{') && input.contains("\"latencyPercentiles\"")
+    }
+
+    fn complete(&self, input: &str) -> bool {
+        complete_json(input)
+    }
+
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let output: Output =
+            serde_json::from_str(input.trim()).map_err(|_| MetricsError::ParseError)?;
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let seconds = |seconds: Option<f64>| {
+            seconds.map_or(Duration::ZERO, |seconds| {
+                Duration::from_secs_f64(seconds.max(0.0))
+            })
+        };
+        let percentile =
+            |key: &str| seconds(output.latency_percentiles.get(key).copied().flatten());
+
+        let average = fields.require("average latency", "summary.average", output.summary.average);
+        let latency = Latency {
+            avg: seconds(Some(average)),
+            std_env: seconds(Some(stdev(&output.response_time_histogram, average))),
+            min: seconds(output.summary.fastest),
+            max: seconds(output.summary.slowest),
+            p50: percentile("p50"),
+            p75: percentile("p75"),
+            p90: percentile("p90"),
+            p99: percentile("p99"),
+        };
+
+        let request = Request {
+            total: output.status_code_distribution.values().sum(),
+            req_per_sec: fields.require(
+                "requests per second",
+                "summary.requestsPerSec",
+                output.summary.requests_per_sec,
+            ),
+        };
+        let transfer = Transfer {
+            total: fields.require("total data", "summary.totalData", output.summary.total_data),
+            rate: fields.require(
+                "data per second",
+                "summary.sizePerSec",
+                output.summary.size_per_sec,
+            ),
+        };
+
+        fields.finish(Metrics {
+            latency,
+            request,
+            transfer,
+            threads: None,
+            errors: errors(&output.status_code_distribution, &output.error_distribution),
+            timeline: Vec::new(),
+            status_codes: status_codes(&output.status_code_distribution),
+            phases: None,
+        })
+    }
+}
+
+/// Standard deviation of latencies in seconds, estimated from the histogram
+/// as oha doesn't report it.
+fn stdev(histogram: &BTreeMap<String, u64>, average: f64) -> f64 {
+    let buckets: Vec<(f64, f64)> = histogram
+        .iter()
+        .filter_map(|(latency, count)| Some((latency.parse::<f64>().ok()?, *count as f64)))
+        .collect();
+    let count: f64 = buckets.iter().map(|(_, count)| count).sum();
+
+    if count == 0.0 {
+        return 0.0;
+    }
+
+    (buckets
+        .iter()
+        .map(|(latency, n)| n * (latency - average).powi(2))
+        .sum::<f64>()
+        / count)
+        .sqrt()
+}
+
+fn errors(status_codes: &BTreeMap<String, u64>, messages: &BTreeMap<String, u64>) -> Errors {
+    let mut errors = Errors {
+        status: status_codes
+            .iter()
+            .filter(|(code, _)| !matches!(code.parse::<u16>(), Ok(200..=399)))
+            .map(|(_, count)| count)
+            .sum(),
+        ..Errors::default()
+    };
+
+    for (message, count) in messages {
+        count_error(&mut errors, message, *count);
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = r#"
+{
+  "summary": {
+    "successRate": 1.0,
+    "total": 30.001,
+    "slowest": 0.00842,
+    "fastest": 0.00002,
+    "average": 0.0008,
+    "requestsPerSec": 574184.09,
+    "totalData": 224587558,
+    "sizePerRequest": 13,
+    "sizePerSec": 7486249.4
+  },
+  "responseTimeHistogram": {
+    "0.0006": 10,
+    "0.001": 10
+  },
+  "latencyPercentiles": {
+    "p10": 0.0003,
+    "p25": 0.0005,
+    "p50": 0.000707,
+    "p75": 0.00107,
+    "p90": 0.0015,
+    "p95": 0.0019,
+    "p99": 0.00256,
+    "p99.9": 0.0051,
+    "p99.99": 0.0079
+  },
+  "rps": {
+    "mean": 574180.1,
+    "stddev": 1200.3
+  },
+  "statusCodeDistribution": {
+    "200": 17275900,
+    "500": 66
+  },
+  "errorDistribution": {
+    "connection closed before message completed": 5,
+    "timeout": 2
+  }
+}
+    "#;
+
+    #[test]
+    fn oha() {
+        let actual = Oha.parse(OUTPUT, true).expect("parse metric fail");
+
+        assert_eq!(
+            actual.latency,
+            Latency {
+                avg: Duration::from_micros(800),
+                std_env: Duration::from_micros(200),
+                min: Duration::from_micros(20),
+                max: Duration::from_micros(8_420),
+                p50: Duration::from_micros(707),
+                p75: Duration::from_micros(1_070),
+                p90: Duration::from_micros(1_500),
+                p99: Duration::from_micros(2_560),
+            }
+        );
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 17275966,
+                req_per_sec: 574184.09,
+            }
+        );
+        assert_eq!(
+            actual.transfer,
+            Transfer {
+                total: 224587558,
+                rate: 7486249.4,
+            }
+        );
+        assert_eq!(
+            actual.errors,
+            Errors {
+                connect: 5,
+                timeout: 2,
+                status: 66,
+                ..Errors::default()
+            }
+        );
+        assert_eq!(
+            actual.status_codes,
+            BTreeMap::from([(200, 17275900), (500, 66)])
+        );
+    }
+
+    #[test]
+    fn strict() {
+        let given = r#"{
+            "summary": {"average": 0.0008, "requestsPerSec": 574184.09},
+            "latencyPercentiles": {}
+        }"#;
+
+        assert!(Oha.parse(given, false).is_ok());
+        assert_eq!(
+            Oha.parse(given, true),
+            Err(MetricsError::MissingField {
+                tool: "oha",
+                field: "total data",
+                pattern: "summary.totalData".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use regex::Regex;
+
+use super::{Fields, MetricsParser, BYTES, NUMBER};
+use crate::report::{Errors, Latency, Metrics, MetricsError, Request, Transfer};
+use crate::units;
+
+/// Output of rewrk, the text layout rather than `--json`.
+pub struct Rewrk;
+
+impl MetricsParser for Rewrk {
+    fn name(&self) -> &'static str {
+        "rewrk"
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.contains("Latencies:") && input.contains("Requests:")
+    }
+
+    fn complete(&self, input: &str) -> bool {
+        input.contains("Transfer Rate:")
+    }
+
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let latency_regex =
+            Regex::new(r"Latencies:\s*Avg\s+Stdev\s+Min\s+Max\s+(\S+)\s+(\S+)\s+(\S+)\s+(\S+)")
+                .unwrap();
+        let requests_regex = Regex::new(&format!(
+            r"Requests:\s*Total:\s*({0})\s+Req/Sec:\s*({0})",
+            NUMBER
+        ))
+        .unwrap();
+        let transfer_regex = Regex::new(&format!(
+            r"Transfer:\s*Total:\s*({0})\s+Transfer Rate:\s*({0})",
+            BYTES
+        ))
+        .unwrap();
+
+        let latency_captures = latency_regex
+            .captures(input)
+            .ok_or(MetricsError::ParseError)?;
+        let duration = |index: usize| {
+            latency_captures
+                .get(index)
+                .and_then(|m| units::parse_duration(m.as_str()))
+                .unwrap_or_default()
+        };
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let requests = requests_regex.captures(input);
+        let number = |index: usize| {
+            requests
+                .as_ref()
+                .and_then(|cap| cap.get(index))
+                .and_then(|m| units::parse_number(m.as_str()))
+        };
+
+        let transfer = transfer_regex.captures(input);
+        let bytes = |index: usize| {
+            transfer
+                .as_ref()
+                .and_then(|cap| cap.get(index))
+                .and_then(|m| units::parse_bytes(m.as_str()))
+        };
+
+        let request = Request {
+            total: fields
+                .require("total requests", requests_regex.as_str(), number(1))
+                .round() as u64,
+            req_per_sec: fields.require("Req/Sec", requests_regex.as_str(), number(2)),
+        };
+        let transfer = Transfer {
+            total: fields.require("transfer total", transfer_regex.as_str(), bytes(1)),
+            rate: fields.require("transfer rate", transfer_regex.as_str(), bytes(2)) as f64,
+        };
+
+        fields.finish(Metrics {
+            // rewrk's percentile table holds the average of the slowest requests,
+            // not percentiles, so the distribution stays unreported
+            latency: Latency {
+                avg: duration(1),
+                std_env: duration(2),
+                min: duration(3),
+                max: duration(4),
+                p50: Duration::ZERO,
+                p75: Duration::ZERO,
+                p90: Duration::ZERO,
+                p99: Duration::ZERO,
+            },
+            request,
+            transfer,
+            threads: None,
+            // error counts of rewrk are not parsed yet
+            errors: Errors::default(),
+            timeline: Vec::new(),
+            status_codes: BTreeMap::new(),
+            phases: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrk() {
+        let given = r#"
+Beginning round 1...
+Benchmarking 256 connections @ http://127.0.0.1:3000 for 30 second(s)
+  Latencies:
+    Avg      Stdev    Min      Max
+    0.69ms   0.37ms   0.03ms   16.72ms
+  Requests:
+    Total: 11110818 Req/Sec: 370360.59
+  Transfer:
+    Total: 942.98 MB Transfer Rate: 31.43 MB/Sec
++ --------------- + --------------- +
+|   Percentile    |   Avg Latency   |
++ --------------- + --------------- +
+|      99.9%      |     5.62ms      |
+|       99%       |     2.39ms      |
++ --------------- + --------------- +
+        "#;
+        let actual = given.parse::<Metrics>().expect("parse metric fail");
+
+        assert_eq!(actual.latency.avg, Duration::from_micros(690));
+        assert_eq!(actual.latency.min, Duration::from_micros(30));
+        assert_eq!(actual.latency.max, Duration::from_micros(16_720));
+        assert_eq!(actual.p99(), 0.0);
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 11110818,
+                req_per_sec: 370360.59,
+            }
+        );
+        assert_eq!(
+            actual.transfer.total,
+            units::parse_bytes("942.98MB").unwrap()
+        );
+        assert_eq!(actual.threads, None);
+    }
+
+    #[test]
+    fn separators_and_suffixes() {
+        let given = r#"
+  Latencies:
+    Avg      Stdev    Min      Max
+    0.69ms   0.37ms   0.03ms   16.72ms
+  Requests:
+    Total: 11,110,818 Req/Sec: 370.36k
+  Transfer:
+    Total: 942.98 MB Transfer Rate: 31.43 MB/Sec
+        "#;
+        let actual = Rewrk.parse(given, true).expect("parse metric fail");
+
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 11110818,
+                req_per_sec: 370360.0,
+            }
+        );
+    }
+}
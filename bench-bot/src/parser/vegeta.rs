@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::{complete_json, status_codes, Fields, MetricsParser};
+use crate::report::{Errors, Latency, Metrics, MetricsError, Request, Transfer};
+
+/// Output of `vegeta report -type=json`, times are in nanoseconds.
+pub struct Vegeta;
+
+#[derive(Deserialize)]
+struct Output {
+    latencies: Latencies,
+    bytes_in: Option<Bytes>,
+    duration: Option<u64>,
+    requests: Option<u64>,
+    /// Successful requests per second.
+    throughput: Option<f64>,
+    /// Share of requests answered with 2xx or 3xx.
+    success: Option<f64>,
+    #[serde(default)]
+    status_codes: BTreeMap<String, u64>,
+}
+
+#[derive(Deserialize)]
+struct Latencies {
+    mean: u64,
+    #[serde(rename = "50th")]
+    p50: u64,
+    #[serde(rename = "90th")]
+    p90: u64,
+    #[serde(rename = "99th")]
+    p99: u64,
+    max: u64,
+    #[serde(default)]
+    min: u64,
+}
+
+#[derive(Deserialize)]
+struct Bytes {
+    total: u64,
+}
+
+impl MetricsParser for Vegeta {
+    fn name(&self) -> &'static str {
+        "vegeta"
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.trim_start().starts_with('{')
+            && input.contains("\"latencies\"")
+            && input.contains("\"bytes_in\"")
+    }
+
+    fn complete(&self, input: &str) -> bool {
+        complete_json(input)
+    }
+
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let output: Output =
+            serde_json::from_str(input.trim()).map_err(|_| MetricsError::ParseError)?;
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let requests = fields.require("requests", "requests", output.requests);
+        let throughput = fields.require("throughput", "throughput", output.throughput);
+        // only recorded as missing, without it no failure but the error
+        // statuses is known
+        fields.require("success ratio", "success", output.success);
+        let bytes_in = fields.require(
+            "bytes in",
+            "bytes_in.total",
+            output.bytes_in.map(|bytes| bytes.total),
+        );
+        let duration =
+            Duration::from_nanos(fields.require("duration", "duration", output.duration));
+
+        // failures that aren't error statuses are transport errors, which
+        // vegeta doesn't count by kind
+        let status = output
+            .status_codes
+            .iter()
+            .filter(|(code, _)| !matches!(code.parse::<u16>(), Ok(0) | Ok(200..=399)))
+            .map(|(_, count)| count)
+            .sum();
+        let failed = output.success.map_or(status, |success| {
+            (requests as f64 * (1.0 - success)).round() as u64
+        });
+        let errors = Errors {
+            read: failed.saturating_sub(status),
+            status,
+            ..Errors::default()
+        };
+
+        let latencies = &output.latencies;
+        fields.finish(Metrics {
+            // vegeta reports neither the deviation nor the 75th percentile
+            latency: Latency {
+                avg: Duration::from_nanos(latencies.mean),
+                std_env: Duration::ZERO,
+                min: Duration::from_nanos(latencies.min),
+                max: Duration::from_nanos(latencies.max),
+                p50: Duration::from_nanos(latencies.p50),
+                p75: Duration::ZERO,
+                p90: Duration::from_nanos(latencies.p90),
+                p99: Duration::from_nanos(latencies.p99),
+            },
+            request: Request {
+                total: requests,
+                req_per_sec: throughput,
+            },
+            transfer: Transfer {
+                total: bytes_in,
+                rate: if duration.is_zero() {
+                    0.0
+                } else {
+                    bytes_in as f64 / duration.as_secs_f64()
+                },
+            },
+            threads: None,
+            errors,
+            timeline: Vec::new(),
+            status_codes: status_codes(&output.status_codes),
+            phases: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vegeta() {
+        let given = r#"
+{
+  "latencies": {"total": 14067170000000, "mean": 814270, "50th": 707000, "90th": 1500000,
+      "95th": 1900000, "99th": 2560000, "max": 8420000, "min": 20000},
+  "bytes_in": {"total": 2093796557, "mean": 121.2},
+  "bytes_out": {"total": 0, "mean": 0},
+  "earliest": "2022-08-01T10:00:00.000000000Z",
+  "latest": "2022-08-01T10:00:30.000000000Z",
+  "end": "2022-08-01T10:00:30.000800000Z",
+  "duration": 30000000000,
+  "wait": 800000,
+  "requests": 17276000,
+  "rate": 575866.67,
+  "throughput": 574184.09,
+  "success": 0.999994,
+  "status_codes": {"0": 38, "200": 17275896, "500": 66},
+  "errors": ["500 Internal Server Error",
+      "Get \"http://127.0.0.1:3000\": dial tcp: connection refused"]
+}
+        "#;
+        let actual = Vegeta.parse(given, true).expect("parse metric fail");
+
+        assert_eq!(actual.latency.p99, Duration::from_micros(2_560));
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 17276000,
+                req_per_sec: 574184.09,
+            }
+        );
+        assert_eq!(
+            actual.transfer,
+            Transfer {
+                total: 2093796557,
+                rate: 2093796557.0 / 30.0,
+            }
+        );
+        assert_eq!(
+            actual.errors,
+            Errors {
+                read: 38,
+                status: 66,
+                ..Errors::default()
+            }
+        );
+        // code 0 of requests without a response is not a status
+        assert_eq!(
+            actual.status_codes,
+            BTreeMap::from([(200, 17275896), (500, 66)])
+        );
+
+        let without_success = given.replace(r#""success": 0.999994,"#, "");
+        let actual = Vegeta
+            .parse(&without_success, false)
+            .expect("parse metric fail");
+        assert_eq!(
+            actual.errors,
+            Errors {
+                status: 66,
+                ..Errors::default()
+            }
+        );
+        assert!(Vegeta.parse(&without_success, true).is_err());
+    }
+}
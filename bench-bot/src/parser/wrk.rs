@@ -0,0 +1,341 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use regex::Regex;
+
+use super::{Fields, MetricsParser, BYTES, DURATION, NUMBER};
+use crate::report::{Errors, Latency, Metrics, MetricsError, Request, ThreadStats, Transfer};
+use crate::timeline;
+use crate::units;
+
+/// Output of wrk, with the distribution when run with `--latency`.
+pub struct Wrk;
+
+impl MetricsParser for Wrk {
+    fn name(&self) -> &'static str {
+        "wrk"
+    }
+
+    fn detect(&self, input: &str) -> bool {
+        input.contains("Thread Stats") || input.contains("Requests/sec:")
+    }
+
+    fn complete(&self, input: &str) -> bool {
+        // the last line wrk prints
+        input.contains("Transfer/sec:")
+    }
+
+    fn parse(&self, input: &str, strict: bool) -> Result<Metrics, MetricsError> {
+        let thread_req_per_sec_regex =
+            Regex::new(&format!(r"Req/Sec\s+({0})\s+({0})", NUMBER)).unwrap();
+        let latency_regex =
+            Regex::new(&format!(r"Latency\s+({0})\s+({0})\s+({0})", DURATION)).unwrap();
+        let total_requests_regex = Regex::new(r"(\d[\d,.' ]*[kKmM]?) requests in").unwrap();
+        let total_data_read_regex = Regex::new(&format!(r", ({}) read", BYTES)).unwrap();
+        let req_per_sec_regex = Regex::new(&format!(r"Requests/sec:\s+({})", NUMBER)).unwrap();
+        let transfer_per_sec_regex = Regex::new(&format!(r"Transfer/sec:\s+({})", BYTES)).unwrap();
+
+        // without the latency line this is not wrk output at all
+        let latency_captures = latency_regex
+            .captures(input)
+            .ok_or(MetricsError::ParseError)?;
+
+        let mut fields = Fields::new(self.name(), strict);
+
+        let duration = |index: usize| {
+            latency_captures
+                .get(index)
+                .and_then(|m| units::parse_duration(m.as_str()))
+                .unwrap_or_default()
+        };
+
+        // numbers are parsed here so reports never see tool or locale specific spellings
+        let total_requests = total_requests_regex
+            .captures(input)
+            .and_then(|cap| cap.get(1))
+            .and_then(|m| units::parse_number(m.as_str()));
+        let req_per_sec = req_per_sec_regex
+            .captures(input)
+            .and_then(|cap| cap.get(1))
+            .and_then(|m| units::parse_number(m.as_str()));
+        let total_data_read = total_data_read_regex
+            .captures(input)
+            .and_then(|cap| cap.get(1))
+            .and_then(|m| units::parse_bytes(m.as_str()));
+        let transfer_per_sec = transfer_per_sec_regex
+            .captures(input)
+            .and_then(|cap| cap.get(1))
+            .and_then(|m| units::parse_bytes(m.as_str()));
+
+        let threads = thread_req_per_sec_regex.captures(input).and_then(|cap| {
+            Some(ThreadStats {
+                req_per_sec_avg: units::parse_number(cap.get(1)?.as_str())?,
+                req_per_sec_stdev: units::parse_number(cap.get(2)?.as_str())?,
+            })
+        });
+
+        let latency_distribution_regex = Regex::new(&format!(
+            r"Latency Distribution\s*50%\s*({0})\s*75%\s*({0})\s*90%\s*({0})\s*99%\s*({0})",
+            DURATION
+        ))
+        .unwrap();
+
+        let distribution = latency_distribution_regex.captures(input);
+        let percentile = |index: usize| {
+            distribution
+                .as_ref()
+                .and_then(|captures| captures.get(index))
+                .and_then(|m| units::parse_duration(m.as_str()))
+                .unwrap_or_default()
+        };
+
+        // Constructing structs from the local variables
+        let latency = Latency {
+            avg: duration(1),
+            std_env: duration(2),
+            // wrk prints no minimum
+            min: Duration::ZERO,
+            max: duration(3),
+            p50: percentile(1),
+            p75: percentile(2),
+            p90: percentile(3),
+            p99: percentile(4),
+        };
+
+        let request = Request {
+            total: fields
+                .require(
+                    "total requests",
+                    total_requests_regex.as_str(),
+                    total_requests,
+                )
+                .round() as u64,
+            req_per_sec: fields.require("Requests/sec", req_per_sec_regex.as_str(), req_per_sec),
+        };
+
+        let transfer = Transfer {
+            total: fields.require(
+                "total data read",
+                total_data_read_regex.as_str(),
+                total_data_read,
+            ),
+            rate: fields.require(
+                "Transfer/sec",
+                transfer_per_sec_regex.as_str(),
+                transfer_per_sec,
+            ) as f64,
+        };
+
+        fields.finish(Metrics {
+            latency,
+            request,
+            transfer,
+            threads,
+            errors: parse_errors(input),
+            timeline: timeline::parse(input),
+            status_codes: BTreeMap::new(),
+            phases: None,
+        })
+    }
+}
+
+/// Socket errors and non-2xx or 3xx responses, printed only when there are
+/// any.
+fn parse_errors(input: &str) -> Errors {
+    let socket_errors_regex =
+        Regex::new(r"Socket errors: connect (\d+), read (\d+), write (\d+), timeout (\d+)")
+            .unwrap();
+    let status_errors_regex = Regex::new(r"Non-2xx or 3xx responses: (\d+)").unwrap();
+
+    let socket = socket_errors_regex.captures(input);
+    let count = |index: usize| {
+        socket
+            .as_ref()
+            .and_then(|cap| cap.get(index))
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or_default()
+    };
+
+    Errors {
+        connect: count(1),
+        read: count(2),
+        write: count(3),
+        timeout: count(4),
+        status: status_errors_regex
+            .captures(input)
+            .and_then(|cap| cap.get(1))
+            .and_then(|m| m.as_str().parse().ok())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn tolerant_numbers() {
+        let given = r#"
+Running 30s test @ http://127.0.0.1:3000
+  16 threads and 500 connections
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+    Req/Sec    36.10k     2.64k   74.83k    75.41%
+  17,275,966 requests in 30.09s, 1.95GiB read
+Requests/sec: 574,184.09
+Transfer/sec:     66.26MiB
+        "#;
+        let actual = given.parse::<Metrics>().expect("parse metric fail");
+
+        assert_eq!(
+            actual.request,
+            Request {
+                total: 17275966,
+                req_per_sec: 574184.09,
+            }
+        );
+        assert_eq!(
+            actual.transfer,
+            Transfer {
+                total: 2093796557,
+                rate: 69478646.0,
+            }
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let given = r#"
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+    Req/Sec    36.10k     2.64k   74.83k    75.41%
+  17275966 requests in 30.09s, 1.95GB read
+  Socket errors: connect 0, read 123, write 0, timeout 5
+  Non-2xx or 3xx responses: 42
+Requests/sec: 574184.09
+        "#;
+        let actual = given.parse::<Metrics>().expect("parse metric fail");
+
+        assert_eq!(
+            actual.errors,
+            Errors {
+                connect: 0,
+                read: 123,
+                write: 0,
+                timeout: 5,
+                status: 42,
+            }
+        );
+        assert_eq!(actual.errors.total(), 170);
+    }
+
+    #[test]
+    fn strict() {
+        let given = r#"
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+  17275966 requests in 30.09s, 1.95GB read
+Requests/sec: 574184.09
+        "#;
+
+        assert_eq!(
+            given
+                .parse::<Metrics>()
+                .map(|metrics| metrics.transfer.rate),
+            Ok(0.0)
+        );
+        assert_eq!(
+            Wrk.parse(given, true),
+            Err(MetricsError::MissingField {
+                tool: "wrk",
+                field: "Transfer/sec",
+                pattern: r"Transfer/sec:\s+(\d[\d,.]*\s?[KMGT]?i?B)".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn units() {
+        let given = r#"
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency     1.20s   512.00ms    1.05m    90.00%
+  Latency Distribution
+     50%  900.00ns
+     75%  850.00us
+     90%    1.10s
+     99%    2.00s
+  1200 requests in 30.09s, 860.50KB read
+Requests/sec:     39.88
+Transfer/sec:     28.60KB
+        "#;
+        let actual = Wrk.parse(given, true).expect("parse metric fail");
+
+        assert_eq!(actual.latency.avg, Duration::from_millis(1_200));
+        assert_eq!(actual.latency.std_env, Duration::from_millis(512));
+        assert_eq!(actual.latency.max, Duration::from_secs(63));
+        assert_eq!(actual.latency.p50, Duration::from_nanos(900));
+        assert_eq!(actual.latency.p75, Duration::from_micros(850));
+        assert_eq!(
+            actual.transfer,
+            Transfer {
+                total: 881_152,
+                rate: 29_286.0,
+            }
+        );
+    }
+
+    #[test]
+    fn ok() {
+        let given = r#"
+Running 30s test @ http://127.0.0.1:3000
+  16 threads and 500 connections
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+    Req/Sec    36.10k     2.64k   74.83k    75.41%
+  Latency Distribution
+     50%  707.00us
+     75%    1.07ms
+     90%    1.50ms
+     99%    2.56ms
+  17275966 requests in 30.09s, 1.95GB read
+Requests/sec: 574184.09
+Transfer/sec:     66.26MB
+
+691 Errors: error shutting down connection: Socket is not connected (os error 57)
+        "#;
+        let actual = given.parse::<Metrics>();
+
+        let expect = Ok(Metrics {
+            latency: Latency {
+                avg: Duration::from_nanos(814_270),
+                std_env: Duration::from_nanos(498_470),
+                min: Duration::ZERO,
+                max: Duration::from_micros(8_420),
+                p50: Duration::from_micros(707),
+                p75: Duration::from_micros(1_070),
+                p90: Duration::from_micros(1_500),
+                p99: Duration::from_micros(2_560),
+            },
+            request: Request {
+                total: 17275966,
+                req_per_sec: 574184.09,
+            },
+            transfer: Transfer {
+                total: 2093796557,
+                rate: 69478646.0,
+            },
+            threads: Some(ThreadStats {
+                req_per_sec_avg: 36100.0,
+                req_per_sec_stdev: 2640.0,
+            }),
+            errors: Errors::default(),
+            timeline: Vec::new(),
+            status_codes: BTreeMap::new(),
+            phases: None,
+        });
+
+        assert_eq!(actual, expect);
+    }
+}
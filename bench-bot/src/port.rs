@@ -0,0 +1,185 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+
+use sysinfo::{Pid, PidExt, ProcessExt, System, SystemExt};
+
+/// Pid of the server being benchmarked, recorded until it is torn down. A
+/// file left behind points at a server bench-bot never stopped.
+#[derive(Debug)]
+pub struct ServerPid {
+    path: PathBuf,
+}
+
+impl ServerPid {
+    /// Record `pid` as the server of `framework` at `path`, removed when
+    /// dropped.
+    pub fn record(path: PathBuf, pid: u32, framework: &str) -> Result<Self, String> {
+        fs::write(&path, format!("{} {}", pid, framework))
+            .map_err(|e| format!("writing {:?}: {}", path, e))?;
+
+        Ok(ServerPid { path })
+    }
+}
+
+impl Drop for ServerPid {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Check that nothing serves the port of `url` before the server of
+/// `framework` is started on it: no server recorded at `pidfile` is still
+/// running and the port can be bound. Benchmarking a server left over from
+/// an earlier benchmark measures that one instead.
+pub fn ensure_free(url: &str, pidfile: &Path, framework: &str) -> Result<(), String> {
+    if let Some((pid, recorded)) = fs::read_to_string(pidfile)
+        .ok()
+        .as_deref()
+        .and_then(parse_pidfile)
+    {
+        if is_running(pid) {
+            return Err(format!(
+                "the server of {} (pid {}) of an earlier benchmark is still running, stop it first",
+                recorded, pid
+            ));
+        }
+
+        log::warn!("Removing stale pidfile {:?}.", pidfile);
+        let _ = fs::remove_file(pidfile);
+    }
+
+    let Some(addr) = socket_addr(url) else {
+        return Ok(());
+    };
+
+    // std sets SO_REUSEADDR, connections of the previous server lingering
+    // in TIME_WAIT don't keep the port busy, only a listening socket does
+    match TcpListener::bind(addr) {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::AddrInUse => {
+            let instances = instances(framework);
+            if instances.is_empty() {
+                Err(format!(
+                    "{} is already served by another process, it would be benchmarked \
+                    instead of {}",
+                    addr, framework
+                ))
+            } else {
+                Err(format!(
+                    "{} is already served, {} is still running as pid {}",
+                    addr,
+                    framework,
+                    instances.join(", ")
+                ))
+            }
+        }
+        // a remote target can't be probed
+        Err(e) => {
+            log::debug!("Not probing {}: {}.", addr, e);
+            Ok(())
+        }
+    }
+}
+
+/// Pid and framework of a pidfile written by [`ServerPid::record`].
+fn parse_pidfile(content: &str) -> Option<(u32, &str)> {
+    let (pid, framework) = content.trim().split_once(' ')?;
+
+    Some((pid.parse().ok()?, framework))
+}
+
+/// Address of `url`, e.g. `http://127.0.0.1:3000/json`.
+fn socket_addr(url: &str) -> Option<SocketAddr> {
+    let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = authority.split('/').next()?;
+    let authority = if authority.contains(':') {
+        authority.to_string()
+    } else {
+        format!("{}:80", authority)
+    };
+
+    authority.to_socket_addrs().ok()?.next()
+}
+
+/// Kill `pids` of [`crate::cpu::tree_pids`], `cargo run` and the server
+/// below it: killing cargo alone leaves the server running.
+pub fn kill_tree(pids: &[u32]) {
+    let mut sys = System::new();
+    sys.refresh_processes();
+
+    // children first, a parent killed first may restart them
+    for pid in pids.iter().rev() {
+        if let Some(process) = sys.process(Pid::from_u32(*pid)) {
+            process.kill();
+        }
+    }
+}
+
+fn is_running(pid: u32) -> bool {
+    let mut sys = System::new();
+
+    sys.refresh_process(Pid::from_u32(pid))
+}
+
+/// Pids of running processes of the binary `name`.
+fn instances(name: &str) -> Vec<String> {
+    let mut sys = System::new();
+    sys.refresh_processes();
+
+    sys.processes_by_exact_name(name)
+        .map(|process| process.pid().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    #[test]
+    fn served() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/json", listener.local_addr().unwrap());
+        let pidfile = env::temp_dir().join("bench-bot-test-served.pid");
+        let _ = fs::remove_file(&pidfile);
+
+        let actual = ensure_free(&url, &pidfile, "bench-bot-test-served");
+        assert_eq!(
+            actual,
+            Err(format!(
+                "{} is already served by another process, it would be \
+            benchmarked instead of bench-bot-test-served",
+                listener.local_addr().unwrap()
+            ))
+        );
+
+        drop(listener);
+        assert_eq!(ensure_free(&url, &pidfile, "bench-bot-test-served"), Ok(()));
+    }
+
+    #[test]
+    fn pidfile() {
+        let path = env::temp_dir().join("bench-bot-test-server.pid");
+        let url = "http://127.0.0.1:0";
+
+        let recorded = ServerPid::record(path.clone(), std::process::id(), "axum").unwrap();
+        assert_eq!(
+            ensure_free(url, &path, "axum"),
+            Err(format!(
+                "the server of axum (pid {}) of an earlier benchmark is still running, \
+                stop it first",
+                std::process::id()
+            ))
+        );
+
+        drop(recorded);
+        assert!(!path.exists());
+
+        fs::write(&path, format!("{} axum", u32::MAX)).unwrap();
+        assert_eq!(ensure_free(url, &path, "axum"), Ok(()));
+        assert!(!path.exists());
+    }
+}
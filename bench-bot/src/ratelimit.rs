@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+
+/// Requests a client may send at once.
+const BURST: f64 = 30.0;
+
+/// Requests a client may send per second in the long run, several times what
+/// a dashboard refreshing every few seconds needs.
+const PER_SECOND: f64 = 2.0;
+
+/// Clients tracked before those with a full bucket are forgotten again.
+const MAX_CLIENTS: usize = 1024;
+
+/// Token bucket of one client: every request takes a token, tokens refill
+/// at a steady rate up to the burst size.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(now: Instant) -> Self {
+        TokenBucket {
+            tokens: BURST,
+            refilled: now,
+        }
+    }
+
+    /// Take a token, or the time until the next one when there is none.
+    pub fn take(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / PER_SECOND))
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * PER_SECOND).min(BURST);
+        self.refilled = now;
+    }
+
+    fn is_full(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        self.tokens >= BURST
+    }
+}
+
+/// A token bucket per client address, so one client flooding the server
+/// doesn't lock out everyone else.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    clients: HashMap<IpAddr, TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Admit a request of `client`, or the time it has to wait.
+    pub fn admit(&mut self, client: IpAddr, now: Instant) -> Result<(), Duration> {
+        if self.clients.len() >= MAX_CLIENTS {
+            // a full bucket is what a new client starts with anyway
+            self.clients.retain(|_, bucket| !bucket.is_full(now));
+        }
+
+        self.clients
+            .entry(client)
+            .or_insert_with(|| TokenBucket::new(now))
+            .take(now)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn burst_then_steady() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(start);
+
+        for _ in 0..BURST as usize {
+            assert_eq!(bucket.take(start), Ok(()));
+        }
+        assert_eq!(bucket.take(start), Err(Duration::from_millis(500)));
+
+        assert_eq!(bucket.take(start + Duration::from_millis(500)), Ok(()));
+        assert!(bucket.take(start + Duration::from_millis(500)).is_err());
+    }
+
+    #[test]
+    fn per_client() {
+        let now = Instant::now();
+        let (flooding, other) = (
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)),
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)),
+        );
+        let mut limiter = RateLimiter::default();
+
+        while limiter.admit(flooding, now).is_ok() {}
+
+        assert!(limiter.admit(other, now).is_ok());
+    }
+}
@@ -0,0 +1,212 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::SystemTime;
+
+use crate::artifacts::{self, Link};
+use crate::status::STATUS_FILE;
+
+/// Attach the results files, pages and charts of `output_dir` written
+/// since `run_started` to the GitHub Release of `tag`, created when missing,
+/// and link every asset. Releases don't expire, so the links are fit for
+/// citing.
+pub fn publish(output_dir: &Path, tag: &str, run_started: SystemTime) -> Result<Vec<Link>, String> {
+    // charts of frameworks or profiles an earlier run measured stay around
+    let written = |name: &String| {
+        fs::metadata(output_dir.join(name))
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified >= run_started)
+    };
+    let mut names: Vec<String> = artifacts::walk(output_dir, output_dir)
+        .into_iter()
+        .filter(|name| published(name) && written(name))
+        .collect();
+    names.sort();
+
+    if gh(&["release", "view", tag, "--json", "url"]).is_err() {
+        gh(&[
+            "release",
+            "create",
+            tag,
+            "--title",
+            &format!("Benchmark results {}", tag),
+            "--notes",
+            "Generated by bench-bot.",
+        ])?;
+    }
+
+    // assets have no directories, detail pages and charts are renamed below
+    // a staging directory and pages link them by their new names
+    let staging = env::temp_dir().join(format!("bench-bot-release-{}", tag));
+    let _ = fs::remove_dir_all(&staging);
+    fs::create_dir_all(&staging).map_err(|e| format!("creating {:?}: {}", staging, e))?;
+    for name in &names {
+        let staged = staging.join(asset_name(name));
+        if name.ends_with(".md") || name.ends_with(".html") {
+            let content = fs::read_to_string(output_dir.join(name))
+                .map_err(|e| format!("staging {}: {}", name, e))?;
+            fs::write(&staged, link_assets(&content, name, &names))
+                .map_err(|e| format!("staging {}: {}", name, e))?;
+        } else {
+            fs::copy(output_dir.join(name), &staged)
+                .map_err(|e| format!("staging {}: {}", name, e))?;
+        }
+    }
+
+    let mut upload = vec![
+        "release".to_string(),
+        "upload".to_string(),
+        tag.to_string(),
+        "--clobber".to_string(),
+    ];
+    upload.extend(
+        names
+            .iter()
+            .map(|name| staging.join(asset_name(name)).to_string_lossy().to_string()),
+    );
+    let result = gh(&upload.iter().map(String::as_str).collect::<Vec<_>>());
+    let _ = fs::remove_dir_all(&staging);
+    result?;
+
+    let page: serde_json::Value =
+        serde_json::from_str(&gh(&["release", "view", tag, "--json", "url"])?)
+            .map_err(|e| format!("parsing release of {}: {}", tag, e))?;
+    let page = page["url"]
+        .as_str()
+        .ok_or_else(|| format!("release of {} has no url", tag))?;
+
+    Ok(names
+        .into_iter()
+        .map(|name| Link {
+            url: download_url(page, &asset_name(&name)),
+            name,
+        })
+        .collect())
+}
+
+// results files and pages of scenarios, detail pages and charts of their
+// frameworks
+fn published(name: &str) -> bool {
+    let top_level = !name.contains('/');
+
+    match name.rsplit_once('.') {
+        Some((_, "json")) => top_level && name != STATUS_FILE,
+        Some((_, "html")) => name.matches('/').count() <= 1,
+        Some((_, "md")) => name.matches('/').count() == 1,
+        Some((_, "svg")) => true,
+        _ => false,
+    }
+}
+
+/// `content` of the output file `name` linking the assets of `names` it
+/// links by their path relative to it, in markdown or HTML.
+fn link_assets(content: &str, name: &str, names: &[String]) -> String {
+    let dir = name
+        .rsplit_once('/')
+        .map(|(dir, _)| format!("{}/", dir))
+        .unwrap_or_default();
+
+    let mut content = content.to_string();
+    for linked in names {
+        let Some(relative) = linked.strip_prefix(&dir) else {
+            continue;
+        };
+        for (open, close) in [("](", ")"), ("href=\"", "\""), ("src=\"", "\"")] {
+            content = content.replace(
+                &format!("{}{}{}", open, relative, close),
+                &format!("{}{}{}", open, asset_name(linked), close),
+            );
+        }
+    }
+
+    content
+}
+
+/// Name of the asset of the output file `name`, e.g. `hello-world.axum.svg`
+/// for `hello-world/axum.svg`.
+pub fn asset_name(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+/// Permalink of `asset` of the release page `page`.
+pub fn download_url(page: &str, asset: &str) -> String {
+    format!(
+        "{}/{}",
+        page.replacen("/releases/tag/", "/releases/download/", 1),
+        asset
+    )
+}
+
+fn gh(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("gh")
+        .args(args)
+        .output()
+        .map_err(|e| format!("running gh: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(format!(
+            "gh {}: {}",
+            args[..2].join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn assets() {
+        assert_eq!(
+            asset_name("hello-world/axum.timeline.svg"),
+            "hello-world.axum.timeline.svg"
+        );
+        assert_eq!(
+            download_url(
+                "https://github.com/hyper-fast/rust-web-benchmarks/releases/tag/bench-2022-08-01",
+                "hello-world.json"
+            ),
+            "https://github.com/hyper-fast/rust-web-benchmarks/releases/download/bench-2022-08-01/\
+             hello-world.json"
+        );
+        assert!(published("hello-world.html"));
+        assert!(published("hello-world/axum.svg"));
+        assert!(published("hello-world/axum.md"));
+        assert!(published("hello-world/axum.html"));
+        assert!(!published("hello-world.md"));
+        assert!(!published("status.json"));
+        assert!(!published("repro/1700000000/repro.json"));
+    }
+
+    #[test]
+    fn linked_assets() {
+        let names = [
+            "hello-world.html",
+            "hello-world/axum.md",
+            "hello-world/axum.svg",
+            "hello-world/my-axum.svg",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            link_assets(
+                "<a href=\"hello-world/axum.md\">axum</a>",
+                "hello-world.html",
+                &names
+            ),
+            "<a href=\"hello-world.axum.md\">axum</a>"
+        );
+        assert_eq!(
+            link_assets(
+                "![Flamegraph](axum.svg) ![Flamegraph](my-axum.svg)",
+                "hello-world/axum.md",
+                &names
+            ),
+            "![Flamegraph](hello-world.axum.svg) ![Flamegraph](hello-world.my-axum.svg)"
+        );
+    }
+}
@@ -1,204 +1,1365 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::str::FromStr;
+use std::time::Duration;
 
-use regex::Regex;
+use clap::ArgEnum;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::analysis;
+use crate::capacity::Capacity;
+use crate::column::{Column, ColumnPreset};
+use crate::environment::Environment;
+use crate::format::NumberFormat;
+use crate::manifest::{Category, Variant};
+use crate::parser;
+use crate::slo;
+use crate::stats;
+use crate::units;
 
 #[derive(PartialEq, Debug)]
 pub enum MetricsError {
-    ParseError
+    /// Output of none of the supported tools.
+    ParseError,
+    /// A field the tool always prints was not found, only reported when
+    /// parsing strictly.
+    MissingField {
+        tool: &'static str,
+        field: &'static str,
+        pattern: String,
+    },
+    /// Output cut off before anything could be recovered from it.
+    Truncated { tool: &'static str },
+}
+
+impl fmt::Display for MetricsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MetricsError::ParseError => write!(f, "not the output of a supported load tool"),
+            MetricsError::MissingField {
+                tool,
+                field,
+                pattern,
+            } => {
+                write!(
+                    f,
+                    "{} output has no {}, nothing matched `{}`",
+                    tool, field, pattern
+                )
+            }
+            MetricsError::Truncated { tool } => {
+                write!(f, "{} output was cut off before any metrics", tool)
+            }
+        }
+    }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Report {
     framework_name: String,
-    max_memory: String,
+    max_memory: f64,
     metrics: Metrics,
+    /// Metrics of every round when the benchmark was repeated.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    rounds: Vec<Metrics>,
+    /// CPU seconds the server spent during all rounds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cpu_time: Option<f64>,
+    /// Joules the CPU packages consumed during all rounds, as read from RAPL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    energy: Option<f64>,
+    /// Connections served before requests failed, when probed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    capacity: Option<Capacity>,
+    /// Values of the metric collectors configured in `bench.toml`, by name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    collected: BTreeMap<String, f64>,
+    /// Output of the load tool was cut off in some round, metrics are those
+    /// printed before.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    incomplete: bool,
+    /// Throughput fell below the floor of the scenario and measuring was
+    /// aborted, metrics are those measured until then.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pathological: bool,
+    /// Version of the framework crate, see [`crate::manifest::resolved_version`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    /// Scenario the report measured, e.g. `hello-world`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scenario: Option<String>,
+}
+
+/// How the comparison of a scenario is printed to stdout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum OutputFormat {
+    /// The comparison table, as in the markdown output.
+    Table,
+    /// A line of [`ScenarioReports`] per scenario, see [`Report::to_json`].
+    Json,
+}
+
+/// Order of the rows of comparison tables.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum SortKey {
+    /// Fastest first.
+    ReqPerSec,
+    /// Lowest 99th percentile latency first.
+    Latency,
+    /// Lowest maximum memory usage first.
+    Memory,
+    /// Alphabetically by framework name.
+    Name,
+    /// In the order the frameworks were measured.
+    Measured,
+}
+
+/// Reports of one scenario as printed with `--format json`.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct ScenarioReports {
+    pub scenario: String,
+    /// What the reports were measured on, so a line stands on its own.
+    pub environment: Environment,
+    /// Load generator invocation the reports were measured with.
+    pub command: String,
+    pub reports: Vec<FlatReport>,
+}
+
+/// A report with every metric a plain number in a fixed unit, for
+/// dashboards and scripts. Metrics the load tool didn't report are `null`.
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct FlatReport {
+    pub framework_name: String,
+    pub version: Option<String>,
+    pub incomplete: bool,
+    pub pathological: bool,
+    pub req_per_sec: f64,
+    /// Coefficient of variation of Req/Sec across rounds.
+    pub req_per_sec_cv: Option<f64>,
+    pub requests_total: u64,
+    pub errors_total: u64,
+    /// Most connections probed without a failed request.
+    pub max_error_free_connections: Option<u32>,
+    pub status_codes: BTreeMap<u16, u64>,
+    pub latency_avg_ms: f64,
+    pub latency_stdev_ms: f64,
+    pub latency_min_ms: Option<f64>,
+    pub latency_p50_ms: Option<f64>,
+    pub latency_p75_ms: Option<f64>,
+    pub latency_p90_ms: Option<f64>,
+    pub latency_p99_ms: Option<f64>,
+    pub latency_max_ms: f64,
+    pub transfer_total_bytes: u64,
+    pub transfer_bytes_per_sec: f64,
+    pub max_memory_mb: f64,
+    pub cpu_us_per_request: Option<f64>,
+    pub joules_per_million_requests: Option<f64>,
+    pub collected: BTreeMap<String, f64>,
+}
+
+/// A row of the variants table, see [`Report::variants`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariantRow {
+    pub framework_name: String,
+    /// What the variant changes, `None` for the implementation it varies.
+    pub axis: Option<String>,
+    /// Req/Sec, p99 and memory, each followed by its change.
+    pub cells: Vec<String>,
+}
+
+/// Options controlling how reports are rendered.
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    pub columns: Vec<Column>,
+    pub format: NumberFormat,
+    /// Prefix the three fastest frameworks with medals. Incomplete reports
+    /// are marked with ⚠️, pathological ones with 🐌, neither gets medals.
+    pub medals: bool,
+    /// Link framework names to `<dir>/<framework>.md` detail pages.
+    pub detail_links: Option<String>,
+    /// Frameworks regressed beyond their thresholds, marked with 🔻.
+    pub regressions: Vec<String>,
+    /// Latency objectives, a column each after the built-in ones.
+    pub slo: Vec<Duration>,
+    /// Target of an Apdex column after the objectives.
+    pub apdex: Option<Duration>,
+    /// Order of the rows.
+    pub sort: SortKey,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            columns: ColumnPreset::Full.columns().to_vec(),
+            format: NumberFormat::default(),
+            medals: false,
+            detail_links: None,
+            regressions: Vec::new(),
+            slo: Vec::new(),
+            apdex: None,
+            sort: SortKey::ReqPerSec,
+        }
+    }
+}
+
+/// Builder of the columns of comparison tables, starting from the full
+/// preset, e.g. `ReportOptions::new().with_latency_percentiles().without_transfer()`.
+/// Added columns take their place of [`Column`]'s order relative to the others.
+impl ReportOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_columns(mut self, preset: ColumnPreset) -> Self {
+        self.columns = preset.columns().to_vec();
+        self
+    }
+
+    pub fn with_column(mut self, column: Column) -> Self {
+        if self.columns.contains(&column) {
+            return self;
+        }
+
+        let order = |c: &Column| {
+            Column::value_variants()
+                .iter()
+                .position(|variant| variant == c)
+        };
+        let index = self
+            .columns
+            .iter()
+            .position(|c| order(c) > order(&column))
+            .unwrap_or(self.columns.len());
+        self.columns.insert(index, column);
+        self
+    }
+
+    pub fn without_column(mut self, column: Column) -> Self {
+        self.columns.retain(|c| *c != column);
+        self
+    }
+
+    /// The 50th, 75th, 90th and 99th percentile of latency.
+    pub fn with_latency_percentiles(self) -> Self {
+        LATENCY_PERCENTILES
+            .into_iter()
+            .fold(self, Self::with_column)
+    }
+
+    /// Neither bytes transferred nor the transfer rate.
+    pub fn without_transfer(self) -> Self {
+        TRANSFER.into_iter().fold(self, Self::without_column)
+    }
+}
+
+const LATENCY_PERCENTILES: [Column; 4] = [
+    Column::LatencyP50,
+    Column::LatencyP75,
+    Column::LatencyP90,
+    Column::LatencyP99,
+];
+
+const TRANSFER: [Column; 2] = [Column::TransferTotal, Column::TransferRate];
+
+const MEDALS: [&str; 3] = ["🥇", "🥈", "🥉"];
+
+/// Change of `value` against `base` in percent, `-` when a metric is missing
+/// on either side.
+fn change(value: f64, base: f64) -> String {
+    match value > 0.0 && base > 0.0 {
+        true => format!("{:+.1}%", (value / base - 1.0) * 100.0),
+        false => "-".to_string(),
+    }
+}
+
+/// Header of [`Report::to_csv`] before the collected metrics.
+const CSV_COLUMNS: [&str; 25] = [
+    "framework_name",
+    "version",
+    "incomplete",
+    "pathological",
+    "req_per_sec",
+    "req_per_sec_cv",
+    "requests_total",
+    "errors_total",
+    "max_error_free_connections",
+    "status_2xx",
+    "status_4xx",
+    "status_5xx",
+    "latency_avg_ms",
+    "latency_stdev_ms",
+    "latency_min_ms",
+    "latency_p50_ms",
+    "latency_p75_ms",
+    "latency_p90_ms",
+    "latency_p99_ms",
+    "latency_max_ms",
+    "transfer_total_bytes",
+    "transfer_bytes_per_sec",
+    "max_memory_mb",
+    "cpu_us_per_request",
+    "joules_per_million_requests",
+];
+
+/// A CSV line of `fields`, quoted where they hold commas, quotes or line
+/// breaks.
+fn csv_row(fields: &[String]) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|field| match field.contains([',', '"', '\n', '\r']) {
+            true => format!("\"{}\"", field.replace('"', "\"\"")),
+            false => field.clone(),
+        })
+        .collect();
+
+    format!("{}\n", quoted.join(","))
 }
 
-const REPORT_HEADER: &str = "| Framework Name | Latency.Avg | Latency.Stdev | Latency.50P | Latency.75P | Latency.90P | Latency.99P | Latency.Max | Request.Total | Request.Req/Sec | Transfer.Total | Transfer.Rate | Max. Memory Usage |";
-const TABLE_SEPARATOR: &str = "\n|---|---|---|---|---|---|---|---|---|---|---|---|---|\n";
+/// Thread balance (stdev/avg of per-thread Req/Sec) above which the load is
+/// considered badly skewed across workers.
+pub const SKEWED_THREAD_BALANCE: f64 = 0.25;
 
 impl Report {
-    pub fn new(framework_name: &str,
-               max_memory: f64,
-               metrics: Metrics) -> Self {
+    pub fn new(framework_name: &str, max_memory: f64, metrics: Metrics) -> Self {
         Self {
             framework_name: framework_name.to_string(),
             metrics,
-            max_memory: format!("{:.1}MB", max_memory),
+            max_memory,
+            rounds: Vec::new(),
+            cpu_time: None,
+            energy: None,
+            capacity: None,
+            collected: BTreeMap::new(),
+            incomplete: false,
+            pathological: false,
+            version: None,
+            scenario: None,
         }
     }
 
-    pub fn generate_from(reports: &Vec<Report>) -> String {
-        let mut res = String::new();
+    /// The report with the version of the framework it measured.
+    pub fn with_version(mut self, version: String) -> Self {
+        self.version = Some(version);
+        self
+    }
 
-        res.push_str(REPORT_HEADER);
-        res.push_str(TABLE_SEPARATOR);
+    /// The report of a run of `scenario`.
+    pub fn with_scenario(mut self, scenario: &str) -> Self {
+        self.scenario = Some(scenario.to_string());
+        self
+    }
 
-        for r in reports {
-            let formatted_p50 = if r.metrics.latency.p50 > 0.0 {
-                format!("{:.4}ms", r.metrics.latency.p50)
-            } else {
-                "-".to_string()
-            };
+    /// Scenario the report measured, `None` for reports of results files
+    /// written before reports knew it.
+    pub fn scenario(&self) -> Option<&str> {
+        self.scenario.as_deref()
+    }
 
-            let formatted_p75 = if r.metrics.latency.p75 > 0.0 {
-                format!("{:.4}ms", r.metrics.latency.p75)
-            } else {
-                "-".to_string()
+    /// Report it under `name` instead, see [`crate::history::stitch`].
+    pub fn rename(&mut self, name: &str) {
+        self.framework_name = name.to_string();
+    }
+
+    /// The report marked as recovered from output cut off early.
+    pub fn with_incomplete(mut self) -> Self {
+        self.incomplete = true;
+        self
+    }
+
+    /// Whether the metrics are recovered from output cut off early.
+    pub fn is_incomplete(&self) -> bool {
+        self.incomplete
+    }
+
+    /// The report marked as aborted for throughput below the floor.
+    pub fn with_pathological(mut self) -> Self {
+        self.pathological = true;
+        self
+    }
+
+    pub fn is_pathological(&self) -> bool {
+        self.pathological
+    }
+
+    /// Neither incomplete nor pathological, the metrics of a full
+    /// measurement that rankings, scores, baselines and trends build on.
+    pub fn is_reliable(&self) -> bool {
+        !self.incomplete && !self.pathological
+    }
+
+    /// The report with the values of metric collectors.
+    pub fn with_collected(mut self, collected: BTreeMap<String, f64>) -> Self {
+        self.collected = collected;
+        self
+    }
+
+    /// The report with the CPU seconds the server spent serving it.
+    pub fn with_cpu_time(mut self, cpu_time: f64) -> Self {
+        self.cpu_time = Some(cpu_time);
+        self
+    }
+
+    /// The report with the joules consumed while serving it.
+    pub fn with_energy(mut self, energy: f64) -> Self {
+        self.energy = Some(energy);
+        self
+    }
+
+    /// The report with the connections probed before requests failed.
+    pub fn with_capacity(mut self, capacity: Capacity) -> Self {
+        self.capacity = Some(capacity);
+        self
+    }
+
+    /// CPU seconds the server spent during all rounds.
+    pub fn cpu_time(&self) -> Option<f64> {
+        self.cpu_time
+    }
+
+    /// Joules consumed during all rounds.
+    pub fn energy(&self) -> Option<f64> {
+        self.energy
+    }
+
+    /// Requests completed over every round.
+    pub fn requests_of_all_rounds(&self) -> f64 {
+        if self.rounds.is_empty() {
+            self.metrics.total_requests()
+        } else {
+            self.rounds.iter().map(Metrics::total_requests).sum()
+        }
+    }
+
+    /// Report of a repeated benchmark, headline metrics are taken from the
+    /// round with the median Req/Sec.
+    pub fn from_rounds(
+        framework_name: &str,
+        max_memory: f64,
+        mut rounds: Vec<Metrics>,
+    ) -> Option<Self> {
+        rounds.sort_by(|a, b| a.req_per_sec().total_cmp(&b.req_per_sec()));
+
+        let metrics = rounds.get(rounds.len() / 2)?.clone();
+        let mut report = Self::new(framework_name, max_memory, metrics);
+
+        if rounds.len() > 1 {
+            report.rounds = rounds;
+        }
+
+        Some(report)
+    }
+
+    /// Coefficient of variation of Req/Sec across rounds.
+    fn rounds_cv(&self) -> Option<f64> {
+        let samples: Vec<f64> = self.rounds.iter().map(Metrics::req_per_sec).collect();
+
+        stats::coefficient_of_variation(&samples)
+    }
+
+    /// Mean and bootstrap 95% confidence interval of `metric` across rounds.
+    fn rounds_ci(&self, metric: impl Fn(&Metrics) -> f64) -> Option<(f64, f64)> {
+        let samples: Vec<f64> = self
+            .rounds
+            .iter()
+            .map(metric)
+            .filter(|v| *v > 0.0)
+            .collect();
+        let (lower, upper) = stats::bootstrap_ci(&samples)?;
+
+        Some((stats::mean(&samples), (upper - lower) / 2.0))
+    }
+
+    /// The comparison table of `reports`. Reports of several scenarios get
+    /// a summary of their Req/Sec in each followed by a table per scenario
+    /// below a heading each, medals are awarded within each scenario.
+    pub fn generate_from(reports: &[Report], options: &ReportOptions) -> String {
+        let mut groups: BTreeMap<Option<&str>, Vec<Report>> = BTreeMap::new();
+        for report in reports {
+            groups
+                .entry(report.scenario())
+                .or_default()
+                .push(report.clone());
+        }
+
+        if groups.len() < 2 {
+            return Self::generate_table(reports, options);
+        }
+
+        let mut res = format!(
+            "### Summary\n\n{}",
+            Self::generate_summary(reports, &options.format)
+        );
+        for (scenario, reports) in &groups {
+            res.push_str(&format!(
+                "\n\n### {}\n\n{}",
+                scenario.unwrap_or("Other"),
+                Self::generate_table(reports, options)
+            ));
+        }
+
+        res
+    }
+
+    /// Req/Sec of every framework of `reports` in each of their scenarios,
+    /// `-` where a framework wasn't measured.
+    pub fn generate_summary(reports: &[Report], format: &NumberFormat) -> String {
+        let scenarios: BTreeSet<&str> = reports.iter().filter_map(Report::scenario).collect();
+        let frameworks: BTreeSet<&str> = reports.iter().map(Report::framework_name).collect();
+
+        let mut res = String::from("| Framework Name |");
+        for scenario in &scenarios {
+            res.push_str(&format!(" {} |", scenario));
+        }
+        res.push_str("\n|");
+        res.push_str(&"---|".repeat(scenarios.len() + 1));
+
+        for framework in frameworks {
+            res.push_str(&format!("\n|{}|", framework));
+            for scenario in &scenarios {
+                let report = reports
+                    .iter()
+                    .find(|r| r.framework_name == framework && r.scenario() == Some(*scenario));
+                res.push_str(
+                    &report.map_or("-".to_string(), |r| format.rate(r.metrics.req_per_sec())),
+                );
+                res.push('|');
+            }
+        }
+
+        res
+    }
+
+    fn generate_table(reports: &[Report], options: &ReportOptions) -> String {
+        let (header, rows) = Self::table(reports, options);
+        let mut res = String::from("|");
+
+        for name in &header {
+            res.push_str(&format!(" {} |", name));
+        }
+        res.push_str("\n|");
+        res.push_str(&"---|".repeat(header.len()));
+
+        for row in rows {
+            res.push_str("\n|");
+            for cell in row {
+                res.push_str(&cell);
+                res.push('|');
+            }
+        }
+
+        res
+    }
+
+    /// Header and rendered rows of the comparison table of `reports`, for
+    /// rendering it as markdown or otherwise.
+    pub fn table(reports: &[Report], options: &ReportOptions) -> (Vec<String>, Vec<Vec<String>>) {
+        // collected metrics follow the built-in columns, by name
+        let collected: BTreeSet<&str> = reports
+            .iter()
+            .flat_map(|r| r.collected.keys().map(String::as_str))
+            .collect();
+
+        let mut header: Vec<String> = options
+            .columns
+            .iter()
+            .map(|column| column.header().to_string())
+            .collect();
+        header.extend(options.slo.iter().map(|threshold| slo::header(*threshold)));
+        header.extend(options.apdex.map(slo::apdex_header));
+        header.extend(collected.iter().map(|name| name.to_string()));
+
+        let rows = Self::sorted(reports, options.sort)
+            .into_iter()
+            .map(|r| {
+                let mut row: Vec<String> = options
+                    .columns
+                    .iter()
+                    .map(|column| r.render_cell(reports, *column, options))
+                    .collect();
+                for threshold in &options.slo {
+                    row.push(
+                        slo::attainment(&r.metrics, *threshold)
+                            .map_or("-".to_string(), |percent| {
+                                format!("{}%", options.format.ratio(percent))
+                            }),
+                    );
+                }
+                if let Some(t) = options.apdex {
+                    row.push(
+                        slo::apdex(&r.metrics, t)
+                            .map_or("-".to_string(), |score| options.format.ratio(score)),
+                    );
+                }
+                for name in &collected {
+                    row.push(
+                        r.collected
+                            .get(*name)
+                            .map_or("-".to_string(), |value| options.format.ratio(*value)),
+                    );
+                }
+                row
+            })
+            .collect();
+
+        (header, rows)
+    }
+
+    /// `reports` in the order of `key`, ties keep the measured order.
+    pub fn sorted(reports: &[Report], key: SortKey) -> Vec<&Report> {
+        let mut sorted: Vec<&Report> = reports.iter().collect();
+
+        match key {
+            SortKey::ReqPerSec => {
+                sorted.sort_by(|a, b| b.metrics.req_per_sec().total_cmp(&a.metrics.req_per_sec()))
+            }
+            // tools reporting no distribution go last
+            SortKey::Latency => {
+                sorted.sort_by_key(|r| (r.metrics.latency.p99.is_zero(), r.metrics.latency.p99))
+            }
+            SortKey::Memory => sorted.sort_by(|a, b| a.max_memory.total_cmp(&b.max_memory)),
+            SortKey::Name => sorted.sort_by(|a, b| a.framework_name.cmp(&b.framework_name)),
+            SortKey::Measured => {}
+        }
+
+        sorted
+    }
+
+    /// One table per category of `reports` below a heading each, a single
+    /// table when they are all of one category. Medals are awarded within
+    /// each category.
+    pub fn generate_by_category(
+        reports: &[Report],
+        category_of: impl Fn(&str) -> Category,
+        options: &ReportOptions,
+    ) -> String {
+        let mut groups: BTreeMap<Category, Vec<Report>> = BTreeMap::new();
+        for report in reports {
+            groups
+                .entry(category_of(&report.framework_name))
+                .or_default()
+                .push(report.clone());
+        }
+
+        if groups.len() < 2 {
+            return Self::generate_from(reports, options);
+        }
+
+        groups
+            .iter()
+            .map(|(category, reports)| {
+                format!(
+                    "### {}\n\n{}",
+                    category.title(),
+                    Self::generate_from(reports, options)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// `reports` of `scenario`, measured in `environment` with `command`, as
+    /// a single line of JSON, so a run prints JSON Lines.
+    pub fn to_json(
+        scenario: &str,
+        environment: &Environment,
+        command: &str,
+        reports: &[Report],
+    ) -> String {
+        let line = ScenarioReports {
+            scenario: scenario.to_string(),
+            environment: environment.clone(),
+            command: command.to_string(),
+            reports: reports.iter().map(Report::flatten).collect(),
+        };
+
+        serde_json::to_string(&line).expect("reports always serialize")
+    }
+
+    /// `reports` as CSV with a header row, one row per report. Metrics are
+    /// plain numbers as in [`FlatReport`], empty where unreported, collected
+    /// metrics follow by name.
+    pub fn to_csv(reports: &[Report]) -> String {
+        let collected: BTreeSet<&str> = reports
+            .iter()
+            .flat_map(|r| r.collected.keys().map(String::as_str))
+            .collect();
+        let mut header = CSV_COLUMNS.map(str::to_string).to_vec();
+        header.extend(collected.iter().map(|name| name.to_string()));
+
+        let mut res = csv_row(&header);
+        for report in reports {
+            let flat = report.flatten();
+            let number = |value: f64| value.to_string();
+            let optional = |value: Option<f64>| value.map_or(String::new(), number);
+            let class = |class: u16| match flat.status_codes.is_empty() {
+                true => String::new(),
+                false => flat
+                    .status_codes
+                    .range(class * 100..(class + 1) * 100)
+                    .map(|(_, count)| count)
+                    .sum::<u64>()
+                    .to_string(),
             };
 
-            let formatted_p90 = if r.metrics.latency.p90 > 0.0 {
-                format!("{:.4}ms", r.metrics.latency.p90)
-            } else {
-                "-".to_string()
+            let mut row = vec![
+                flat.framework_name.clone(),
+                flat.version.clone().unwrap_or_default(),
+                flat.incomplete.to_string(),
+                flat.pathological.to_string(),
+                number(flat.req_per_sec),
+                optional(flat.req_per_sec_cv),
+                flat.requests_total.to_string(),
+                flat.errors_total.to_string(),
+                flat.max_error_free_connections
+                    .map_or(String::new(), |connections| connections.to_string()),
+                class(2),
+                class(4),
+                class(5),
+                number(flat.latency_avg_ms),
+                number(flat.latency_stdev_ms),
+                optional(flat.latency_min_ms),
+                optional(flat.latency_p50_ms),
+                optional(flat.latency_p75_ms),
+                optional(flat.latency_p90_ms),
+                optional(flat.latency_p99_ms),
+                number(flat.latency_max_ms),
+                flat.transfer_total_bytes.to_string(),
+                number(flat.transfer_bytes_per_sec),
+                number(flat.max_memory_mb),
+                optional(flat.cpu_us_per_request),
+                optional(flat.joules_per_million_requests),
+            ];
+            row.extend(
+                collected
+                    .iter()
+                    .map(|name| optional(flat.collected.get(*name).copied())),
+            );
+            res.push_str(&csv_row(&row));
+        }
+
+        res
+    }
+
+    pub fn flatten(&self) -> FlatReport {
+        let latency = &self.metrics.latency;
+        let ms = |duration: Duration| duration.as_nanos() as f64 / 1e6;
+        // zero is what parsers leave for metrics the tool doesn't print
+        let reported = |duration: Duration| (!duration.is_zero()).then(|| ms(duration));
+
+        FlatReport {
+            framework_name: self.framework_name.clone(),
+            version: self.version.clone(),
+            incomplete: self.incomplete,
+            pathological: self.pathological,
+            req_per_sec: self.metrics.req_per_sec(),
+            req_per_sec_cv: self.rounds_cv(),
+            requests_total: self.metrics.request.total,
+            errors_total: self.metrics.errors.total(),
+            max_error_free_connections: self.capacity.and_then(|capacity| capacity.max_error_free),
+            status_codes: self.metrics.status_codes.clone(),
+            latency_avg_ms: ms(latency.avg),
+            latency_stdev_ms: ms(latency.std_env),
+            latency_min_ms: reported(latency.min),
+            latency_p50_ms: reported(latency.p50),
+            latency_p75_ms: reported(latency.p75),
+            latency_p90_ms: reported(latency.p90),
+            latency_p99_ms: reported(latency.p99),
+            latency_max_ms: ms(latency.max),
+            transfer_total_bytes: self.metrics.transfer.total,
+            transfer_bytes_per_sec: self.metrics.transfer.rate,
+            max_memory_mb: self.max_memory,
+            cpu_us_per_request: analysis::cpu_per_request(self),
+            joules_per_million_requests: analysis::energy_per_million_requests(self),
+            collected: self.collected.clone(),
+        }
+    }
+
+    /// Variants of an implementation as sub-rows below it, with their change
+    /// of Req/Sec, p99 and memory against it. `None` when no implementation
+    /// of `reports` has a variant among them.
+    pub fn generate_variants(
+        reports: &[Report],
+        variant_of: impl Fn(&str) -> Option<Variant>,
+        format: &NumberFormat,
+    ) -> Option<String> {
+        let rows = Self::variants(reports, variant_of, format);
+        if rows.is_empty() {
+            return None;
+        }
+
+        let header = Self::variants_header();
+        let mut res = format!(
+            "| {} |\n|{}",
+            header.join(" | "),
+            "---|".repeat(header.len())
+        );
+        for row in rows {
+            match &row.axis {
+                None => res.push_str(&format!("\n|**{}**|-|", row.framework_name)),
+                Some(axis) => res.push_str(&format!("\n|↳ {}|{}|", row.framework_name, axis)),
+            }
+            for cell in &row.cells {
+                res.push_str(cell);
+                res.push('|');
+            }
+        }
+
+        Some(res)
+    }
+
+    /// Header of [`Report::variants`], the framework name and axis before
+    /// the cells.
+    pub fn variants_header() -> [&'static str; 8] {
+        [
+            Column::FrameworkName.header(),
+            "Axis",
+            Column::RequestReqPerSec.header(),
+            "Change",
+            Column::LatencyP99.header(),
+            "Change",
+            Column::MaxMemory.header(),
+            "Change",
+        ]
+    }
+
+    /// Rows of the variants table of `reports`, every implementation with a
+    /// variant among them followed by its variants, for rendering it as
+    /// markdown or otherwise.
+    pub fn variants(
+        reports: &[Report],
+        variant_of: impl Fn(&str) -> Option<Variant>,
+        format: &NumberFormat,
+    ) -> Vec<VariantRow> {
+        let find = |name: &str| reports.iter().find(|r| r.framework_name == name);
+        let mut groups: BTreeMap<String, Vec<(&Report, String)>> = BTreeMap::new();
+        for report in reports {
+            // variants of implementations that failed have nothing to compare with
+            if let Some(variant) =
+                variant_of(&report.framework_name).filter(|variant| find(&variant.of).is_some())
+            {
+                groups
+                    .entry(variant.of)
+                    .or_default()
+                    .push((report, variant.axis));
+            }
+        }
+
+        let mut rows = Vec::new();
+        for (name, variants) in &groups {
+            let Some(base) = find(name) else {
+                continue;
             };
+            rows.push(VariantRow {
+                framework_name: base.framework_name.clone(),
+                axis: None,
+                cells: vec![
+                    base.cell(Column::RequestReqPerSec, format),
+                    "-".to_string(),
+                    base.cell(Column::LatencyP99, format),
+                    "-".to_string(),
+                    base.cell(Column::MaxMemory, format),
+                    "-".to_string(),
+                ],
+            });
+            for (variant, axis) in variants {
+                rows.push(VariantRow {
+                    framework_name: variant.framework_name.clone(),
+                    axis: Some(axis.clone()),
+                    cells: vec![
+                        variant.cell(Column::RequestReqPerSec, format),
+                        change(variant.metrics.req_per_sec(), base.metrics.req_per_sec()),
+                        variant.cell(Column::LatencyP99, format),
+                        change(variant.metrics.p99(), base.metrics.p99()),
+                        variant.cell(Column::MaxMemory, format),
+                        change(variant.max_memory, base.max_memory),
+                    ],
+                });
+            }
+        }
 
-            let formatted_p99 = if r.metrics.latency.p99 > 0.0 {
-                format!("{:.4}ms", r.metrics.latency.p99)
-            } else {
-                "-".to_string()
+        rows
+    }
+
+    /// Table of `current` with the change of Req/Sec, 99th percentile
+    /// latency and memory against `baseline` per framework. Frameworks
+    /// missing in `current` are listed last, without values.
+    pub fn generate_deltas(
+        baseline: &[Report],
+        current: &[Report],
+        format: &NumberFormat,
+    ) -> String {
+        let find = |name: &str| baseline.iter().find(|r| r.framework_name == name);
+
+        let mut res = format!(
+            "| {} | {} | Change | {} | Change | {} | Change |\n|---|---|---|---|---|---|---|",
+            Column::FrameworkName.header(),
+            Column::RequestReqPerSec.header(),
+            Column::LatencyP99.header(),
+            Column::MaxMemory.header()
+        );
+        for report in Self::sorted(current, SortKey::ReqPerSec) {
+            let base = find(&report.framework_name);
+            let delta = |value: fn(&Report) -> f64| {
+                base.map_or("-".to_string(), |base| change(value(report), value(base)))
             };
 
-            let row = format!("|{}|{:.4}ms|{:.4}ms|{}|{}|{}|{}|{:.4}ms|{}|{}|{}|{}|{}|",
-                              r.framework_name,
-                              r.metrics.latency.avg,
-                              r.metrics.latency.std_env,
-                              formatted_p50,
-                              formatted_p75,
-                              formatted_p90,
-                              formatted_p99,
-                              r.metrics.latency.max,
-                              r.metrics.request.total,
-                              r.metrics.request.req_per_sec,
-                              r.metrics.transfer.total,
-                              r.metrics.transfer.rate,
-                              r.max_memory);
-            res.push_str(&row);
-            res.push('\n');
-        }
-
-        res.pop(); // drop last '\n'
+            res.push_str(&format!(
+                "\n|{}|{}|{}|{}|{}|{}|{}|",
+                report.framework_name,
+                report.cell(Column::RequestReqPerSec, format),
+                delta(|r| r.metrics.req_per_sec()),
+                report.cell(Column::LatencyP99, format),
+                delta(|r| r.metrics.p99()),
+                report.cell(Column::MaxMemory, format),
+                delta(|r| r.max_memory)
+            ));
+        }
+        for missing in baseline
+            .iter()
+            .filter(|r| !current.iter().any(|c| c.framework_name == r.framework_name))
+        {
+            res.push_str(&format!(
+                "\n|{} (not measured)|-|-|-|-|-|-|",
+                missing.framework_name
+            ));
+        }
+
+        res
+    }
+
+    /// Medal of `report` when it is among the three fastest of `reports`.
+    /// Reports showing the same Req/Sec in `format` are tied and share a
+    /// medal, the next one ranks below all of them.
+    pub fn medal(
+        reports: &[Report],
+        report: &Report,
+        format: &NumberFormat,
+    ) -> Option<&'static str> {
+        let shown = report.cell(Column::RequestReqPerSec, format);
+        let faster = reports
+            .iter()
+            .filter(|r| r.is_reliable() && r.metrics.req_per_sec() > report.metrics.req_per_sec())
+            .filter(|r| r.cell(Column::RequestReqPerSec, format) != shown)
+            .count();
+
+        MEDALS.get(faster).copied()
+    }
+
+    /// Cell of `column` with the decorations enabled in `options`.
+    pub fn render_cell(
+        &self,
+        reports: &[Report],
+        column: Column,
+        options: &ReportOptions,
+    ) -> String {
+        let mut cell = self.cell(column, &options.format);
+
+        if column == Column::PercentOfBest {
+            return analysis::percent_of_best(self, reports).map_or(cell, |percent| {
+                format!("{}%", options.format.ratio(percent))
+            });
+        }
+
+        if column != Column::FrameworkName {
+            return cell;
+        }
+
+        if let Some(dir) = &options.detail_links {
+            cell = format!("[{}]({}/{}.md)", cell, dir, self.framework_name);
+        }
+
+        if options.regressions.contains(&self.framework_name) {
+            cell = format!("🔻 {}", cell);
+        }
+
+        if self.incomplete {
+            cell = format!("⚠️ {}", cell);
+        }
+
+        if self.pathological {
+            cell = format!("🐌 {}", cell);
+        }
+
+        if !self.is_reliable() {
+            return cell;
+        }
+
+        match Self::medal(reports, self, &options.format) {
+            Some(medal) if options.medals => format!("{} {}", medal, cell),
+            _ => cell,
+        }
+    }
+
+    /// Every collected metric as a two column table, for detail pages.
+    pub fn detail_table(&self, format: &NumberFormat) -> String {
+        let mut res = String::from("| Metric | Value |\n|---|---|");
+
+        for (name, value) in self.detail_rows(format) {
+            res.push_str(&format!("\n| {} | {} |", name, value));
+        }
 
         res
     }
+
+    /// (metric, value) of every collected metric, the rows of
+    /// [`Report::detail_table`].
+    pub fn detail_rows(&self, format: &NumberFormat) -> Vec<(String, String)> {
+        let mut rows = Vec::new();
+
+        for column in Column::value_variants()
+            .iter()
+            .filter(|c| !matches!(c, Column::FrameworkName | Column::PercentOfBest))
+        {
+            rows.push((column.header().to_string(), self.cell(*column, format)));
+        }
+        if let Some(phases) = &self.metrics.phases {
+            for (name, value) in [
+                ("P99 Connecting", phases.connecting),
+                ("P99 TLS Handshaking", phases.tls_handshaking),
+                ("P99 Waiting", phases.waiting),
+            ] {
+                rows.push((name.to_string(), format.latency(value)));
+            }
+        }
+        for (name, value) in &self.collected {
+            rows.push((name.clone(), format.ratio(*value)));
+        }
+
+        rows
+    }
+
+    /// Mean, standard deviation and best round of Req/Sec and latencies,
+    /// for detail pages. `None` unless the benchmark was repeated.
+    pub fn rounds_table(&self, format: &NumberFormat) -> Option<String> {
+        let mut res = String::from("| Metric | Mean | Stdev | Best |\n|---|---|---|---|");
+
+        for [name, mean, std_dev, best] in self.rounds_rows(format)? {
+            res.push_str(&format!(
+                "\n| {} | {} | {} | {} |",
+                name, mean, std_dev, best
+            ));
+        }
+
+        Some(res)
+    }
+
+    /// [metric, mean, stdev, best] rows of [`Report::rounds_table`].
+    pub fn rounds_rows(&self, format: &NumberFormat) -> Option<Vec<[String; 4]>> {
+        let req_per_sec: Vec<f64> = self.rounds.iter().map(Metrics::req_per_sec).collect();
+        let best = req_per_sec.iter().copied().fold(0.0, f64::max);
+
+        let mut rows = vec![[
+            Column::RequestReqPerSec.header().to_string(),
+            format.rate(stats::mean(&req_per_sec)),
+            format.rate(stats::std_dev(&req_per_sec)?),
+            format.rate(best),
+        ]];
+
+        for column in [Column::LatencyAvg, Column::LatencyP99] {
+            let latency = |m: &Metrics| {
+                if column == Column::LatencyAvg {
+                    m.latency.avg
+                } else {
+                    m.latency.p99
+                }
+            };
+            // rounds without a distribution don't count, the best is the lowest
+            let samples: Vec<f64> = self
+                .rounds
+                .iter()
+                .map(|m| latency(m).as_secs_f64())
+                .filter(|v| *v > 0.0)
+                .collect();
+            let Some(std_dev) = stats::std_dev(&samples) else {
+                continue;
+            };
+            let seconds = |value: f64| format.latency(Duration::from_secs_f64(value));
+
+            rows.push([
+                column.header().to_string(),
+                seconds(stats::mean(&samples)),
+                seconds(std_dev),
+                seconds(samples.iter().copied().fold(f64::INFINITY, f64::min)),
+            ]);
+        }
+
+        Some(rows)
+    }
+
+    pub fn framework_name(&self) -> &str {
+        &self.framework_name
+    }
+
+    /// Maximum memory usage in MB.
+    pub fn max_memory(&self) -> f64 {
+        self.max_memory
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    fn cell(&self, column: Column, format: &NumberFormat) -> String {
+        let latency = &self.metrics.latency;
+
+        // wrk omits the distribution without `--latency`
+        let percentile = |value: Duration| {
+            if value.is_zero() {
+                "-".to_string()
+            } else {
+                format.latency(value)
+            }
+        };
+
+        match column {
+            Column::FrameworkName => self.framework_name.clone(),
+            Column::Version => self.version.clone().unwrap_or_else(|| "-".to_string()),
+            Column::LatencyAvg => format.latency(latency.avg),
+            Column::LatencyStdev => format.latency(latency.std_env),
+            Column::LatencyMin => percentile(latency.min),
+            Column::LatencyP50 => percentile(latency.p50),
+            Column::LatencyP75 => percentile(latency.p75),
+            Column::LatencyP90 => percentile(latency.p90),
+            Column::LatencyP99 => match self.rounds_ci(Metrics::p99) {
+                Some((mean, half_width)) => format.uncertain_latency(mean, half_width),
+                None => percentile(latency.p99),
+            },
+            Column::LatencyMax => format.latency(latency.max),
+            Column::RequestTotal => format.count(self.metrics.request.total),
+            Column::RequestReqPerSec => match self.rounds_ci(Metrics::req_per_sec) {
+                Some((mean, half_width)) => format.uncertain_rate(mean, half_width),
+                None => format.rate(self.metrics.request.req_per_sec),
+            },
+            Column::Errors => match self.metrics.errors.total() {
+                0 => "0".to_string(),
+                total => format!(
+                    "{} ({}%)",
+                    format.count(total),
+                    format.ratio(total as f64 / self.metrics.total_requests().max(1.0) * 100.0)
+                ),
+            },
+            Column::MaxConnections => self
+                .capacity
+                .map_or_else(|| "-".to_string(), |capacity| capacity.cell(format)),
+            Column::StatusCodes => match self.metrics.status_codes.is_empty() {
+                true => "-".to_string(),
+                false => {
+                    let class = |class: u16| {
+                        self.metrics
+                            .status_codes
+                            .range(class * 100..(class + 1) * 100)
+                            .map(|(_, count)| count)
+                            .sum::<u64>()
+                    };
+                    format!(
+                        "{}/{}/{}",
+                        format.count(class(2)),
+                        format.count(class(4)),
+                        format.count(class(5))
+                    )
+                }
+            },
+            // noise across rounds, a single round says nothing about it
+            Column::RequestCv => match self.rounds_cv() {
+                Some(cv) => format!("{}%", format.ratio(cv * 100.0)),
+                None => "-".to_string(),
+            },
+            Column::Grade => match self.rounds_cv() {
+                Some(cv) => stats::noise_grade(cv).to_string(),
+                None => "-".to_string(),
+            },
+            Column::ThreadBalance => match self.metrics.thread_balance() {
+                Some(balance) if balance > SKEWED_THREAD_BALANCE => {
+                    format!("{} (skewed)", format.ratio(balance))
+                }
+                Some(balance) => format.ratio(balance),
+                None => "-".to_string(),
+            },
+            Column::TransferTotal => format.bytes(self.metrics.transfer.total as f64),
+            Column::TransferRate => format.bytes(self.metrics.transfer.rate),
+            Column::MaxMemory => format.memory(self.max_memory),
+            Column::CpuPerRequest => match analysis::cpu_per_request(self) {
+                Some(us) => format.cpu_time(us),
+                None => "-".to_string(),
+            },
+            Column::EnergyPerMillionRequests => match analysis::energy_per_million_requests(self) {
+                Some(joules) => format!("{}J", format.ratio(joules)),
+                None => "-".to_string(),
+            },
+            // relative to the other reports, see `render_cell`
+            Column::PercentOfBest => "-".to_string(),
+            Column::RequestsPerMb => match analysis::requests_per_mb(self) {
+                Some(rate) => format.ratio(rate),
+                None => "-".to_string(),
+            },
+        }
+    }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Metrics {
-    latency: Latency,
-    request: Request,
-    transfer: Transfer,
+    pub latency: Latency,
+    pub request: Request,
+    pub transfer: Transfer,
+    pub threads: Option<ThreadStats>,
+    #[serde(default)]
+    pub errors: Errors,
+    /// Responses of each second of the run, see [`crate::timeline`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub timeline: Vec<u64>,
+    /// Responses by status code, empty when the tool doesn't report them.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub status_codes: BTreeMap<u16, u64>,
+    /// 99th percentiles of the phases of requests, for tools timing them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub phases: Option<Phases>,
 }
 
-// parse std output from wrk result
-impl FromStr for Metrics {
-    type Err = MetricsError;
+/// 99th percentile of each phase of a request, telling whether slow
+/// requests wait for accepting, TLS or the handler. The whole request is
+/// [`Latency::p99`].
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Phases {
+    /// Establishing the TCP connection.
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub connecting: Duration,
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub tls_handshaking: Duration,
+    /// From sending the request to the first byte of the response.
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub waiting: Duration,
+}
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let latency_regex = Regex::new(r"Latency\s+(\d+\.\d+(?:us|ms|s))\s+(\d+\.\d+(?:us|ms|s))\s+(\d+\.\d+(?:us|ms|s))").unwrap();
-        let total_requests_regex = Regex::new(r"(\d+) requests in").unwrap();
-        let total_data_read_regex = Regex::new(r", (\d+\.\d+[GMK]?B) read").unwrap();
-        let req_per_sec_regex = Regex::new(r"Requests/sec:\s+(\d+\.\d+)").unwrap();
-        let transfer_per_sec_regex = Regex::new(r"Transfer/sec:\s+(\d+\.\d+[GMK]?B)").unwrap();
-
-        let avg_latency = latency_regex.captures(input).and_then(|cap| cap.get(1)).map(|m| m.as_str().to_string());
-        let stddev_latency = latency_regex.captures(input).and_then(|cap| cap.get(2)).map(|m| m.as_str().to_string());
-        let max_latency = latency_regex.captures(input).and_then(|cap| cap.get(3)).map(|m| m.as_str().to_string());
-
-        let total_requests = total_requests_regex.captures(input).and_then(|cap| cap.get(1)).map(|m| m.as_str().to_string());
-        let req_per_sec = req_per_sec_regex.captures(input).and_then(|cap| cap.get(1)).map(|m| m.as_str().to_string());
-        let total_data_read = total_data_read_regex.captures(input).and_then(|cap| cap.get(1)).map(|m| m.as_str().to_string());
-        let transfer_per_sec = transfer_per_sec_regex.captures(input).and_then(|cap| cap.get(1)).map(|m| m.as_str().to_string());
-
-        let latency_distribution_regex = Regex::new(r"Latency Distribution\s*50%\s*(\d+\.\d+(us|ms|s)?)\s*75%\s*(\d+\.\d+(us|ms|s)?)\s*90%\s*(\d+\.\d+(us|ms|s)?)\s*99%\s*(\d+\.\d+(us|ms|s)?)").unwrap();
-
-        let mut p50_latency_ms = 0.0;
-        let mut p75_latency_ms = 0.0;
-        let mut p90_latency_ms = 0.0;
-        let mut p99_latency_ms = 0.0;
-
-        if let Some(captures) = latency_distribution_regex.captures(input) {
-            let p50_latency = captures.get(1).map_or("0.0ms", |m| m.as_str());
-            let p75_latency = captures.get(3).map_or("0.0ms", |m| m.as_str());
-            let p90_latency = captures.get(5).map_or("0.0ms", |m| m.as_str());
-            let p99_latency = captures.get(7).map_or("0.0ms", |m| m.as_str());
-
-            p50_latency_ms = convert_to_ms(p50_latency);
-            p75_latency_ms = convert_to_ms(p75_latency);
-            p90_latency_ms = convert_to_ms(p90_latency);
-            p99_latency_ms = convert_to_ms(p99_latency);
-        }
-
-        // Constructing structs from the local variables
-        let latency = Latency {
-            avg: convert_to_ms(&avg_latency.unwrap_or_default()),
-            std_env: convert_to_ms(&stddev_latency.unwrap_or_default()),
-            max: convert_to_ms(&max_latency.unwrap_or_default()),
-            p50: p50_latency_ms,
-            p75: p75_latency_ms,
-            p90: p90_latency_ms,
-            p99: p99_latency_ms,
-        };
+impl Metrics {
+    pub fn req_per_sec(&self) -> f64 {
+        self.request.req_per_sec
+    }
 
-        let request = Request {
-            total: total_requests.unwrap_or_default(),
-            req_per_sec: req_per_sec.unwrap_or_default(),
-        };
+    pub fn total_requests(&self) -> f64 {
+        self.request.total as f64
+    }
 
-        let metrics = Metrics {
-            latency,
-            request,
-            transfer: Transfer {
-                total: total_data_read.unwrap_or_default(),
-                rate: transfer_per_sec.unwrap_or_default(),
-            },
-        };
+    /// Median latency in ms, 0 when not reported.
+    pub fn p50(&self) -> f64 {
+        millis(self.latency.p50)
+    }
 
-        Ok(metrics)
+    /// 75th percentile latency in ms, 0 when not reported.
+    pub fn p75(&self) -> f64 {
+        millis(self.latency.p75)
     }
-}
 
-#[derive(PartialEq, Debug)]
-struct Latency {
-    avg: f64,
-    std_env: f64,
-    max: f64,
-    p50: f64,
-    p75: f64,
-    p90: f64,
-    p99: f64,
+    /// 90th percentile latency in ms, 0 when not reported.
+    pub fn p90(&self) -> f64 {
+        millis(self.latency.p90)
+    }
+
+    /// 99th percentile latency in ms, 0 when not reported.
+    pub fn p99(&self) -> f64 {
+        millis(self.latency.p99)
+    }
+
+    /// Coefficient of variation of per-thread Req/Sec, 0 means perfectly
+    /// balanced workers.
+    pub fn thread_balance(&self) -> Option<f64> {
+        self.threads
+            .as_ref()
+            .filter(|threads| threads.req_per_sec_avg > 0.0)
+            .map(|threads| threads.req_per_sec_stdev / threads.req_per_sec_avg)
+    }
+
+    pub fn timeline(&self) -> &[u64] {
+        &self.timeline
+    }
 }
 
-#[derive(PartialEq, Debug)]
-struct Request {
-    total: String,
-    req_per_sec: String,
+// parse std output of whichever load tool produced it, see `crate::parser`
+impl FromStr for Metrics {
+    type Err = MetricsError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parser::parse(input, false)
+    }
 }
 
-#[derive(PartialEq, Debug)]
-struct Transfer {
-    total: String,
-    rate: String,
+/// Latencies, stored as milliseconds. Percentiles are zero when the load
+/// tool didn't report the distribution.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Latency {
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub avg: Duration,
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub std_env: Duration,
+    /// Zero when the tool reports no minimum, wrk doesn't.
+    #[serde(default, with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub min: Duration,
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub max: Duration,
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub p50: Duration,
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub p75: Duration,
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub p90: Duration,
+    #[serde(with = "units::serde_metrics::millis")]
+    #[schemars(with = "f64")]
+    pub p99: Duration,
 }
 
-fn convert_to_ms(latency: &str) -> f64 {
-    let regex = Regex::new(r"(\d+\.\d+)(us|ms|s)").unwrap();
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Request {
+    #[serde(deserialize_with = "units::serde_metrics::count")]
+    pub total: u64,
+    #[serde(deserialize_with = "units::serde_metrics::rate")]
+    pub req_per_sec: f64,
+}
 
-    if let Some(captures) = regex.captures(latency) {
-        let value: f64 = captures.get(1).map_or("0.0", |m| m.as_str()).parse().unwrap_or(0.0);
-        let unit = captures.get(2).map_or("ms", |m| m.as_str());
+/// Per-thread Req/Sec as printed in wrk's "Thread Stats".
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct ThreadStats {
+    pub req_per_sec_avg: f64,
+    pub req_per_sec_stdev: f64,
+}
 
-        let converted = match unit {
-            "us" => value / 1000.0, // microseconds to milliseconds
-            "s" => value * 1000.0, // seconds to milliseconds
-            "ms" => value, // already in milliseconds
-            _ => 0.0, // invalid or unknown unit
-        };
+/// Failed requests, as counted by the load tool.
+#[derive(PartialEq, Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Errors {
+    pub connect: u64,
+    pub read: u64,
+    pub write: u64,
+    pub timeout: u64,
+    /// Responses with a status other than 2xx or 3xx.
+    pub status: u64,
+}
 
-        // Round off to 4 decimal places
-        return (converted * 10_000.0).round() / 10_000.0;
+impl Errors {
+    pub fn total(&self) -> u64 {
+        self.connect + self.read + self.write + self.timeout + self.status
     }
+}
 
-    0.0
+/// Bytes read, in total and per second.
+#[derive(PartialEq, Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Transfer {
+    #[serde(deserialize_with = "units::serde_metrics::bytes")]
+    pub total: u64,
+    #[serde(deserialize_with = "units::serde_metrics::byte_rate")]
+    pub rate: f64,
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
 }
 
 #[cfg(test)]
@@ -211,7 +1372,10 @@ mod tests {
         #[test]
         fn generate() {
             let given = vec![
-                Report::new("actix-web", 13.7, r#"
+                Report::new(
+                    "actix-web",
+                    13.7,
+                    r#"
                     Running 30s test @ http://127.0.0.1:3000
                       16 threads and 500 connections
                       Thread Stats   Avg      Stdev     Max   +/- Stdev
@@ -225,8 +1389,14 @@ mod tests {
                       17275966 requests in 30.09s, 1.95GB read
                     Requests/sec: 574184.09
                     Transfer/sec:     66.26MB
-                "#.parse().expect("parse metric fail")),
-                Report::new("axum", 12.4, r#"
+                "#
+                    .parse()
+                    .expect("parse metric fail"),
+                ),
+                Report::new(
+                    "axum",
+                    12.4,
+                    r#"
                     Running 30s test @ http://127.0.0.1:3000
                       16 threads and 200 connections
                       Thread Stats   Avg      Stdev     Max   +/- Stdev
@@ -235,28 +1405,119 @@ mod tests {
                       14134927 requests in 30.10s, 1.59GB read
                     Requests/sec: 469597.42
                     Transfer/sec:     54.19MB
-                "#.parse().expect("parse metric fail")),
+                "#
+                    .parse()
+                    .expect("parse metric fail"),
+                ),
             ];
 
-            let actual = Report::generate_from(&given);
+            let actual = Report::generate_from(&given, &ReportOptions::default());
 
             let expect = r#"
-| Framework Name | Latency.Avg | Latency.Stdev | Latency.50P | Latency.75P | Latency.90P | Latency.99P | Latency.Max | Request.Total | Request.Req/Sec | Transfer.Total | Transfer.Rate | Max. Memory Usage |
-|---|---|---|---|---|---|---|---|---|---|---|---|---|
-|actix-web|0.8143ms|0.4985ms|0.7070ms|1.0700ms|1.5000ms|2.5600ms|8.4200ms|17275966|574184.09|1.95GB|66.26MB|13.7MB|
-|axum|0.3923ms|0.1997ms|-|-|-|-|4.6700ms|14134927|469597.42|1.59GB|54.19MB|12.4MB|
+| Framework Name | Version | Latency.Avg | Latency.Stdev | Latency.50P | Latency.75P | Latency.90P | Latency.99P | Latency.Max | Request.Total | Request.Req/Sec | % of Best | Errors | Request.CV | Grade | Thread.Balance | Transfer.Total | Transfer.Rate | Max. Memory Usage | Req/Sec per MB | CPU/Request | Energy/1M Req |
+|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|---|
+|actix-web|-|0.8143ms|0.4985ms|0.7070ms|1.0700ms|1.5000ms|2.5600ms|8.4200ms|17275966|574184.09|100.00%|0|-|-|0.07|1.95GB|66.26MB|13.7MB|41911.25|-|-|
+|axum|-|0.3923ms|0.1997ms|-|-|-|-|4.6700ms|14134927|469597.42|81.79%|0|-|-|0.03|1.59GB|54.19MB|12.4MB|37870.76|-|-|
 "#.trim();
 
             assert_eq!(actual, expect);
         }
+
+        #[test]
+        fn column_builder() {
+            let actual = ReportOptions::new()
+                .with_columns(ColumnPreset::Compact)
+                .with_latency_percentiles()
+                .without_column(Column::Grade)
+                .with_column(Column::TransferRate)
+                .without_transfer();
+
+            assert_eq!(
+                actual.columns,
+                [
+                    Column::FrameworkName,
+                    Column::LatencyAvg,
+                    Column::LatencyP50,
+                    Column::LatencyP75,
+                    Column::LatencyP90,
+                    Column::LatencyP99,
+                    Column::RequestReqPerSec,
+                    Column::PercentOfBest,
+                    Column::Errors,
+                    Column::MaxMemory,
+                    Column::CpuPerRequest,
+                ]
+            );
+            assert_eq!(
+                ReportOptions::new().with_column(Column::Version).columns,
+                ColumnPreset::Full.columns()
+            );
+
+            let with_status = ReportOptions::new()
+                .with_column(Column::StatusCodes)
+                .columns;
+            let errors = with_status
+                .iter()
+                .position(|c| *c == Column::Errors)
+                .unwrap();
+            assert_eq!(
+                with_status[errors + 1..errors + 3],
+                [Column::StatusCodes, Column::RequestCv]
+            );
+        }
+
+        #[test]
+        fn json() {
+            let given = vec![Report::new(
+                "axum",
+                12.4,
+                r#"
+                  Thread Stats   Avg      Stdev     Max   +/- Stdev
+                    Latency   392.28us  199.70us   4.67ms   70.95%
+                  14134927 requests in 30.10s, 1.59GB read
+                Requests/sec: 469597.42
+                Transfer/sec:     54.19MB
+            "#
+                .parse()
+                .expect("parse metric fail"),
+            )
+            .with_version("0.7.4".to_string())];
+
+            let environment: Environment = serde_json::from_value(
+                serde_json::json!({ "cpu": "AMD EPYC 7B13", "kernel": "5.15.0" }),
+            )
+            .unwrap();
+            let command =
+                "wrk --threads 16 --connections 500 --duration 30s --latency http://127.0.0.1:3000";
+
+            let actual: serde_json::Value = serde_json::from_str(&Report::to_json(
+                "hello-world",
+                &environment,
+                command,
+                &given,
+            ))
+            .expect("parse json fail");
+
+            assert_eq!(actual["scenario"], "hello-world");
+            assert_eq!(actual["environment"]["kernel"], "5.15.0");
+            assert_eq!(actual["command"], command);
+            let axum = &actual["reports"][0];
+            assert_eq!(axum["framework_name"], "axum");
+            assert_eq!(axum["version"], "0.7.4");
+            assert_eq!(axum["req_per_sec"], 469597.42);
+            assert_eq!(axum["requests_total"], 14134927);
+            assert_eq!(axum["latency_max_ms"], 4.67);
+            assert_eq!(axum["latency_p99_ms"], serde_json::Value::Null);
+            assert_eq!(axum["max_memory_mb"], 12.4);
+        }
     }
 
-    mod metrics {
+    mod snapshots {
         use super::*;
+        use crate::format::UnitDisplay;
+        use crate::markdown::Markdown;
 
-        #[test]
-        fn ok() {
-            let given = r#"
+        const WITH_DISTRIBUTION: &str = r#"
 Running 30s test @ http://127.0.0.1:3000
   16 threads and 500 connections
   Thread Stats   Avg      Stdev     Max   +/- Stdev
@@ -270,34 +1531,571 @@ Running 30s test @ http://127.0.0.1:3000
   17275966 requests in 30.09s, 1.95GB read
 Requests/sec: 574184.09
 Transfer/sec:     66.26MB
+"#;
+
+        const WITHOUT_DISTRIBUTION: &str = r#"
+Running 30s test @ http://127.0.0.1:3000
+  16 threads and 200 connections
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   392.28us  199.70us   4.67ms   70.95%
+    Req/Sec    29.50k     0.98k   33.01k    68.63%
+  14134927 requests in 30.10s, 1.59GB read
+Requests/sec: 469597.42
+Transfer/sec:     54.19MB
+"#;
+
+        fn report(name: &str, max_memory: f64, output: &str) -> Report {
+            Report::new(name, max_memory, output.parse().expect("parse metric fail"))
+        }
+
+        #[test]
+        fn markdown_table() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report("axum", 12.4, WITH_DISTRIBUTION),
+            ];
+
+            insta::assert_snapshot!(Report::generate_from(&given, &ReportOptions::default()));
+        }
+
+        #[test]
+        fn markdown_table_missing_optional_metrics() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+
+            insta::assert_snapshot!(Report::generate_from(&given, &ReportOptions::default()));
+        }
+
+        #[test]
+        fn markdown_table_scenarios() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION).with_scenario("hello-world"),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION).with_scenario("hello-world"),
+                report("axum", 14.1, WITH_DISTRIBUTION).with_scenario("json"),
+            ];
+
+            insta::assert_snapshot!(Report::generate_from(
+                &given,
+                &ReportOptions {
+                    columns: ColumnPreset::Compact.columns().to_vec(),
+                    medals: true,
+                    ..ReportOptions::default()
+                }
+            ));
+        }
+
+        #[test]
+        fn markdown_table_unicode_names() {
+            let given = vec![
+                report("ferris-🦀", 1.0, WITH_DISTRIBUTION),
+                report("сервер", 1024.25, WITHOUT_DISTRIBUTION),
+            ];
+
+            insta::assert_snapshot!(Report::generate_from(&given, &ReportOptions::default()));
+        }
+
+        #[test]
+        fn markdown_table_presets() {
+            let given = vec![report("actix-web", 13.7, WITH_DISTRIBUTION)];
+
+            for preset in [
+                ColumnPreset::Compact,
+                ColumnPreset::Latency,
+                ColumnPreset::Memory,
+            ] {
+                insta::assert_snapshot!(
+                    format!("markdown_table_preset_{:?}", preset).to_lowercase(),
+                    Report::generate_from(
+                        &given,
+                        &ReportOptions {
+                            columns: preset.columns().to_vec(),
+                            ..ReportOptions::default()
+                        }
+                    )
+                );
+            }
+        }
+
+        #[test]
+        fn markdown_table_humanized() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report("axum", 0.5, WITHOUT_DISTRIBUTION),
+            ];
+            let options = ReportOptions {
+                format: NumberFormat {
+                    decimals: None,
+                    units: UnitDisplay::Humanized,
+                    thousands_separator: Some(','),
+                },
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_table_medals() {
+            let given = vec![
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report(
+                    "hyper",
+                    11.0,
+                    WITH_DISTRIBUTION.replace("574184.09", "635430.27").as_str(),
+                ),
+                report(
+                    "tide",
+                    22.4,
+                    WITHOUT_DISTRIBUTION
+                        .replace("469597.42", "10747.70")
+                        .as_str(),
+                ),
+            ];
+            let options = ReportOptions {
+                columns: ColumnPreset::Memory.columns().to_vec(),
+                medals: true,
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn tied_medals() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report(
+                    "hyper",
+                    11.0,
+                    WITH_DISTRIBUTION.replace("574184.09", "574180.00").as_str(),
+                ),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            let humanized = NumberFormat {
+                units: UnitDisplay::Humanized,
+                ..NumberFormat::default()
+            };
+            let medals: Vec<_> = given
+                .iter()
+                .map(|r| Report::medal(&given, r, &humanized))
+                .collect();
+
+            assert_eq!(medals, vec![Some("🥇"), Some("🥇"), Some("🥉")]);
+            assert_eq!(
+                Report::medal(&given, &given[1], &NumberFormat::default()),
+                Some("🥈")
+            );
+        }
+
+        #[test]
+        fn sorted() {
+            let given = vec![
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report(
+                    "hyper",
+                    11.0,
+                    WITH_DISTRIBUTION.replace("574184.09", "635430.27").as_str(),
+                ),
+            ];
+            let names = |key| {
+                Report::sorted(&given, key)
+                    .iter()
+                    .map(|r| r.framework_name())
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(names(SortKey::ReqPerSec), ["hyper", "actix-web", "axum"]);
+            assert_eq!(names(SortKey::Latency), ["actix-web", "hyper", "axum"]);
+            assert_eq!(names(SortKey::Memory), ["hyper", "axum", "actix-web"]);
+            assert_eq!(names(SortKey::Name), ["actix-web", "axum", "hyper"]);
+            assert_eq!(names(SortKey::Measured), ["axum", "actix-web", "hyper"]);
+        }
+
+        #[test]
+        fn markdown_table_detail_links() {
+            let given = vec![report("actix-web", 13.7, WITH_DISTRIBUTION)];
+            let options = ReportOptions {
+                columns: ColumnPreset::Memory.columns().to_vec(),
+                medals: true,
+                detail_links: Some("hello-world".to_string()),
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_detail_table() {
+            let given = report("actix-web", 13.7, WITH_DISTRIBUTION);
+
+            insta::assert_snapshot!(given.detail_table(&NumberFormat::default()));
+        }
+
+        #[test]
+        fn markdown_table_regressions() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            let options = ReportOptions {
+                columns: ColumnPreset::Memory.columns().to_vec(),
+                medals: true,
+                regressions: vec!["axum".to_string()],
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_rounds_table() {
+            let rounds = ["574184.09", "568000.00", "580100.50"]
+                .iter()
+                .map(|rps| {
+                    WITH_DISTRIBUTION
+                        .replace("574184.09", rps)
+                        .parse()
+                        .expect("parse metric fail")
+                })
+                .collect();
+            let given = Report::from_rounds("actix-web", 13.7, rounds).unwrap();
+
+            assert_eq!(
+                report("axum", 12.4, WITHOUT_DISTRIBUTION).rounds_table(&NumberFormat::default()),
+                None
+            );
+            insta::assert_snapshot!(given.rounds_table(&NumberFormat::default()).unwrap());
+        }
+
+        #[test]
+        fn markdown_table_incomplete() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION).with_incomplete(),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            let options = ReportOptions {
+                columns: ColumnPreset::Memory.columns().to_vec(),
+                medals: true,
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_table_status_codes() {
+            let mut given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            given[0].metrics.status_codes =
+                BTreeMap::from([(200, 17275900), (204, 10), (404, 6), (500, 50)]);
+            let options = ReportOptions {
+                columns: vec![Column::FrameworkName, Column::Errors, Column::StatusCodes],
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_table_pathological() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report(
+                    "axum",
+                    12.4,
+                    WITHOUT_DISTRIBUTION.replace("469597.42", "812.50").as_str(),
+                )
+                .with_pathological(),
+                report(
+                    "tide",
+                    12.4,
+                    WITHOUT_DISTRIBUTION.replace("469597.42", "401.25").as_str(),
+                )
+                .with_pathological()
+                .with_incomplete(),
+            ];
+            let options = ReportOptions {
+                columns: ColumnPreset::Compact.columns().to_vec(),
+                medals: true,
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_table_rounds() {
+            let rounds = [
+                ("574184.09", "2.56ms"),
+                ("568000.00", "2.71ms"),
+                ("580100.50", "2.49ms"),
+                ("571250.00", "2.60ms"),
+                ("576900.00", "2.52ms"),
+            ]
+            .iter()
+            .map(|(rps, p99)| {
+                WITH_DISTRIBUTION
+                    .replace("574184.09", rps)
+                    .replace("99%    2.56ms", &format!("99%    {}", p99))
+                    .parse()
+                    .expect("parse metric fail")
+            })
+            .collect();
+            let given = vec![Report::from_rounds("actix-web", 13.7, rounds)
+                .unwrap()
+                .with_cpu_time(103.66)];
+            let options = ReportOptions {
+                columns: ColumnPreset::Compact.columns().to_vec(),
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_table_categories() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report(
+                    "hyper",
+                    11.0,
+                    WITH_DISTRIBUTION.replace("574184.09", "635430.27").as_str(),
+                ),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            let category_of = |name: &str| match name {
+                "hyper" => Category::RawServer,
+                _ => Category::Framework,
+            };
+            let options = ReportOptions {
+                columns: ColumnPreset::Memory.columns().to_vec(),
+                medals: true,
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_by_category(&given, category_of, &options));
+        }
+
+        #[test]
+        fn markdown_variants() {
+            let given = vec![
+                report("axum", 12.4, WITH_DISTRIBUTION),
+                report(
+                    "axum-mimalloc",
+                    14.1,
+                    WITH_DISTRIBUTION
+                        .replace("574184.09", "592562.10")
+                        .replace("2.56ms", "2.31ms")
+                        .as_str(),
+                ),
+                report("axum-tokio-current-thread", 9.8, WITHOUT_DISTRIBUTION),
+                report("hyper", 11.0, WITH_DISTRIBUTION),
+            ];
+            let variant_of = |name: &str| {
+                let axis = match name {
+                    "axum-mimalloc" => "allocator",
+                    "axum-tokio-current-thread" => "runtime",
+                    _ => return None,
+                };
+                Some(Variant {
+                    of: "axum".to_string(),
+                    axis: axis.to_string(),
+                })
+            };
+
+            assert_eq!(
+                Report::generate_variants(&given[3..], variant_of, &NumberFormat::default()),
+                None
+            );
+            insta::assert_snapshot!(Report::generate_variants(
+                &given,
+                variant_of,
+                &NumberFormat::default()
+            )
+            .unwrap());
+        }
+
+        #[test]
+        fn markdown_deltas() {
+            let baseline = vec![
+                report("axum", 12.4, WITH_DISTRIBUTION),
+                report("hyper", 11.0, WITH_DISTRIBUTION),
+                report("tide", 22.4, WITHOUT_DISTRIBUTION),
+            ];
+            let current = vec![
+                report(
+                    "axum",
+                    13.1,
+                    WITH_DISTRIBUTION
+                        .replace("574184.09", "612004.51")
+                        .replace("2.56ms", "2.31ms")
+                        .as_str(),
+                ),
+                report(
+                    "hyper",
+                    11.0,
+                    WITH_DISTRIBUTION.replace("574184.09", "551216.73").as_str(),
+                ),
+                report("salvo", 15.2, WITHOUT_DISTRIBUTION),
+            ];
+
+            insta::assert_snapshot!(Report::generate_deltas(
+                &baseline,
+                &current,
+                &NumberFormat::default()
+            ));
+        }
 
-691 Errors: error shutting down connection: Socket is not connected (os error 57)
+        #[test]
+        fn markdown_table_slo() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            let options = ReportOptions {
+                columns: vec![Column::FrameworkName, Column::LatencyP99],
+                slo: vec![Duration::from_millis(1), Duration::from_millis(5)],
+                apdex: Some(Duration::from_millis(1)),
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn csv() {
+            let mut given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION)
+                    .with_collected(BTreeMap::from([("GPU %".to_string(), 12.5)])),
+                report("axum, tokio", 12.4, WITHOUT_DISTRIBUTION)
+                    .with_version("0.7.4 (git 3a1b6f2)".to_string()),
+            ];
+            given[0].metrics.status_codes = BTreeMap::from([(200, 17275900), (500, 66)]);
+
+            insta::assert_snapshot!(Report::to_csv(&given));
+        }
+
+        #[test]
+        fn json_line() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION)
+                    .with_collected(BTreeMap::from([("GPU %".to_string(), 12.5)])),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION).with_version("0.7.4".to_string()),
+            ];
+            let environment: Environment = serde_json::from_value(
+                serde_json::json!({ "cpu": "AMD EPYC 7B13", "kernel": "5.15.0" }),
+            )
+            .unwrap();
+            let command =
+                "wrk --threads 16 --connections 500 --duration 30s --latency http://127.0.0.1:3000";
+
+            let line: serde_json::Value = serde_json::from_str(&Report::to_json(
+                "hello-world",
+                &environment,
+                command,
+                &given,
+            ))
+            .expect("parse json fail");
+
+            insta::assert_snapshot!(serde_json::to_string_pretty(&line).unwrap());
+        }
+
+        #[test]
+        fn html_page() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            let options = ReportOptions {
+                medals: true,
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(crate::html::report("hello-world", &given, &[], &options));
+        }
+
+        #[test]
+        fn markdown_table_collected() {
+            let given = vec![
+                report("actix-web", 13.7, WITH_DISTRIBUTION)
+                    .with_collected(BTreeMap::from([("GPU %".to_string(), 12.5)])),
+                report("axum", 12.4, WITHOUT_DISTRIBUTION),
+            ];
+            let options = ReportOptions {
+                columns: ColumnPreset::Memory.columns().to_vec(),
+                ..ReportOptions::default()
+            };
+
+            insta::assert_snapshot!(Report::generate_from(&given, &options));
+        }
+
+        #[test]
+        fn markdown_table_all_frameworks_failed() {
+            insta::assert_snapshot!(Report::generate_from(
+                &Vec::new(),
+                &ReportOptions::default()
+            ));
+        }
+
+        #[test]
+        fn markdown_document() {
+            let given = vec![report("actix-web", 13.7, WITH_DISTRIBUTION)];
+
+            let mut md = Markdown::new();
+            md.add_item("## Comparisons");
+            md.add_item(Report::generate_from(&given, &ReportOptions::default()));
+            md.add_item("## actix-web");
+            md.add_item(format!("```\n{}\n```", WITH_DISTRIBUTION.trim()));
+
+            insta::assert_snapshot!(md.finish());
+        }
+    }
+
+    mod metrics {
+        use super::*;
+
+        #[test]
+        fn not_wrk_output() {
+            let actual = "connection refused".parse::<Metrics>();
+
+            assert_eq!(actual, Err(MetricsError::ParseError));
+        }
+
+        #[test]
+        fn thread_balance() {
+            let given = r#"
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+    Req/Sec    20.00k    10.00k   74.83k    75.41%
             "#;
-            let actual = given.parse::<Metrics>();
-
-            let expect = Ok(
-                Metrics {
-                    latency: Latency {
-                        avg: 0.8143,
-                        std_env: 0.4985,
-                        // min: "0.02ms".to_string(),
-                        max: 8.4200,
-                        p50: 0.7070,
-                        p75: 1.0700,
-                        p90: 1.5000,
-                        p99: 2.5600,
-                    },
-                    request: Request {
-                        total: "17275966".to_string(),
-                        req_per_sec: "574184.09".to_string(),
-                    },
-                    transfer: Transfer {
-                        total: "1.95GB".to_string(),
-                        rate: "66.26MB".to_string(),
-                    },
-                });
+            let actual = given.parse::<Metrics>().expect("parse metric fail");
 
-            assert_eq!(actual, expect);
+            assert_eq!(actual.thread_balance(), Some(0.5));
+        }
+
+        #[test]
+        fn legacy_results() {
+            let given = r#"{
+                "latency": {"avg": 0.8143, "std_env": 0.4985, "max": 8.42,
+                    "p50": 0.707, "p75": 1.07, "p90": 1.5, "p99": 2.56},
+                "request": {"total": "17275966", "req_per_sec": "574184.09"},
+                "transfer": {"total": "1.95GB", "rate": "66.26MB"},
+                "threads": null
+            }"#;
+            let actual: Metrics = serde_json::from_str(given).expect("parse legacy metrics fail");
+
+            assert_eq!(actual.total_requests(), 17275966.0);
+            assert_eq!(actual.p99(), 2.56);
+            assert_eq!(actual.transfer.total, 2093796557);
+
+            let stored = serde_json::to_string(&actual).unwrap();
+            assert_eq!(serde_json::from_str::<Metrics>(&stored).unwrap(), actual);
         }
     }
 }
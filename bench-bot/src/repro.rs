@@ -0,0 +1,269 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+
+/// Directory of the output directory keeping what reproducing each run takes.
+pub const REPRO_DIR: &str = "repro";
+
+const MANIFEST_FILE: &str = "repro.json";
+const CONFIG_FILE: &str = "bench.toml";
+const LOCK_FILE: &str = "Cargo.lock";
+
+/// Everything besides the workspace sources a run depended on.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Repro {
+    pub id: String,
+    /// Commit of the benchmarked workspace, if it is a git checkout.
+    pub commit: Option<String>,
+    /// Pinned toolchain, the default one when unset.
+    pub toolchain: Option<String>,
+    /// `rustc --version` of the run.
+    pub rustc: Option<String>,
+    pub profile: Option<String>,
+    /// Config overrides of the environment and `--set`, as `KEY=VALUE`.
+    pub overrides: Vec<String>,
+    pub parameters: Parameters,
+    /// Seed of the bootstrap confidence intervals.
+    pub seed: u64,
+    /// The load tool invocation.
+    pub bench_command: String,
+    pub command_line: String,
+}
+
+/// Run parameters after applying the profile.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Parameters {
+    pub connections: usize,
+    pub duration: usize,
+    pub url: String,
+    pub cooldown: u64,
+    pub rounds: usize,
+    pub timeline: bool,
+    /// Percent of the mean the Req/Sec confidence interval of adaptive runs
+    /// was narrowed to, adding rounds up to `max_rounds`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_ci: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_rounds: Option<usize>,
+}
+
+/// Keep `repro` with the config file and the lockfile of the workspace
+/// below `<output_dir>/repro/<id>`.
+pub fn save(
+    output_dir: &Path,
+    repro: &Repro,
+    config_file: Option<&Path>,
+    workspace_dir: &Path,
+) -> Result<(), String> {
+    let dir = output_dir.join(REPRO_DIR).join(&repro.id);
+    fs::create_dir_all(&dir).map_err(|e| format!("creating {:?}: {}", dir, e))?;
+
+    let sources = [
+        (config_file.map(Path::to_path_buf), CONFIG_FILE),
+        (Some(workspace_dir.join(LOCK_FILE)), LOCK_FILE),
+    ];
+    for (source, name) in sources {
+        if let Some(source) = source.filter(|source| source.exists()) {
+            fs::copy(&source, dir.join(name))
+                .map_err(|e| format!("copying {:?}: {}", source, e))?;
+        }
+    }
+
+    let content = serde_json::to_vec_pretty(repro).map_err(|e| e.to_string())?;
+    let path = dir.join(MANIFEST_FILE);
+
+    fs::write(&path, content).map_err(|e| format!("writing {:?}: {}", path, e))
+}
+
+/// Pack what was saved for run `id` into the tarball `to`.
+pub fn export(output_dir: &Path, id: &str, to: &Path) -> Result<(), String> {
+    let repro_dir = output_dir.join(REPRO_DIR);
+    if !repro_dir.join(id).join(MANIFEST_FILE).exists() {
+        return Err(format!(
+            "no reproduction data of run {:?} in {:?}",
+            id, repro_dir
+        ));
+    }
+
+    let output = Command::new("tar")
+        .arg("-czf")
+        .arg(to)
+        .arg("-C")
+        .arg(&repro_dir)
+        .arg(id)
+        .output()
+        .map_err(|e| format!("running tar: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "tar failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// A bundle unpacked by [`import`].
+pub struct Bundle {
+    pub repro: Repro,
+    dir: PathBuf,
+}
+
+impl Bundle {
+    pub fn config_file(&self) -> Option<PathBuf> {
+        Some(self.dir.join(CONFIG_FILE)).filter(|path| path.exists())
+    }
+
+    pub fn lock_file(&self) -> Option<PathBuf> {
+        Some(self.dir.join(LOCK_FILE)).filter(|path| path.exists())
+    }
+
+    /// Put the lockfile of the bundle in place of the one of `workspace_dir`
+    /// until the returned guard is dropped, `None` when it has none.
+    pub fn replace_lock_file(&self, workspace_dir: &Path) -> Result<Option<LockFile>, String> {
+        let Some(bundled) = self.lock_file() else {
+            return Ok(None);
+        };
+        let path = workspace_dir.join(LOCK_FILE);
+        let original = fs::read(&path).ok();
+
+        fs::copy(&bundled, &path)
+            .map_err(|e| format!("copying {:?} to {:?}: {}", bundled, path, e))?;
+        Ok(Some(LockFile { path, original }))
+    }
+}
+
+/// Lockfile of a workspace replaced by [`Bundle::replace_lock_file`], the
+/// original is restored when dropped.
+pub struct LockFile {
+    path: PathBuf,
+    original: Option<Vec<u8>>,
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let restored = match &self.original {
+            Some(original) => fs::write(&self.path, original),
+            None => fs::remove_file(&self.path),
+        };
+        if let Err(e) = restored {
+            log::warn!("Restoring {:?} failed: {}.", self.path, e);
+        }
+    }
+}
+
+/// Unpack a tarball written by [`export`].
+pub fn import(tarball: &Path) -> Result<Bundle, String> {
+    let target = env::temp_dir().join("bench-bot-repro");
+    let _ = fs::remove_dir_all(&target);
+    fs::create_dir_all(&target).map_err(|e| format!("creating {:?}: {}", target, e))?;
+
+    let output = Command::new("tar")
+        .arg("-xzf")
+        .arg(tarball)
+        .arg("-C")
+        .arg(&target)
+        .output()
+        .map_err(|e| format!("running tar: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "unpacking {:?}: {}",
+            tarball,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    // the bundle holds the directory of a single run
+    let dir = fs::read_dir(&target)
+        .map_err(|e| format!("reading {:?}: {}", target, e))?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.join(MANIFEST_FILE).exists())
+        .ok_or_else(|| format!("{:?} holds no {}", tarball, MANIFEST_FILE))?;
+
+    let path = dir.join(MANIFEST_FILE);
+    let content = fs::read(&path).map_err(|e| format!("reading {:?}: {}", path, e))?;
+    let repro =
+        serde_json::from_slice(&content).map_err(|e| format!("parsing {:?}: {}", path, e))?;
+
+    Ok(Bundle { repro, dir })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dir = env::temp_dir().join("bench-bot-repro-test");
+        let _ = fs::remove_dir_all(&dir);
+        let (output_dir, workspace_dir) = (dir.join("result"), dir.join("benchmark"));
+        fs::create_dir_all(&workspace_dir).unwrap();
+        fs::write(workspace_dir.join(LOCK_FILE), "# lockfile").unwrap();
+        fs::write(
+            workspace_dir.join(CONFIG_FILE),
+            "[thresholds]\nrps = \"3%\"\n",
+        )
+        .unwrap();
+
+        let repro = Repro {
+            id: "1700000000".to_string(),
+            commit: Some("9f3c1a2".to_string()),
+            toolchain: Some("1.75.0".to_string()),
+            rustc: Some("rustc 1.75.0".to_string()),
+            profile: None,
+            overrides: vec!["thresholds.rps=\"5%\"".to_string()],
+            parameters: Parameters {
+                connections: 500,
+                duration: 30,
+                url: "http://127.0.0.1:3000".to_string(),
+                cooldown: 5,
+                rounds: 3,
+                timeline: false,
+                target_ci: None,
+                max_rounds: None,
+            },
+            seed: 1,
+            bench_command: "wrk --threads 16".to_string(),
+            command_line: "bench-bot -w benchmark -o result".to_string(),
+        };
+
+        save(
+            &output_dir,
+            &repro,
+            Some(&workspace_dir.join(CONFIG_FILE)),
+            &workspace_dir,
+        )
+        .expect("save fail");
+        export(&output_dir, &repro.id, &dir.join("repro.tar.gz")).expect("export fail");
+
+        let actual = import(&dir.join("repro.tar.gz")).expect("import fail");
+
+        assert_eq!(actual.repro, repro);
+        assert_eq!(
+            fs::read_to_string(actual.lock_file().unwrap()).unwrap(),
+            "# lockfile"
+        );
+
+        fs::write(workspace_dir.join(LOCK_FILE), "# changed since").unwrap();
+        let replaced = actual
+            .replace_lock_file(&workspace_dir)
+            .expect("replace fail");
+        assert_eq!(
+            fs::read_to_string(workspace_dir.join(LOCK_FILE)).unwrap(),
+            "# lockfile"
+        );
+        drop(replaced);
+        assert_eq!(
+            fs::read_to_string(workspace_dir.join(LOCK_FILE)).unwrap(),
+            "# changed since"
+        );
+        assert!(actual.config_file().is_some());
+        assert!(export(&output_dir, "missing", &dir.join("missing.tar.gz")).is_err());
+    }
+}
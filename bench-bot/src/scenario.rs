@@ -0,0 +1,201 @@
+/// What a scenario asks of its implementations. Reports describe scenarios
+/// from these definitions, keep them in sync with the implementations.
+#[derive(Debug, PartialEq)]
+pub struct Scenario {
+    pub name: &'static str,
+    /// Raised whenever the contract changes, results of different versions
+    /// are not comparable.
+    pub version: u32,
+    /// What the scenario measures.
+    pub measures: &'static str,
+    pub method: &'static str,
+    pub request: Option<&'static str>,
+    /// Exact headers of every request but `Host`, sent by the load tool in
+    /// place of its own defaults so every tool issues the same request.
+    /// `Host` is the authority of the benchmarked url, as any client sends.
+    pub headers: &'static [(&'static str, &'static str)],
+    pub response: &'static str,
+}
+
+const PLAIN_HEADERS: &[(&str, &str)] = &[
+    ("Accept", "*/*"),
+    ("Accept-Encoding", "identity"),
+    ("Connection", "keep-alive"),
+];
+
+const JSON_HEADERS: &[(&str, &str)] = &[
+    ("Accept", "application/json"),
+    ("Accept-Encoding", "identity"),
+    ("Connection", "keep-alive"),
+];
+
+pub const SCENARIOS: &[Scenario] = &[
+    Scenario {
+        name: "hello-world",
+        version: 1,
+        measures: "Raw request routing and response writing overhead.",
+        method: "GET",
+        request: None,
+        headers: PLAIN_HEADERS,
+        response: "A short plain text greeting such as `Hello, World!`.",
+    },
+    Scenario {
+        name: "json",
+        version: 1,
+        measures: "Serializing a small JSON document on every request.",
+        method: "GET",
+        request: None,
+        headers: JSON_HEADERS,
+        response: "`{\"message\":\"Hello, World!\"}` with `Content-Type: application/json`.",
+    },
+    Scenario {
+        name: "echo",
+        version: 1,
+        measures: "Reading request bodies.",
+        method: "POST",
+        request: Some("Any body."),
+        // the body is sent by the script of the scenario, see `bench.toml`
+        headers: &[
+            ("Accept", "*/*"),
+            ("Accept-Encoding", "identity"),
+            ("Connection", "keep-alive"),
+            ("Content-Type", "text/plain"),
+        ],
+        response: "The request body.",
+    },
+    Scenario {
+        name: "compute",
+        version: 1,
+        measures: "CPU bound handlers competing with request handling.",
+        method: "GET",
+        request: None,
+        headers: PLAIN_HEADERS,
+        response: "`7919`, the 1000th prime, computed by trial division on every request.",
+    },
+    Scenario {
+        name: "file",
+        version: 1,
+        measures: "Serving a file from disk.",
+        method: "GET",
+        request: None,
+        headers: PLAIN_HEADERS,
+        response: "The contents of `benchmark/static/index.html`, read on every request.",
+    },
+    Scenario {
+        name: "background",
+        version: 1,
+        measures: "Interference of background work with request handling, compare with json.",
+        method: "GET",
+        request: None,
+        headers: JSON_HEADERS,
+        response:
+            "A JSON payload cached by the server and rebuilt every 10ms by a background task.",
+    },
+    Scenario {
+        name: "error",
+        version: 1,
+        measures: "The framework's error type and its mapping to a status code.",
+        method: "GET",
+        request: None,
+        headers: PLAIN_HEADERS,
+        response: "`400 Bad Request` with an error message.",
+    },
+];
+
+/// Definition of the scenario called `name`.
+pub fn find(name: &str) -> Option<&'static Scenario> {
+    SCENARIOS.iter().find(|scenario| scenario.name == name)
+}
+
+impl Scenario {
+    /// Description section of reports, below a heading of the caller's.
+    pub fn to_markdown(&self) -> String {
+        let mut res = format!(
+            "{}\n\n- Version: {}\n- Endpoint: `{} <prefix>/{}`, `/` in binaries serving it alone",
+            self.measures, self.version, self.method, self.name
+        );
+
+        if let Some(request) = self.request {
+            res.push_str(&format!("\n- Request: {}", request));
+        }
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|(name, value)| format!("`{}: {}`", name, value))
+            .collect();
+        res.push_str(&format!(
+            "\n- Headers: `Host` of the url, {}",
+            headers.join(", ")
+        ));
+        res.push_str(&format!("\n- Response: {}", self.response));
+
+        res
+    }
+
+    /// Arguments of wrk sending the headers of the scenario to `url`.
+    pub fn header_args(&self, url: &str) -> Vec<String> {
+        let authority = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let authority = authority.split('/').next().unwrap_or_default();
+
+        std::iter::once(("Host", authority))
+            .chain(self.headers.iter().copied())
+            .flat_map(|(name, value)| ["--header".to_string(), format!("{}: {}", name, value)])
+            .collect()
+    }
+}
+
+/// Table of contents linking the sections of `scenarios` followed by one
+/// section each, e.g. for a README.
+pub fn document(scenarios: &[&Scenario]) -> String {
+    let mut res = String::from("## Scenarios\n");
+
+    for scenario in scenarios {
+        res.push_str(&format!("\n- [{}](#{})", scenario.name, scenario.name));
+    }
+
+    for scenario in scenarios {
+        res.push_str(&format!(
+            "\n\n### {}\n\n{}",
+            scenario.name,
+            scenario.to_markdown()
+        ));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markdown() {
+        let given: Vec<&Scenario> = ["hello-world", "echo"]
+            .iter()
+            .filter_map(|name| find(name))
+            .collect();
+
+        insta::assert_snapshot!(document(&given));
+    }
+
+    #[test]
+    fn headers() {
+        let actual = find("echo")
+            .unwrap()
+            .header_args("http://127.0.0.1:3000/echo");
+
+        assert_eq!(
+            actual[..4],
+            [
+                "--header",
+                "Host: 127.0.0.1:3000",
+                "--header",
+                "Accept: */*"
+            ]
+        );
+        assert_eq!(
+            actual.last().map(String::as_str),
+            Some("Content-Type: text/plain")
+        );
+    }
+}
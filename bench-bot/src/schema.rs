@@ -0,0 +1,59 @@
+use clap::ArgEnum;
+use schemars::schema_for;
+
+use crate::compare::Results;
+use crate::config::Config;
+use crate::events::EventLine;
+use crate::history::RunRecord;
+use crate::report::ScenarioReports;
+use crate::submission::Submission;
+
+/// Files written or read by bench-bot that other tools may consume.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum Format {
+    /// `<scenario>.json` results next to the markdown output.
+    Results,
+    /// One line of `history.jsonl`.
+    History,
+    /// `bench.toml`.
+    Config,
+    /// A line printed per scenario with `--format json`.
+    Reports,
+    /// A line written to `--events`.
+    Events,
+    /// A community submission posted by `submit`.
+    Submission,
+}
+
+/// JSON Schema of `format`, pretty printed.
+pub fn json_schema(format: Format) -> String {
+    let schema = match format {
+        Format::Results => schema_for!(Results),
+        Format::History => schema_for!(RunRecord),
+        Format::Config => schema_for!(Config),
+        Format::Reports => schema_for!(ScenarioReports),
+        Format::Events => schema_for!(EventLine),
+        Format::Submission => schema_for!(Submission),
+    };
+
+    serde_json::to_string_pretty(&schema).expect("schemas always serialize")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schemas() {
+        for format in Format::value_variants() {
+            let schema: serde_json::Value =
+                serde_json::from_str(&json_schema(*format)).expect("parse schema fail");
+
+            assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        }
+
+        let config: serde_json::Value = serde_json::from_str(&json_schema(Format::Config)).unwrap();
+        assert_eq!(config["additionalProperties"], false);
+        assert_eq!(config["definitions"]["Percent"]["type"], "string");
+    }
+}
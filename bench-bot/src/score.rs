@@ -0,0 +1,196 @@
+use std::collections::BTreeMap;
+
+use crate::config::Weights;
+use crate::report::Report;
+
+/// Weighted throughput of a framework over all scenarios.
+#[derive(Debug, PartialEq)]
+pub struct Score {
+    pub framework_name: String,
+    /// Req/Sec relative to the fastest framework, by scenario.
+    pub relative: BTreeMap<String, f64>,
+    /// 100 means fastest in every scenario, `None` when the framework has
+    /// no result for some scenario.
+    pub score: Option<f64>,
+}
+
+/// Score every framework by its Req/Sec relative to the fastest one of each
+/// scenario, combined as their geometric mean with the configured weights,
+/// so a framework can't make up for one slow scenario with a fast one as
+/// easily. Frameworks without a result for every scenario stay unscored and
+/// rank last.
+pub fn composite_scores(
+    scenarios: &BTreeMap<String, Vec<Report>>,
+    weights: &Weights,
+) -> Vec<Score> {
+    // scenarios without results, e.g. of experimental frameworks only, aren't required
+    let measured: Vec<&String> = scenarios
+        .iter()
+        .filter(|(_, reports)| !reports.is_empty())
+        .map(|(s, _)| s)
+        .collect();
+    let total_weight: f64 = measured.iter().map(|s| weights.get(s)).sum();
+    let mut relatives: BTreeMap<&str, BTreeMap<String, f64>> = BTreeMap::new();
+
+    for (scenario, reports) in scenarios {
+        let best = reports
+            .iter()
+            .map(|r| r.metrics().req_per_sec())
+            .fold(0.0, f64::max);
+
+        for report in reports {
+            let relative = if best > 0.0 {
+                report.metrics().req_per_sec() / best
+            } else {
+                0.0
+            };
+            relatives
+                .entry(report.framework_name())
+                .or_default()
+                .insert(scenario.clone(), relative);
+        }
+    }
+
+    let mut scores: Vec<Score> = relatives
+        .into_iter()
+        .map(|(framework_name, relative)| {
+            let complete = measured.iter().all(|s| relative.contains_key(*s));
+            let score = (complete && total_weight > 0.0).then(|| {
+                relative
+                    .iter()
+                    .map(|(s, r)| r.powf(weights.get(s) / total_weight))
+                    .product::<f64>()
+                    * 100.0
+            });
+
+            Score {
+                framework_name: framework_name.to_string(),
+                relative,
+                score,
+            }
+        })
+        .collect();
+
+    scores.sort_by(|a, b| b.score.unwrap_or(-1.0).total_cmp(&a.score.unwrap_or(-1.0)));
+    scores
+}
+
+/// Markdown listing the weights followed by the ranking.
+pub fn scores_markdown(scores: &[Score], scenarios: &[String], weights: &Weights) -> String {
+    let weights = scenarios
+        .iter()
+        .map(|s| format!("{} {}x", s, weights.get(s)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut res = format!(
+        "Score is the weighted geometric mean of Req/Sec relative to the fastest framework of each \
+            scenario, frameworks missing a scenario are not ranked.\n\nWeights: {}\n\n| Rank | \
+            Framework Name |",
+        weights
+    );
+    for scenario in scenarios {
+        res.push_str(&format!(" {} |", scenario));
+    }
+    res.push_str(" Overall |\n|");
+    res.push_str(&"---|".repeat(scenarios.len() + 3));
+
+    for (index, s) in scores.iter().enumerate() {
+        let rank = s.score.map_or("-".to_string(), |_| (index + 1).to_string());
+        res.push_str(&format!("\n|{}|{}|", rank, s.framework_name));
+        for scenario in scenarios {
+            res.push_str(
+                &s.relative
+                    .get(scenario)
+                    .map_or("-".to_string(), |r| format!("{:.1}%", r * 100.0)),
+            );
+            res.push('|');
+        }
+        res.push_str(
+            &s.score
+                .map_or("-".to_string(), |score| format!("{:.1}", score)),
+        );
+        res.push('|');
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn report(name: &str, req_per_sec: f64) -> Report {
+        let output = format!(
+            "    Latency   814.27us  498.47us   8.42ms   69.23%\nRequests/sec: {:.2}\n",
+            req_per_sec
+        );
+
+        Report::new(name, 12.0, output.parse().expect("parse metric fail"))
+    }
+
+    #[test]
+    fn weighted() {
+        let config: Config = toml::from_str(
+            r#"
+            [weights]
+            json = 3
+            "#,
+        )
+        .expect("parse config fail");
+
+        let mut given = BTreeMap::new();
+        given.insert(
+            "hello-world".to_string(),
+            vec![report("actix-web", 500000.0), report("axum", 400000.0)],
+        );
+        given.insert(
+            "json".to_string(),
+            vec![report("actix-web", 200000.0), report("axum", 250000.0)],
+        );
+
+        let actual = composite_scores(&given, &config.weights);
+
+        assert_eq!(actual[0].framework_name, "axum");
+        insta::assert_snapshot!(scores_markdown(
+            &actual,
+            &given.keys().cloned().collect::<Vec<_>>(),
+            &config.weights
+        ));
+    }
+
+    #[test]
+    fn geometric() {
+        let mut given = BTreeMap::new();
+        given.insert(
+            "hello-world".to_string(),
+            vec![report("actix-web", 500000.0), report("axum", 125000.0)],
+        );
+        given.insert(
+            "json".to_string(),
+            vec![report("actix-web", 200000.0), report("axum", 200000.0)],
+        );
+
+        let actual = composite_scores(&given, &Weights::default());
+
+        assert_eq!(actual[0].score, Some(100.0));
+        assert_eq!(actual[1].score, Some(50.0));
+    }
+
+    #[test]
+    fn missing_scenario_is_not_ranked() {
+        let mut given = BTreeMap::new();
+        given.insert(
+            "hello-world".to_string(),
+            vec![report("actix-web", 500000.0), report("axum", 500000.0)],
+        );
+        given.insert("json".to_string(), vec![report("actix-web", 200000.0)]);
+
+        let actual = composite_scores(&given, &Weights::default());
+
+        assert_eq!(actual[0].score, Some(100.0));
+        assert_eq!(actual[1].framework_name, "axum");
+        assert_eq!(actual[1].score, None);
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory of the output the scripts of a run are copied to.
+pub const SCRIPTS_DIR: &str = "scripts";
+
+/// Copy the wrk scripts of `scripts`, paths relative to the workspace by
+/// scenario, to `<output_dir>/scripts/<scenario>.lua`, so published results
+/// keep the script they were measured with. wrk runs the copies, given as
+/// the paths returned.
+pub fn install(
+    scripts: &BTreeMap<String, PathBuf>,
+    workspace_dir: &Path,
+    output_dir: &Path,
+) -> Result<BTreeMap<String, String>, String> {
+    let dir = output_dir.join(SCRIPTS_DIR);
+    if !scripts.is_empty() {
+        fs::create_dir_all(&dir).map_err(|e| format!("creating {:?}: {}", dir, e))?;
+    }
+
+    scripts
+        .iter()
+        .map(|(scenario, source)| {
+            let source = workspace_dir.join(source);
+            let installed = dir.join(file_name(scenario));
+            fs::copy(&source, &installed)
+                .map_err(|e| format!("copying script of {} from {:?}: {}", scenario, source, e))?;
+            let installed = installed
+                .into_os_string()
+                .into_string()
+                .map_err(|installed| {
+                    format!(
+                        "script of {} is installed as {:?}, which isn't UTF-8 for wrk's arguments",
+                        scenario, installed
+                    )
+                })?;
+
+            Ok((scenario.clone(), installed))
+        })
+        .collect()
+}
+
+/// File name of the script of `scenario` below [`SCRIPTS_DIR`].
+pub fn file_name(scenario: &str) -> String {
+    format!("{}.lua", scenario)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn install() {
+        let dir = std::env::temp_dir().join("bench-bot-test-script");
+        fs::create_dir_all(dir.join("lua")).unwrap();
+        fs::write(dir.join("lua/post.lua"), "wrk.method = \"POST\"").unwrap();
+
+        let given = BTreeMap::from([("echo".to_string(), PathBuf::from("lua/post.lua"))]);
+        let actual = super::install(&given, &dir, &dir.join("output"));
+        let missing = super::install(
+            &BTreeMap::from([("json".to_string(), PathBuf::from("lua/missing.lua"))]),
+            &dir,
+            &dir.join("output"),
+        );
+
+        let installed = dir.join("output/scripts/echo.lua");
+        assert_eq!(
+            actual,
+            Ok(BTreeMap::from([(
+                "echo".to_string(),
+                installed.to_string_lossy().to_string()
+            )]))
+        );
+        assert_eq!(
+            fs::read_to_string(installed).unwrap(),
+            "wrk.method = \"POST\""
+        );
+        assert!(missing.is_err());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,143 @@
+use std::time::Duration;
+
+use crate::report::Metrics;
+use crate::units;
+
+/// Parse a latency objective, e.g. `5ms` or `500us`.
+pub fn parse(input: &str) -> Result<Duration, String> {
+    let invalid = |reason: &str| {
+        format!(
+            "invalid latency objective {:?} ({}), expected a duration like 5ms or 500us",
+            input, reason
+        )
+    };
+
+    match units::try_parse_duration(input) {
+        Ok(threshold) if threshold.is_zero() => Err(invalid("zero")),
+        Ok(threshold) => Ok(threshold),
+        Err(e) => Err(invalid(&e)),
+    }
+}
+
+/// Header of the column of `threshold`, e.g. `< 5ms`.
+pub fn header(threshold: Duration) -> String {
+    format!("< {}", label(threshold))
+}
+
+/// Header of the Apdex column of target `t`, e.g. `Apdex (T=5ms)`.
+pub fn apdex_header(t: Duration) -> String {
+    format!("Apdex (T={})", label(t))
+}
+
+fn label(threshold: Duration) -> String {
+    let ns = threshold.as_nanos() as f64;
+    let (value, unit) = match ns {
+        ns if ns >= 1e9 && ns % 1e9 == 0.0 => (ns / 1e9, "s"),
+        ns if ns >= 1e6 => (ns / 1e6, "ms"),
+        ns if ns >= 1e3 => (ns / 1e3, "us"),
+        ns => (ns, "ns"),
+    };
+
+    format!("{}{}", value, unit)
+}
+
+/// Percentage of requests faster than `threshold`, interpolated between the
+/// minimum, the percentiles and the maximum of the latency distribution.
+/// `None` for tools reporting no distribution.
+pub fn attainment(metrics: &Metrics, threshold: Duration) -> Option<f64> {
+    let latency = &metrics.latency;
+    if latency.p99.is_zero() {
+        return None;
+    }
+
+    let points = [
+        (latency.min, 0.0),
+        (latency.p50, 50.0),
+        (latency.p75, 75.0),
+        (latency.p90, 90.0),
+        (latency.p99, 99.0),
+        (latency.max.max(latency.p99), 100.0),
+    ];
+    let threshold = threshold.as_secs_f64();
+
+    let mut below = (0.0, 0.0);
+    for (latency, percent) in points {
+        let latency = latency.as_secs_f64();
+        if threshold < latency {
+            let (lower, lower_percent) = below;
+            return Some(
+                lower_percent + (percent - lower_percent) * (threshold - lower) / (latency - lower),
+            );
+        }
+        below = (latency, percent);
+    }
+
+    Some(100.0)
+}
+
+/// Apdex score of target `t`: requests within `t` count fully, those within
+/// four times `t` half, slower ones not at all.
+pub fn apdex(metrics: &Metrics, t: Duration) -> Option<f64> {
+    let satisfied = attainment(metrics, t)?;
+    let tolerated = attainment(metrics, t * 4)?;
+
+    Some((satisfied + tolerated) / 2.0 / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn headers() {
+        assert_eq!(header(Duration::from_millis(5)), "< 5ms");
+        assert_eq!(header(Duration::from_micros(500)), "< 500us");
+        assert_eq!(header(Duration::from_micros(1_500)), "< 1.5ms");
+        assert_eq!(header(Duration::from_secs(1)), "< 1s");
+        assert_eq!(apdex_header(Duration::from_millis(1)), "Apdex (T=1ms)");
+        assert!(parse("0ms").is_err());
+        assert!(parse("-1ms").is_err());
+        assert_eq!(parse("2.5ms"), Ok(Duration::from_micros(2_500)));
+    }
+
+    #[test]
+    fn interpolated() {
+        let given = r#"
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+  Latency Distribution
+     50%  707.00us
+     75%    1.07ms
+     90%    1.50ms
+     99%    2.56ms
+  17275966 requests in 30.09s, 1.95GB read
+Requests/sec: 574184.09
+        "#;
+        let metrics: Metrics = given.parse().expect("parse metric fail");
+        let at = |ms: f64| {
+            attainment(&metrics, Duration::from_secs_f64(ms / 1e3))
+                .map(|percent| (percent * 10.0).round() / 10.0)
+        };
+
+        assert_eq!(at(0.707), Some(50.0));
+        assert_eq!(at(1.0), Some(70.2));
+        assert_eq!(at(2.03), Some(94.5));
+        assert_eq!(at(5.0), Some(99.4));
+        assert_eq!(at(10.0), Some(100.0));
+
+        // 70.2% satisfied within 1ms, 99.2% within 4ms
+        assert_eq!(
+            apdex(&metrics, Duration::from_millis(1)).map(|score| (score * 100.0).round() / 100.0),
+            Some(0.85)
+        );
+
+        let without_distribution = given.replace("99%    2.56ms", "");
+        assert_eq!(
+            attainment(
+                &without_distribution.parse().unwrap(),
+                Duration::from_millis(5)
+            ),
+            None
+        );
+    }
+}
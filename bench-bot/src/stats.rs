@@ -0,0 +1,161 @@
+/// Resamples drawn for bootstrap confidence intervals.
+const BOOTSTRAP_RESAMPLES: usize = 2000;
+
+/// Fixed seed so that published intervals are reproducible.
+pub const BOOTSTRAP_SEED: u64 = 0x5eed_5eed_5eed_5eed;
+
+pub fn mean(samples: &[f64]) -> f64 {
+    samples.iter().sum::<f64>() / samples.len() as f64
+}
+
+/// Population standard deviation, `None` with fewer than two samples.
+pub fn std_dev(samples: &[f64]) -> Option<f64> {
+    if samples.len() < 2 {
+        return None;
+    }
+
+    let mean = mean(samples);
+    let variance = samples.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / samples.len() as f64;
+
+    Some(variance.sqrt())
+}
+
+/// Population standard deviation divided by the mean, `None` with fewer
+/// than two samples.
+pub fn coefficient_of_variation(samples: &[f64]) -> Option<f64> {
+    Some(std_dev(samples)? / mean(samples))
+}
+
+/// Letter grade of a coefficient of variation, A being the least noisy.
+pub fn noise_grade(cv: f64) -> char {
+    match cv {
+        cv if cv < 0.02 => 'A',
+        cv if cv < 0.05 => 'B',
+        cv if cv < 0.10 => 'C',
+        cv if cv < 0.20 => 'D',
+        _ => 'F',
+    }
+}
+
+/// Value below which `percent` of `samples` lie, interpolated between the
+/// closest two. `None` without samples.
+pub fn percentile(samples: &[f64], percent: f64) -> Option<f64> {
+    let mut sorted = samples.to_vec();
+    sorted.sort_by(f64::total_cmp);
+
+    let rank = (sorted.len().checked_sub(1)? as f64) * percent / 100.0;
+    let (lower, upper) = (sorted[rank.floor() as usize], sorted[rank.ceil() as usize]);
+
+    Some(lower + (upper - lower) * rank.fract())
+}
+
+/// Fewest samples a confidence interval is estimated from. Resampling two or
+/// three rounds finds a handful of means only, identical rounds would claim
+/// no uncertainty at all.
+pub const MIN_CI_SAMPLES: usize = 5;
+
+/// Bootstrap 95% confidence interval of the mean of `samples`, `None` with
+/// fewer than [`MIN_CI_SAMPLES`].
+pub fn bootstrap_ci(samples: &[f64]) -> Option<(f64, f64)> {
+    if samples.len() < MIN_CI_SAMPLES {
+        return None;
+    }
+
+    let mut rng = XorShift(BOOTSTRAP_SEED);
+    let mut means: Vec<f64> = (0..BOOTSTRAP_RESAMPLES)
+        .map(|_| {
+            let sum: f64 = (0..samples.len())
+                .map(|_| samples[rng.next() as usize % samples.len()])
+                .sum();
+            sum / samples.len() as f64
+        })
+        .collect();
+
+    means.sort_by(f64::total_cmp);
+
+    let lower = means[(BOOTSTRAP_RESAMPLES as f64 * 0.025) as usize];
+    let upper = means[(BOOTSTRAP_RESAMPLES as f64 * 0.975) as usize - 1];
+
+    Some((lower, upper))
+}
+
+/// Half width of the bootstrap 95% confidence interval relative to the mean
+/// of `samples`, `None` with fewer than [`MIN_CI_SAMPLES`].
+pub fn relative_half_width(samples: &[f64]) -> Option<f64> {
+    let (lower, upper) = bootstrap_ci(samples)?;
+
+    Some((upper - lower) / 2.0 / mean(samples))
+}
+
+/// xorshift64*, good enough for resampling and free of dependencies.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn few_samples_have_no_interval() {
+        assert_eq!(bootstrap_ci(&[574184.09]), None);
+        assert_eq!(bootstrap_ci(&[574184.09, 574184.09]), None);
+        assert_eq!(bootstrap_ci(&[1.0, 2.0, 3.0, 4.0]), None);
+    }
+
+    #[test]
+    fn interval_contains_mean() {
+        let samples = [574184.09, 568000.0, 580100.5, 571250.0, 576900.0];
+        let (lower, upper) = bootstrap_ci(&samples).unwrap();
+
+        assert!(lower < mean(&samples) && mean(&samples) < upper);
+        assert!(lower >= 568000.0 && upper <= 580100.5);
+    }
+
+    #[test]
+    fn percentiles() {
+        let samples = [40.0, 10.0, 30.0, 20.0];
+
+        assert_eq!(percentile(&samples, 0.0), Some(10.0));
+        assert_eq!(percentile(&samples, 50.0), Some(25.0));
+        assert_eq!(percentile(&samples, 100.0), Some(40.0));
+        assert_eq!(percentile(&[], 50.0), None);
+    }
+
+    #[test]
+    fn identical_samples() {
+        assert_eq!(bootstrap_ci(&[2.5; MIN_CI_SAMPLES]), Some((2.5, 2.5)));
+    }
+
+    #[test]
+    fn coefficient_of_variation_and_grade() {
+        assert_eq!(coefficient_of_variation(&[1.0]), None);
+        assert_eq!(coefficient_of_variation(&[90.0, 110.0]), Some(0.1));
+        assert_eq!(noise_grade(0.01), 'A');
+        assert_eq!(noise_grade(0.049), 'B');
+        assert_eq!(noise_grade(0.1), 'D');
+        assert_eq!(noise_grade(0.5), 'F');
+    }
+
+    #[test]
+    fn relative_width() {
+        assert_eq!(relative_half_width(&[2.5; MIN_CI_SAMPLES]), Some(0.0));
+        assert_eq!(relative_half_width(&[2.5]), None);
+        assert!(relative_half_width(&[90.0, 110.0, 95.0, 105.0, 100.0])
+            .is_some_and(|width| width > 0.02 && width < 0.1));
+    }
+
+    #[test]
+    fn reproducible() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0];
+
+        assert_eq!(bootstrap_ci(&samples), bootstrap_ci(&samples));
+    }
+}
@@ -0,0 +1,100 @@
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// File in the output directory the running bot keeps its [`Status`] in.
+pub const STATUS_FILE: &str = "status.json";
+
+/// File in the output directory the log of the latest run is copied to.
+pub const LOG_FILE: &str = "bench-bot.log";
+
+/// Progress of the current or latest run.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Status {
+    /// `building`, `benchmarking`, `reporting`, `uploading` or `finished`.
+    pub phase: String,
+    /// Implementation the phase is working on.
+    pub current: Option<String>,
+    /// Implementations still waiting for the phase.
+    pub queue: Vec<String>,
+    pub done: Vec<String>,
+    /// Unix timestamp in seconds of the last update.
+    pub updated: u64,
+}
+
+impl Status {
+    /// Enter `phase` working on `current`, and save the status to `dir`.
+    pub fn update(&mut self, dir: &Path, phase: &str, current: Option<&str>) {
+        self.phase = phase.to_string();
+        self.current = current.map(str::to_string);
+        if let Some(current) = current {
+            self.queue.retain(|member| member != current);
+        }
+        self.updated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        // the status is informational, a run must not fail on it
+        if let Err(e) = serde_json::to_vec_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                fs::write(dir.join(STATUS_FILE), content).map_err(|e| e.to_string())
+            })
+        {
+            log::warn!("Writing status failed: {}", e);
+        }
+    }
+
+    pub fn load(dir: &Path) -> Option<Self> {
+        fs::read(dir.join(STATUS_FILE))
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+    }
+}
+
+/// Log writer copying everything to stderr and a file.
+pub struct Tee(pub File);
+
+impl Write for Tee {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stderr().write_all(buf)?;
+        self.0.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()?;
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn update() {
+        let dir = env::temp_dir().join("bench-bot-test-status");
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut status = Status {
+            queue: vec![
+                "hello-world/axum".to_string(),
+                "hello-world/tide".to_string(),
+            ],
+            ..Status::default()
+        };
+        status.update(&dir, "benchmarking", Some("hello-world/axum"));
+
+        let actual = Status::load(&dir).expect("load status fail");
+
+        assert_eq!(actual.phase, "benchmarking");
+        assert_eq!(actual.current.as_deref(), Some("hello-world/axum"));
+        assert_eq!(actual.queue, vec!["hello-world/tide".to_string()]);
+    }
+}
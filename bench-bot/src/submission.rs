@@ -0,0 +1,370 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::compare::Results;
+use crate::environment::Environment;
+use crate::html::escape;
+use crate::report::Report;
+use crate::stats;
+
+/// Directory of the output directory community submissions are stored in,
+/// apart from the official results.
+pub const SUBMISSIONS_DIR: &str = "submissions";
+
+/// Most submissions stored across scenarios, anyone reaching the dashboard
+/// may post them.
+const MAX_SUBMISSIONS: usize = 10_000;
+
+/// Results of a scenario measured on someone's own hardware, as sent by
+/// `submit`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Submission {
+    pub scenario: String,
+    /// See [`Environment::machine_profile`].
+    pub machine: Environment,
+    pub reports: Vec<Report>,
+}
+
+impl Submission {
+    /// Submission of the results of `scenario`, leaving out everything about
+    /// the machine but its profile.
+    pub fn from_results(scenario: &str, results: Results) -> Self {
+        Self {
+            scenario: scenario.to_string(),
+            machine: results.environment.machine_profile(),
+            reports: results.reports,
+        }
+    }
+}
+
+/// Post `submission` to the dashboard at `url`, with curl.
+pub fn send(url: &str, submission: &Submission) -> Result<(), String> {
+    let body = serde_json::to_vec(submission).map_err(|e| e.to_string())?;
+
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--header",
+            "Content-Type: application/json",
+            "--data-binary",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("running curl: {}", e))?;
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(&body)
+        .map_err(|e| format!("writing to curl: {}", e))?;
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("running curl: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "submitting to {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Store the submission in `body` below `output_dir`, its scenario on
+/// success.
+pub fn accept(output_dir: &Path, body: &[u8]) -> Result<String, String> {
+    let submission: Submission =
+        serde_json::from_slice(body).map_err(|e| format!("invalid submission: {}", e))?;
+    if !valid_scenario(&submission.scenario) {
+        return Err(format!("invalid scenario {:?}", submission.scenario));
+    }
+    if submission.reports.is_empty() {
+        return Err("submission without reports".to_string());
+    }
+    // whatever the client sent, nothing naming the machine is stored
+    let submission = Submission {
+        machine: submission.machine.machine_profile(),
+        ..submission
+    };
+
+    let dir = output_dir.join(SUBMISSIONS_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("creating {:?}: {}", dir, e))?;
+
+    let mut line = serde_json::to_string(&submission).map_err(|e| e.to_string())?;
+    line.push('\n');
+    let path = dir.join(format!("{}.jsonl", submission.scenario));
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("writing {:?}: {}", path, e))?;
+
+    Ok(submission.scenario)
+}
+
+/// Whether [`MAX_SUBMISSIONS`] are stored below `output_dir`.
+pub fn is_full(output_dir: &Path) -> bool {
+    stored(output_dir) >= MAX_SUBMISSIONS
+}
+
+fn stored(output_dir: &Path) -> usize {
+    scenarios(output_dir)
+        .iter()
+        .filter_map(|scenario| {
+            fs::read_to_string(
+                output_dir
+                    .join(SUBMISSIONS_DIR)
+                    .join(format!("{}.jsonl", scenario)),
+            )
+            .ok()
+        })
+        .map(|content| content.lines().count())
+        .sum()
+}
+
+// names only, the scenario becomes a file name
+fn valid_scenario(scenario: &str) -> bool {
+    !scenario.is_empty()
+        && scenario
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Scenarios with submissions below `output_dir`.
+pub fn scenarios(output_dir: &Path) -> Vec<String> {
+    let mut scenarios: Vec<String> = fs::read_dir(output_dir.join(SUBMISSIONS_DIR))
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    entry
+                        .file_name()
+                        .to_string_lossy()
+                        .strip_suffix(".jsonl")
+                        .map(str::to_string)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    scenarios.sort();
+
+    scenarios
+}
+
+/// Submissions of `scenario`, `None` when there are none or the name is
+/// invalid. Lines failing to parse are skipped.
+pub fn load(output_dir: &Path, scenario: &str) -> Option<Vec<Submission>> {
+    if !valid_scenario(scenario) {
+        return None;
+    }
+    let content = fs::read_to_string(
+        output_dir
+            .join(SUBMISSIONS_DIR)
+            .join(format!("{}.jsonl", scenario)),
+    )
+    .ok()?;
+
+    Some(
+        content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect(),
+    )
+}
+
+/// Req/Sec of a framework across submissions.
+#[derive(Debug, PartialEq)]
+pub struct Distribution {
+    pub framework_name: String,
+    pub submissions: usize,
+    /// Distinct CPU models it was measured on.
+    pub machines: usize,
+    pub min: f64,
+    pub p25: f64,
+    pub median: f64,
+    pub p75: f64,
+    pub max: f64,
+}
+
+/// Distribution of every framework of `submissions`, fastest median first.
+pub fn distributions(submissions: &[Submission]) -> Vec<Distribution> {
+    let mut samples: BTreeMap<&str, (Vec<f64>, BTreeSet<Option<&str>>)> = BTreeMap::new();
+    for submission in submissions {
+        for report in &submission.reports {
+            let (values, machines) = samples.entry(report.framework_name()).or_default();
+            values.push(report.metrics().req_per_sec());
+            machines.insert(submission.machine.get("cpu"));
+        }
+    }
+
+    let mut distributions: Vec<Distribution> = samples
+        .into_iter()
+        .filter_map(|(name, (values, machines))| {
+            Some(Distribution {
+                framework_name: name.to_string(),
+                submissions: values.len(),
+                machines: machines.len(),
+                min: stats::percentile(&values, 0.0)?,
+                p25: stats::percentile(&values, 25.0)?,
+                median: stats::percentile(&values, 50.0)?,
+                p75: stats::percentile(&values, 75.0)?,
+                max: stats::percentile(&values, 100.0)?,
+            })
+        })
+        .collect();
+    distributions.sort_by(|a, b| b.median.total_cmp(&a.median));
+
+    distributions
+}
+
+/// Page of the Req/Sec distribution of every framework of `scenario`
+/// submitted by the community, with a box of the middle half on a shared
+/// scale.
+pub fn page(scenario: &str, distributions: &[Distribution]) -> String {
+    let scale = distributions
+        .iter()
+        .map(|d| d.max)
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let percent = |value: f64| value / scale * 100.0;
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{0} community results - bench-bot</title>\n</head>\n<body>\n<h1>{0}</h1>\n\
+         <p>Req/Sec submitted by the community from their own hardware, \
+         apart from the official results.</p>\n",
+        escape(scenario)
+    );
+    html.push_str(
+        "<table>\n<tr><th>Framework Name</th><th>Submissions</th><th>Machines</th>\
+         <th>Min</th><th>25%</th><th>Median</th><th>75%</th><th>Max</th><th></th></tr>\n",
+    );
+    for d in distributions {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td>{}</td><td>{}</td>\
+             <td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td><td>{:.0}</td>\
+             <td><div style=\"position: relative; width: 20em; height: 1em; background: #f4f4f4\">\
+             <div style=\"position: absolute; left: {:.1}%; width: {:.1}%; height: 100%; \
+             background: #4a90d9\"></div></div></td></tr>\n",
+            escape(&d.framework_name),
+            d.submissions,
+            d.machines,
+            d.min,
+            d.p25,
+            d.median,
+            d.p75,
+            d.max,
+            percent(d.p25),
+            percent(d.p75) - percent(d.p25)
+        ));
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    fn submission(cpu: &str, reports: &[(&str, &str)]) -> Submission {
+        let output = |req_per_sec: &str| {
+            format!(
+                "    Latency   814.27us  498.47us   8.42ms   69.23%\n  17275966 requests in \
+                    30.09s, 1.95GB read\nRequests/sec: {}\nTransfer/sec:     66.26MB\n",
+                req_per_sec
+            )
+        };
+
+        Submission {
+            scenario: "hello-world".to_string(),
+            machine: serde_json::from_value(serde_json::json!({ "cpu": cpu })).unwrap(),
+            reports: reports
+                .iter()
+                .map(|(name, req_per_sec)| {
+                    Report::new(
+                        name,
+                        13.7,
+                        output(req_per_sec).parse().expect("parse metric fail"),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn stored() {
+        let dir = env::temp_dir().join("bench-bot-test-submissions");
+        let _ = fs::remove_dir_all(&dir);
+
+        let mut given = submission("AMD EPYC 7B13", &[("axum", "574184.09")]);
+        given.machine = serde_json::from_value(
+            serde_json::json!({ "cpu": "AMD EPYC 7B13", "hostname": "alice-desktop" }),
+        )
+        .unwrap();
+        let body = serde_json::to_vec(&given).unwrap();
+        assert_eq!(accept(&dir, &body), Ok("hello-world".to_string()));
+        assert_eq!(scenarios(&dir), ["hello-world"]);
+        let stored = load(&dir, "hello-world").unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].machine.get("hostname"), None);
+        assert_eq!(super::stored(&dir), 1);
+        assert!(!is_full(&dir));
+
+        let mut escaping = submission("AMD EPYC 7B13", &[("axum", "574184.09")]);
+        escaping.scenario = "../hello-world".to_string();
+        assert!(accept(&dir, &serde_json::to_vec(&escaping).unwrap()).is_err());
+        assert!(accept(&dir, b"{}").is_err());
+
+        let mut overflowing = serde_json::to_value(&given).unwrap();
+        *overflowing
+            .pointer_mut("/reports/0/metrics/latency/avg")
+            .unwrap() = serde_json::json!(1e300);
+        assert!(accept(&dir, &serde_json::to_vec(&overflowing).unwrap()).is_err());
+        assert!(load(&dir, "../hello-world").is_none());
+    }
+
+    #[test]
+    fn distribution() {
+        let given = [
+            submission("AMD EPYC 7B13", &[("axum", "500000"), ("tide", "100000")]),
+            submission("Apple M1", &[("axum", "300000")]),
+            submission("AMD EPYC 7B13", &[("axum", "400000")]),
+        ];
+
+        let actual = distributions(&given);
+
+        assert_eq!(
+            actual[0],
+            Distribution {
+                framework_name: "axum".to_string(),
+                submissions: 3,
+                machines: 2,
+                min: 300000.0,
+                p25: 350000.0,
+                median: 400000.0,
+                p75: 450000.0,
+                max: 500000.0,
+            }
+        );
+        assert_eq!(actual[1].framework_name, "tide");
+        insta::assert_snapshot!(page("hello-world", &actual));
+    }
+}
@@ -0,0 +1,70 @@
+use std::time::Duration;
+
+/// Self-describing preamble of a published run.
+#[derive(Debug)]
+pub struct RunSummary {
+    pub frameworks: usize,
+    pub scenarios: Vec<String>,
+    pub failures: usize,
+    pub duration: Duration,
+    pub environment: String,
+    pub command_line: String,
+}
+
+impl RunSummary {
+    pub fn to_markdown(&self) -> String {
+        [
+            format!("- Frameworks: {}", self.frameworks),
+            format!(
+                "- Scenarios: {} ({})",
+                self.scenarios.len(),
+                self.scenarios.join(", ")
+            ),
+            format!("- Failures: {}", self.failures),
+            format!("- Duration: {}", format_duration(self.duration)),
+            format!("- Environment: {}", self.environment),
+            format!("- Command Line: `{}`", self.command_line),
+        ]
+        .join("\n")
+    }
+}
+
+/// Format a wall-clock duration as e.g. `1h 2m 3s`.
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3600, secs / 60 % 60, secs % 60);
+
+    if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration() {
+        assert_eq!(format_duration(Duration::from_secs(42)), "42s");
+        assert_eq!(format_duration(Duration::from_secs(452)), "7m 32s");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1h 2m 3s");
+    }
+
+    #[test]
+    fn markdown() {
+        let given = RunSummary {
+            frameworks: 12,
+            scenarios: vec!["hello-world".to_string()],
+            failures: 1,
+            duration: Duration::from_secs(452),
+            environment: "AMD EPYC 7B13 (16 CPUs), Linux 6.1.0".to_string(),
+            command_line: "bench-bot -w ../benchmark -o ../result".to_string(),
+        };
+
+        insta::assert_snapshot!(given.to_markdown());
+    }
+}
@@ -0,0 +1,94 @@
+use comfy_table::presets::UTF8_FULL_CONDENSED;
+use comfy_table::{Attribute, Cell, CellAlignment, Color, Table};
+
+use crate::column::Column;
+use crate::report::{Report, ReportOptions, SKEWED_THREAD_BALANCE};
+
+/// Render reports as an aligned table for interactive terminals.
+///
+/// The fastest framework is highlighted green and skewed thread balance red,
+/// colors are dropped when `styled` is false or stdout is not a tty.
+pub fn render(reports: &[Report], options: &ReportOptions, styled: bool) -> String {
+    let columns = &options.columns;
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL_CONDENSED);
+
+    if styled {
+        table.enforce_styling();
+    } else {
+        table.force_no_tty();
+    }
+
+    table.set_header(
+        columns
+            .iter()
+            .map(|column| Cell::new(column.header()).add_attribute(Attribute::Bold)),
+    );
+
+    let best_req_per_sec = reports
+        .iter()
+        .map(|r| r.metrics().req_per_sec())
+        .fold(0.0, f64::max);
+
+    for r in Report::sorted(reports, options.sort) {
+        table.add_row(columns.iter().map(|column| {
+            let mut cell = Cell::new(r.render_cell(reports, *column, options));
+
+            if *column != Column::FrameworkName {
+                cell = cell.set_alignment(CellAlignment::Right);
+            }
+
+            match column {
+                Column::RequestReqPerSec
+                    if best_req_per_sec > 0.0 && r.metrics().req_per_sec() == best_req_per_sec =>
+                {
+                    cell.fg(Color::Green).add_attribute(Attribute::Bold)
+                }
+                Column::ThreadBalance
+                    if r.metrics().thread_balance().unwrap_or(0.0) > SKEWED_THREAD_BALANCE =>
+                {
+                    cell.fg(Color::Red)
+                }
+                _ => cell,
+            }
+        }));
+    }
+
+    table.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::column::ColumnPreset;
+
+    #[test]
+    fn render_plain() {
+        let given = vec![Report::new(
+            "actix-web",
+            13.7,
+            r#"
+  Thread Stats   Avg      Stdev     Max   +/- Stdev
+    Latency   814.27us  498.47us   8.42ms   69.23%
+    Req/Sec    36.10k     2.64k   74.83k    75.41%
+  Latency Distribution
+     50%  707.00us
+     75%    1.07ms
+     90%    1.50ms
+     99%    2.56ms
+  17275966 requests in 30.09s, 1.95GB read
+Requests/sec: 574184.09
+Transfer/sec:     66.26MB
+        "#
+            .parse()
+            .expect("parse metric fail"),
+        )];
+
+        let options = ReportOptions {
+            columns: ColumnPreset::Compact.columns().to_vec(),
+            ..ReportOptions::default()
+        };
+
+        insta::assert_snapshot!(render(&given, &options, false));
+    }
+}
@@ -0,0 +1,33 @@
+-- wrk script counting responses per second of the run. `done` prints one
+-- `timeline <second> <responses>` line per second for bench-bot to parse.
+local threads = {}
+
+function setup(thread)
+  table.insert(threads, thread)
+end
+
+function init(args)
+  counts = {}
+  start = os.time()
+end
+
+function response(status, headers, body)
+  local second = os.time() - start
+  counts[second] = (counts[second] or 0) + 1
+end
+
+function done(summary, latency, requests)
+  local totals = {}
+  local last = -1
+
+  for _, thread in ipairs(threads) do
+    for second, count in pairs(thread:get("counts")) do
+      totals[second] = (totals[second] or 0) + count
+      last = math.max(last, second)
+    end
+  end
+
+  for second = 0, last do
+    io.write(string.format("timeline %d %d\n", second, totals[second] or 0))
+  end
+end
@@ -0,0 +1,93 @@
+use std::fmt::Write;
+
+/// wrk script recording achieved Req/Sec for every second of the run.
+pub const SCRIPT: &str = include_str!("timeline.lua");
+
+const WIDTH: f64 = 640.0;
+const HEIGHT: f64 = 240.0;
+const MARGIN: f64 = 40.0;
+
+/// Responses of each second as printed by [`SCRIPT`], empty when the run
+/// was not scripted.
+pub fn parse(output: &str) -> Vec<u64> {
+    let mut points = Vec::new();
+
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("timeline") {
+            continue;
+        }
+        let (Some(second), Some(count)) = (
+            fields.next().and_then(|f| f.parse::<usize>().ok()),
+            fields.next().and_then(|f| f.parse::<u64>().ok()),
+        ) else {
+            continue;
+        };
+
+        if points.len() <= second {
+            points.resize(second + 1, 0);
+        }
+        points[second] = count;
+    }
+
+    points
+}
+
+/// SVG line chart of Req/Sec over the seconds of a run. The first and last
+/// second are usually partial and show as dips.
+pub fn svg(points: &[u64]) -> String {
+    let max = points.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let step = if points.len() > 1 {
+        (WIDTH - 2.0 * MARGIN) / (points.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    let mut polyline = String::new();
+    for (second, count) in points.iter().enumerate() {
+        let x = MARGIN + second as f64 * step;
+        let y = HEIGHT - MARGIN - *count as f64 / max * (HEIGHT - 2.0 * MARGIN);
+        let _ = write!(polyline, "{:.1},{:.1} ", x, y);
+    }
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" font-family="sans-serif" font-size="12">
+<rect width="{w}" height="{h}" fill="white"/>
+<line x1="{m}" y1="{b}" x2="{r}" y2="{b}" stroke="black"/>
+<line x1="{m}" y1="{m}" x2="{m}" y2="{b}" stroke="black"/>
+<text x="{m}" y="{t}">{max} req/s</text>
+<text x="{r}" y="{l}" text-anchor="end">{seconds}s</text>
+<polyline fill="none" stroke="#1f77b4" stroke-width="2" points="{points}"/>
+</svg>
+"##,
+        w = WIDTH,
+        h = HEIGHT,
+        m = MARGIN,
+        b = HEIGHT - MARGIN,
+        r = WIDTH - MARGIN,
+        t = MARGIN - 8.0,
+        l = HEIGHT - MARGIN + 16.0,
+        max = max,
+        seconds = points.len(),
+        points = polyline.trim_end(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_output() {
+        let given =
+            "Requests/sec: 574184.09\ntimeline 0 281000\ntimeline 1 574000\ntimeline 3 12\n";
+
+        assert_eq!(parse(given), vec![281000, 574000, 0, 12]);
+        assert_eq!(parse("Requests/sec: 574184.09"), Vec::<u64>::new());
+    }
+
+    #[test]
+    fn chart() {
+        insta::assert_snapshot!(svg(&[281000, 574000, 570000, 300000]));
+    }
+}
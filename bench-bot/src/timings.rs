@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Wall-clock seconds the harness spent in each phase of one framework.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct PhaseTimings {
+    /// `cargo build --release`.
+    pub build: f64,
+    /// Starting the server and waiting for it to come up.
+    pub provision: f64,
+    /// Load generation, every round.
+    pub measure: f64,
+    /// Stopping the server and collecting memory samples.
+    pub teardown: f64,
+    /// Parsing the load tool output and writing the detail page.
+    pub parse: f64,
+}
+
+impl PhaseTimings {
+    pub fn total(&self) -> f64 {
+        self.build + self.provision + self.measure + self.teardown + self.parse
+    }
+
+    /// Time spent on anything but measuring.
+    pub fn overhead(&self) -> f64 {
+        self.total() - self.measure
+    }
+}
+
+pub fn secs(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1000.0).round() / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overhead() {
+        let given = PhaseTimings {
+            build: 40.0,
+            provision: 1.0,
+            measure: 30.0,
+            teardown: 0.5,
+            parse: 0.25,
+        };
+
+        assert_eq!(given.total(), 71.75);
+        assert_eq!(given.overhead(), 41.75);
+        assert_eq!(secs(Duration::from_micros(1_234_567)), 1.235);
+    }
+}
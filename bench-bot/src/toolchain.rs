@@ -0,0 +1,54 @@
+use std::process::Command;
+
+/// Install `channel` with rustup if needed and return its `rustc --version`.
+/// Fails when rustup is unavailable or a version pinned like `1.75.0`
+/// resolves to a different rustc.
+pub fn install(channel: &str) -> Result<String, String> {
+    let output = Command::new("rustup")
+        .args(["toolchain", "install", channel, "--profile", "minimal"])
+        .output()
+        .map_err(|e| format!("running rustup: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "installing toolchain {}: {}",
+            channel,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let output = Command::new("rustup")
+        .args(["run", channel, "rustc", "--version"])
+        .output()
+        .map_err(|e| format!("running rustup: {}", e))?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if drifted(channel, &version) {
+        return Err(format!("toolchain {} drifted, found {}", channel, version));
+    }
+
+    Ok(version)
+}
+
+// only numeric channels pin a version, `stable` or `nightly-2024-01-01` can't be checked
+fn drifted(channel: &str, version: &str) -> bool {
+    let numeric = channel.chars().all(|c| c.is_ascii_digit() || c == '.');
+
+    numeric
+        && !version.starts_with(&format!("rustc {} ", channel))
+        && !version.starts_with(&format!("rustc {}.", channel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drift() {
+        assert!(!drifted("1.75.0", "rustc 1.75.0 (82e1608df 2023-12-21)"));
+        assert!(!drifted("1.75", "rustc 1.75.0 (82e1608df 2023-12-21)"));
+        assert!(drifted("1.75.0", "rustc 1.76.0 (07dca489a 2024-02-04)"));
+        assert!(drifted("1.7", "rustc 1.75.0 (82e1608df 2023-12-21)"));
+        assert!(!drifted("stable", "rustc 1.76.0 (07dca489a 2024-02-04)"));
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+
+use crate::history::RunRecord;
+
+/// (commit, req/sec) points of one framework in chronological order.
+type Series<'a> = Vec<(Option<&'a str>, f64)>;
+
+/// A sudden change of a framework's throughput between two runs.
+#[derive(Debug, PartialEq)]
+pub struct Cliff {
+    pub scenario: String,
+    pub framework_name: String,
+    /// Commit before and after the change.
+    pub from_commit: Option<String>,
+    pub to_commit: Option<String>,
+    pub before: f64,
+    pub after: f64,
+    pub z_score: f64,
+}
+
+/// Run a rolling z-score over each framework's Req/Sec series and report
+/// points deviating more than `threshold` standard deviations from the
+/// preceding `window` runs.
+pub fn find_cliffs(records: &[RunRecord], window: usize, threshold: f64) -> Vec<Cliff> {
+    let mut series: BTreeMap<(&str, &str), Series> = BTreeMap::new();
+
+    for record in records.iter().filter(|r| !r.is_replicate()) {
+        // an aborted or cut off measurement would read as a cliff
+        for report in record.reports.iter().filter(|r| r.is_reliable()) {
+            series
+                .entry((&record.scenario, report.framework_name()))
+                .or_default()
+                .push((record.commit.as_deref(), report.metrics().req_per_sec()));
+        }
+    }
+
+    let window = window.max(2);
+    let mut cliffs = Vec::new();
+
+    for ((scenario, framework_name), points) in series {
+        for index in window..points.len() {
+            let previous = &points[index - window..index];
+            let mean = previous.iter().map(|(_, v)| v).sum::<f64>() / previous.len() as f64;
+            let variance = previous
+                .iter()
+                .map(|(_, v)| (v - mean).powi(2))
+                .sum::<f64>()
+                / previous.len() as f64;
+            // a perfectly flat history would flag any noise, assume at least 1% deviation
+            let stdev = variance.sqrt().max(mean.abs() * 0.01);
+
+            let (commit, value) = points[index];
+            let z_score = if stdev > 0.0 {
+                (value - mean) / stdev
+            } else {
+                0.0
+            };
+
+            if z_score.abs() > threshold {
+                cliffs.push(Cliff {
+                    scenario: scenario.to_string(),
+                    framework_name: framework_name.to_string(),
+                    from_commit: points[index - 1].0.map(str::to_string),
+                    to_commit: commit.map(str::to_string),
+                    before: mean,
+                    after: value,
+                    z_score,
+                });
+            }
+        }
+    }
+
+    cliffs
+}
+
+/// Markdown table listing `cliffs`.
+pub fn cliffs_table(cliffs: &[Cliff]) -> String {
+    let mut res = String::from(
+        "| Scenario | Framework Name | Commits | Req/Sec Before | Req/Sec \
+        After | Z-Score |\n|---|---|---|---|---|---|",
+    );
+
+    for c in cliffs {
+        res.push_str(&format!(
+            "\n|{}|{}|{}..{}|{:.2}|{:.2}|{:.1}|",
+            c.scenario,
+            c.framework_name,
+            c.from_commit.as_deref().unwrap_or("?"),
+            c.to_commit.as_deref().unwrap_or("?"),
+            c.before,
+            c.after,
+            c.z_score
+        ));
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history;
+    use crate::report::Report;
+    use std::{env, fs};
+
+    fn record(timestamp: u64, commit: &str, req_per_sec: f64) -> RunRecord {
+        let output = format!(
+            "    Latency   814.27us  498.47us   8.42ms   69.23%\nRequests/sec: {:.2}\n",
+            req_per_sec
+        );
+
+        RunRecord {
+            id: timestamp.to_string(),
+            timestamp,
+            commit: Some(commit.to_string()),
+            scenario: "hello-world".to_string(),
+            reports: vec![Report::new(
+                "axum",
+                12.0,
+                output.parse().expect("parse metric fail"),
+            )],
+            failures: 0,
+            fingerprint: None,
+            replicate_of: None,
+            machine: None,
+        }
+    }
+
+    #[test]
+    fn cliff() {
+        let given = vec![
+            record(1, "a1", 550000.0),
+            record(2, "b2", 552000.0),
+            record(3, "c3", 548000.0),
+            record(4, "d4", 551000.0),
+            record(5, "e5", 420000.0),
+            record(6, "f6", 421000.0),
+        ];
+
+        let actual = find_cliffs(&given, 4, 3.0);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].from_commit.as_deref(), Some("d4"));
+        assert_eq!(actual[0].to_commit.as_deref(), Some("e5"));
+        insta::assert_snapshot!(cliffs_table(&actual));
+    }
+
+    #[test]
+    fn scenarios_are_separate_series() {
+        let path = env::temp_dir().join("bench-bot-test-scenario-series.jsonl");
+        let _ = fs::remove_file(&path);
+
+        for timestamp in 1..=6 {
+            let reports = vec![
+                record(timestamp, "a1", 550000.0)
+                    .reports
+                    .remove(0)
+                    .with_scenario("hello-world"),
+                record(timestamp, "a1", 120000.0)
+                    .reports
+                    .remove(0)
+                    .with_scenario("json"),
+            ];
+            for scenario in ["hello-world", "json"] {
+                let record = RunRecord {
+                    scenario: scenario.to_string(),
+                    reports: history::of_scenario(&reports, scenario),
+                    ..record(timestamp, "a1", 0.0)
+                };
+                history::append(&path, &record).unwrap();
+            }
+        }
+        let given = history::load(&path).unwrap();
+
+        assert!(given.iter().all(|r| r.reports.len() == 1));
+        assert_eq!(find_cliffs(&given, 4, 3.0), Vec::new());
+    }
+
+    #[test]
+    fn noise_is_not_a_cliff() {
+        let given = vec![
+            record(1, "a1", 550000.0),
+            record(2, "b2", 552000.0),
+            record(3, "c3", 548000.0),
+            record(4, "d4", 551000.0),
+            record(5, "e5", 549000.0),
+        ];
+
+        assert_eq!(find_cliffs(&given, 4, 3.0), Vec::new());
+    }
+
+    #[test]
+    fn incomplete_is_not_a_cliff() {
+        let mut given = vec![
+            record(1, "a1", 550000.0),
+            record(2, "b2", 552000.0),
+            record(3, "c3", 548000.0),
+            record(4, "d4", 551000.0),
+            record(5, "e5", 120000.0),
+        ];
+        given[4].reports = given[4]
+            .reports
+            .drain(..)
+            .map(Report::with_incomplete)
+            .collect();
+
+        assert_eq!(find_cliffs(&given, 4, 3.0), Vec::new());
+    }
+}
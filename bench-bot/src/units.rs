@@ -0,0 +1,311 @@
+use std::time::Duration;
+
+/// Parse a number the way different load tools and locales print it.
+///
+/// Accepts thousands separators (`17,275,966`, `17 275 966`, `17'275'966`),
+/// a decimal comma (`574184,09`, `574.184,09`) and `k`/`m`/`g` magnitude
+/// suffixes as printed by wrk's thread stats (`36.10k`).
+pub fn parse_number(input: &str) -> Option<f64> {
+    let input = input.trim();
+
+    let (digits, multiplier) = match input.chars().last()? {
+        'k' | 'K' => (&input[..input.len() - 1], 1e3),
+        'm' | 'M' => (&input[..input.len() - 1], 1e6),
+        'g' | 'G' => (&input[..input.len() - 1], 1e9),
+        _ => (input, 1.0),
+    };
+
+    let digits: String = digits
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '\'' | '\u{a0}' | '\u{202f}'))
+        .collect();
+
+    let decimal_separator = match (digits.rfind('.'), digits.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(if dot > comma { '.' } else { ',' }),
+        (Some(_), None) => Some('.'),
+        // a lone comma followed by exactly three digits is a thousands separator
+        (None, Some(comma))
+            if digits.matches(',').count() == 1 && digits.len() - comma - 1 != 3 =>
+        {
+            Some(',')
+        }
+        _ => None,
+    };
+
+    let normalized: String = digits
+        .chars()
+        .filter_map(|c| match c {
+            '.' | ',' if Some(c) == decimal_separator => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect();
+
+    normalized
+        .parse::<f64>()
+        .ok()
+        .map(|value| value * multiplier)
+}
+
+/// Parse a duration as printed by wrk, e.g. `814.27us`, `1.07ms` or `2.00s`,
+/// or by humanized reports, e.g. `814µs`.
+pub fn parse_duration(input: &str) -> Option<Duration> {
+    try_parse_duration(input).ok()
+}
+
+/// [`parse_duration`] telling why `input` is not a duration, for input of
+/// users.
+pub fn try_parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let unit_start = input.find(char::is_alphabetic).ok_or("no unit")?;
+    let (value, unit) = input.split_at(unit_start);
+
+    let seconds = match unit {
+        "ns" => 1e-9,
+        "us" | "µs" => 1e-6,
+        "ms" => 1e-3,
+        "s" => 1.0,
+        "m" => 60.0,
+        "h" => 3600.0,
+        _ => return Err(format!("unknown unit {:?}", unit)),
+    };
+
+    let value = parse_number(value).ok_or_else(|| format!("{:?} is not a number", value))?;
+    if value < 0.0 {
+        return Err("negative".to_string());
+    }
+    Duration::try_from_secs_f64(value * seconds).map_err(|_| "out of range".to_string())
+}
+
+/// Parse a byte size as printed by wrk, e.g. `1.95GB`.
+///
+/// wrk prints binary units without the `i`, so `GiB` and `GB` are treated the
+/// same and both scaled by powers of 1024.
+pub fn parse_bytes(input: &str) -> Option<u64> {
+    scaled_bytes(input, 0).map(|bytes| bytes.round() as u64)
+}
+
+/// Parse a memory size as printed by [`NumberFormat::memory`], e.g. `12.4MB`
+/// or `1.2GB`, in MB.
+///
+/// [`NumberFormat::memory`]: crate::format::NumberFormat::memory
+pub fn parse_megabytes(input: &str) -> Option<f64> {
+    scaled_bytes(input, 2)
+}
+
+// `input` in units of 1024 to the power of `exponent` bytes
+fn scaled_bytes(input: &str, exponent: i32) -> Option<f64> {
+    let input = input.trim();
+    let unit_start = input.find(|c: char| c.is_ascii_alphabetic())?;
+    let (value, unit) = input.split_at(unit_start);
+
+    let unit_exponent = BYTE_UNITS
+        .iter()
+        .position(|known| unit.replace('i', "").eq_ignore_ascii_case(known))?;
+
+    Some(parse_number(value)? * 1024f64.powi(unit_exponent as i32 - exponent))
+}
+
+/// Units of [`parse_bytes`] and [`scale_bytes`] by power of 1024.
+const BYTE_UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+/// A byte size scaled to the largest unit below it and its unit, the way
+/// wrk prints it.
+pub fn scale_bytes(bytes: f64) -> (f64, &'static str) {
+    let mut value = bytes;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < BYTE_UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    (value, BYTE_UNITS[unit])
+}
+
+/// Serde helpers for metrics, keeping result files of earlier versions
+/// readable: they stored counts, rates and byte sizes as text the way wrk
+/// prints them.
+pub mod serde_metrics {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Value {
+        Number(f64),
+        Text(String),
+    }
+
+    fn value<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        parse: fn(&str) -> Option<f64>,
+    ) -> Result<f64, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Number(number) => Ok(number),
+            // metrics the load tool didn't print were stored empty
+            Value::Text(text) if text.is_empty() => Ok(0.0),
+            Value::Text(text) => parse(&text)
+                .ok_or_else(|| serde::de::Error::custom(format!("invalid number {:?}", text))),
+        }
+    }
+
+    pub fn count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        value(deserializer, super::parse_number).map(|count| count.round() as u64)
+    }
+
+    pub fn rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        value(deserializer, super::parse_number)
+    }
+
+    pub fn bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        value(deserializer, |text| {
+            super::parse_bytes(text).map(|bytes| bytes as f64)
+        })
+        .map(|bytes| bytes.round() as u64)
+    }
+
+    pub fn byte_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+        value(deserializer, |text| {
+            super::parse_bytes(text).map(|bytes| bytes as f64)
+        })
+    }
+
+    /// Durations as milliseconds, the unit latencies were always stored in.
+    pub mod millis {
+        use super::*;
+
+        pub fn serialize<S: Serializer>(
+            duration: &Duration,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.serialize_f64(duration.as_secs_f64() * 1000.0)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Duration, D::Error> {
+            let ms = f64::deserialize(deserializer)?;
+
+            // a corrupt or hostile file is an error, not a panic
+            Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).map_err(|_| {
+                serde::de::Error::custom(format!("duration of {:e}ms out of range", ms))
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_plain() {
+        assert_eq!(parse_number("574184.09"), Some(574184.09));
+        assert_eq!(parse_number("17275966"), Some(17275966.0));
+    }
+
+    #[test]
+    fn number_thousands_separators() {
+        assert_eq!(parse_number("17,275,966"), Some(17275966.0));
+        assert_eq!(parse_number("17 275 966"), Some(17275966.0));
+        assert_eq!(parse_number("17'275'966"), Some(17275966.0));
+        assert_eq!(parse_number("574,184.09"), Some(574184.09));
+        assert_eq!(parse_number("1,000"), Some(1000.0));
+    }
+
+    #[test]
+    fn number_decimal_comma() {
+        assert_eq!(parse_number("574.184,09"), Some(574184.09));
+        assert_eq!(parse_number("574184,09"), Some(574184.09));
+    }
+
+    #[test]
+    fn number_suffixes() {
+        assert_eq!(parse_number("36.10k"), Some(36100.0));
+        assert_eq!(parse_number("1.5M"), Some(1500000.0));
+        assert_eq!(parse_number("2g"), Some(2e9));
+    }
+
+    #[test]
+    fn number_invalid() {
+        assert_eq!(parse_number(""), None);
+        assert_eq!(parse_number("k"), None);
+        assert_eq!(parse_number("abc"), None);
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(
+            parse_duration("814.27us"),
+            Some(Duration::from_nanos(814_270))
+        );
+        assert_eq!(parse_duration("1.07ms"), Some(Duration::from_micros(1070)));
+        assert_eq!(parse_duration("2.00s"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_duration("1.5m"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("814µs"), Some(Duration::from_micros(814)));
+        assert_eq!(parse_duration("8.42"), None);
+        assert_eq!(try_parse_duration("-1ms"), Err("negative".to_string()));
+        assert_eq!(
+            try_parse_duration("99999999999999999999999h"),
+            Err("out of range".to_string())
+        );
+        assert_eq!(parse_duration("-5m"), None);
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(parse_bytes("1.95GB"), Some(2_093_796_557));
+        assert_eq!(parse_bytes("1.95GiB"), parse_bytes("1.95GB"));
+        assert_eq!(parse_bytes("1,024.5 MiB"), Some(1_074_266_112));
+        assert_eq!(parse_bytes("860.5KB"), Some(881_152));
+        assert_eq!(parse_bytes("1.95XB"), None);
+        assert_eq!(parse_megabytes("12.4MB"), Some(12.4));
+        assert_eq!(parse_megabytes("1.5GB"), Some(1536.0));
+        assert_eq!(parse_megabytes("512KB"), Some(0.5));
+        assert_eq!(parse_megabytes("12.4"), None);
+    }
+
+    #[test]
+    fn scaled_bytes() {
+        let (value, unit) = scale_bytes(2_093_796_557.0);
+        assert_eq!((format!("{:.2}", value), unit), ("1.95".to_string(), "GB"));
+        assert_eq!(scale_bytes(512.0), (512.0, "B"));
+    }
+
+    #[test]
+    fn legacy_metrics() {
+        #[derive(serde::Deserialize)]
+        struct Given {
+            #[serde(deserialize_with = "serde_metrics::count")]
+            count: u64,
+            #[serde(deserialize_with = "serde_metrics::bytes")]
+            bytes: u64,
+        }
+
+        let actual: Given =
+            serde_json::from_str(r#"{"count": "17275966", "bytes": "1.95GB"}"#).unwrap();
+        assert_eq!((actual.count, actual.bytes), (17_275_966, 2_093_796_557));
+
+        let actual: Given = serde_json::from_str(r#"{"count": 17275966, "bytes": ""}"#).unwrap();
+        assert_eq!((actual.count, actual.bytes), (17_275_966, 0));
+    }
+
+    #[test]
+    fn millis() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Given {
+            #[serde(with = "serde_metrics::millis")]
+            latency: Duration,
+        }
+
+        let actual: Given = serde_json::from_str(r#"{"latency": 1.07}"#).unwrap();
+        assert_eq!(actual.latency, Duration::from_micros(1070));
+
+        let actual = serde_json::from_str::<Given>(r#"{"latency": 1e300}"#).unwrap_err();
+        assert!(actual
+            .to_string()
+            .starts_with("duration of 1e300ms out of range"));
+    }
+}
@@ -0,0 +1,223 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use regex::Regex;
+use semver::{Version, VersionReq};
+
+use crate::manifest::Manifest;
+
+/// A framework dependency whose requirement excludes its latest release.
+#[derive(Debug, PartialEq)]
+pub struct Update {
+    pub member: PathBuf,
+    pub dependency: String,
+    pub requirement: String,
+    pub latest: String,
+}
+
+/// Frameworks of `members` with a newer, incompatible release on crates.io.
+pub fn check(workspace_dir: &Path, members: &[PathBuf]) -> Vec<Update> {
+    let mut updates = Vec::new();
+
+    for member in members {
+        let member_dir = workspace_dir.join(member);
+        let manifest = match Manifest::load(&member_dir) {
+            Ok(manifest) => manifest,
+            Err(e) => {
+                log::warn!("Skipping {:?}: {}", member, e);
+                continue;
+            }
+        };
+        let dependency = manifest.framework_crate(&member_dir);
+
+        let Some(requirement) = fs::read_to_string(member_dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|content| requirement(&content, &dependency))
+        else {
+            log::warn!("{:?} does not depend on {}.", member, dependency);
+            continue;
+        };
+
+        let Some(latest) = latest_version(&dependency) else {
+            log::warn!("Could not find the latest release of {}.", dependency);
+            continue;
+        };
+
+        if is_outdated(&requirement, &latest) {
+            updates.push(Update {
+                member: member.clone(),
+                dependency,
+                requirement,
+                latest,
+            });
+        }
+    }
+
+    updates
+}
+
+/// Raise the requirement of `update` to its latest release and refresh the
+/// lockfile.
+pub fn apply(workspace_dir: &Path, update: &Update) -> Result<(), String> {
+    let path = workspace_dir.join(&update.member).join("Cargo.toml");
+    let content = fs::read_to_string(&path).map_err(|e| format!("reading {:?}: {}", path, e))?;
+
+    fs::write(
+        &path,
+        set_requirement(&content, &update.dependency, &update.latest),
+    )
+    .map_err(|e| format!("writing {:?}: {}", path, e))?;
+
+    run(
+        workspace_dir,
+        "cargo",
+        &["update", "-p", &update.dependency],
+    )
+}
+
+/// Check out a new branch `name`, apply `updates` and commit them, then
+/// check out the branch it started from again.
+pub fn commit_branch(workspace_dir: &Path, name: &str, updates: &[Update]) -> Result<(), String> {
+    // a detached head has no branch name, its commit is checked out again
+    let original = match output(workspace_dir, "git", &["rev-parse", "--abbrev-ref", "HEAD"])? {
+        branch if branch.trim() == "HEAD" => output(workspace_dir, "git", &["rev-parse", "HEAD"])?,
+        branch => branch,
+    };
+    run(workspace_dir, "git", &["checkout", "-b", name])?;
+
+    for update in updates {
+        apply(workspace_dir, update)?;
+    }
+
+    let message = updates
+        .iter()
+        .map(|u| format!("Update {} to {}", u.dependency, u.latest))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // workspaces commonly ignore their lockfile, it's what pins the update
+    run(workspace_dir, "git", &["add", "--force", "Cargo.lock"])?;
+    run(workspace_dir, "git", &["commit", "-a", "-m", &message])?;
+    run(workspace_dir, "git", &["checkout", original.trim()])
+}
+
+/// Markdown table listing `updates`.
+pub fn updates_table(updates: &[Update]) -> String {
+    let mut res =
+        String::from("| Implementation | Framework | Requirement | Latest |\n|---|---|---|---|");
+
+    for u in updates {
+        res.push_str(&format!(
+            "\n|{}|{}|{}|{}|",
+            u.member.display(),
+            u.dependency,
+            u.requirement,
+            u.latest
+        ));
+    }
+
+    res
+}
+
+fn dependency_regex(dependency: &str) -> Regex {
+    Regex::new(&format!(
+        r#"(?m)^(\s*{}\s*=\s*(?:\{{[^}}\n]*version\s*=\s*)?")([^"]+)(")"#,
+        regex::escape(dependency)
+    ))
+    .unwrap()
+}
+
+fn requirement(cargo_toml: &str, dependency: &str) -> Option<String> {
+    dependency_regex(dependency)
+        .captures(cargo_toml)
+        .and_then(|cap| cap.get(2))
+        .map(|m| m.as_str().to_string())
+}
+
+fn set_requirement(cargo_toml: &str, dependency: &str, version: &str) -> String {
+    dependency_regex(dependency)
+        .replace(cargo_toml, |cap: &regex::Captures| {
+            format!("{}{}{}", &cap[1], version, &cap[3])
+        })
+        .to_string()
+}
+
+fn is_outdated(requirement: &str, latest: &str) -> bool {
+    match (VersionReq::parse(requirement), Version::parse(latest)) {
+        (Ok(requirement), Ok(latest)) => !requirement.matches(&latest),
+        _ => false,
+    }
+}
+
+// `cargo search` prints e.g. `axum = "0.7.5"    # Web framework ...`
+fn latest_version(dependency: &str) -> Option<String> {
+    let output = Command::new("cargo")
+        .args(["search", dependency, "--limit", "1"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let line = stdout.lines().next()?;
+    let (name, rest) = line.split_once('=')?;
+    if name.trim() != dependency {
+        return None;
+    }
+
+    rest.split('"').nth(1).map(str::to_string)
+}
+
+fn run(dir: &Path, program: &str, args: &[&str]) -> Result<(), String> {
+    output(dir, program, args).map(|_| ())
+}
+
+/// Stdout of running `program`.
+fn output(dir: &Path, program: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| format!("running {}: {}", program, e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO_TOML: &str = r#"
+[dependencies]
+axum = "0.5"
+hyper = { version = "0.14", features = ["full"] }
+tokio = { version = "1", features = ["full"] }
+"#;
+
+    #[test]
+    fn requirements() {
+        assert_eq!(requirement(CARGO_TOML, "axum").as_deref(), Some("0.5"));
+        assert_eq!(requirement(CARGO_TOML, "hyper").as_deref(), Some("0.14"));
+        assert_eq!(requirement(CARGO_TOML, "warp"), None);
+
+        let updated = set_requirement(CARGO_TOML, "hyper", "1.4.1");
+        assert!(updated.contains(r#"hyper = { version = "1.4.1", features = ["full"] }"#));
+        assert!(updated.contains(r#"axum = "0.5""#));
+    }
+
+    #[test]
+    fn outdated() {
+        assert!(is_outdated("0.5", "0.8.1"));
+        assert!(!is_outdated("4", "4.9.0"));
+        assert!(!is_outdated("0.5.0-rc.2", "0.5.1"));
+    }
+}
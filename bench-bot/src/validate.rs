@@ -0,0 +1,342 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::collector;
+use crate::config::{Config, Override};
+use crate::manifest::Benchmark;
+
+/// Longest lifetime of presigned S3 links.
+const MAX_EXPIRES_IN: u64 = 7 * 24 * 60 * 60;
+
+/// Load the config at `path`, or the defaults without one, apply
+/// `overrides` and check the result against the `benchmarks` of the workspace.
+/// Problems are reported as `path:line: message` where the line is known.
+pub fn load(
+    path: Option<&Path>,
+    overrides: &[Override],
+    benchmarks: &[Benchmark],
+) -> Result<Config, Vec<String>> {
+    let (content, label) = match path {
+        Some(path) => (
+            fs::read_to_string(path).map_err(|e| vec![format!("reading {:?}: {}", path, e)])?,
+            path.display().to_string(),
+        ),
+        None => (String::new(), "defaults".to_string()),
+    };
+
+    let mut config: Config = toml::from_str(&content).map_err(|e| {
+        let message = e.to_string();
+        match unknown_key_line(&content, &message) {
+            Some(line) => vec![format!("{}:{}: {}", label, line, message)],
+            // other toml errors carry their own line and column
+            None => vec![format!("{}: {}", label, message)],
+        }
+    })?;
+
+    if !overrides.is_empty() {
+        let mut value: toml::Value = toml::from_str(&content).expect("parsed above");
+        for o in overrides {
+            o.apply(&mut value)
+                .map_err(|e| vec![format!("override {}: {}", o.key.join("."), e)])?;
+        }
+        config = value
+            .try_into()
+            .map_err(|e| vec![format!("{} with overrides: {}", label, e)])?;
+    }
+
+    let problems = check(&config, &content, benchmarks);
+    if problems.is_empty() {
+        Ok(config)
+    } else {
+        Err(problems
+            .into_iter()
+            .map(|(line, message)| match line {
+                Some(line) => format!("{}:{}: {}", label, line, message),
+                None => format!("{}: {}", label, message),
+            })
+            .collect())
+    }
+}
+
+/// Problems of `config` with the line of `content` they are at.
+fn check(config: &Config, content: &str, benchmarks: &[Benchmark]) -> Vec<(Option<usize>, String)> {
+    let frameworks: BTreeSet<String> = benchmarks.iter().map(Benchmark::framework_name).collect();
+    let scenarios: BTreeSet<String> = benchmarks.iter().map(|b| b.scenario.clone()).collect();
+
+    let mut problems = Vec::new();
+
+    if config.toolchain.as_deref().map(str::trim) == Some("") {
+        problems.push((
+            locate(content, "", "toolchain"),
+            "toolchain must not be empty".to_string(),
+        ));
+    }
+
+    for framework in config.thresholds.frameworks.keys() {
+        if !frameworks.contains(framework) {
+            problems.push((
+                locate(content, &format!("thresholds.frameworks.{}", framework), ""),
+                format!(
+                    "unknown framework {:?} in thresholds, expected one of {}",
+                    framework,
+                    list(&frameworks)
+                ),
+            ));
+        }
+    }
+
+    for (scenario, weight) in config.weights.iter() {
+        let line = locate(content, "weights", scenario);
+        if !scenarios.contains(scenario) {
+            problems.push((
+                line,
+                format!(
+                    "unknown scenario {:?} in weights, expected one of {}",
+                    scenario,
+                    list(&scenarios)
+                ),
+            ));
+        }
+        if !weight.is_finite() || *weight < 0.0 {
+            problems.push((
+                line,
+                format!(
+                    "weight of {:?} must be a non-negative number, got {}",
+                    scenario, weight
+                ),
+            ));
+        }
+    }
+
+    for scenario in config.scripts.keys() {
+        if !scenarios.contains(scenario) {
+            problems.push((
+                locate(content, "scripts", scenario),
+                format!(
+                    "unknown scenario {:?} in scripts, expected one of {}",
+                    scenario,
+                    list(&scenarios)
+                ),
+            ));
+        }
+    }
+
+    for (scenario, floor) in &config.floors {
+        let line = locate(content, "floors", scenario);
+        if !scenarios.contains(scenario) {
+            problems.push((
+                line,
+                format!(
+                    "unknown scenario {:?} in floors, expected one of {}",
+                    scenario,
+                    list(&scenarios)
+                ),
+            ));
+        }
+        if !floor.is_finite() || *floor <= 0.0 {
+            problems.push((
+                line,
+                format!(
+                    "floor of {:?} must be a positive number, got {}",
+                    scenario, floor
+                ),
+            ));
+        }
+    }
+
+    for (name, profile) in &config.profiles {
+        let section = format!("profiles.{}", name);
+
+        for (key, value) in [
+            ("duration", profile.duration),
+            ("connections", profile.connections),
+            ("rounds", profile.rounds),
+        ] {
+            if value == Some(0) {
+                problems.push((
+                    locate(content, &section, key),
+                    format!("{} of profile {:?} must be at least 1", key, name),
+                ));
+            }
+        }
+
+        for scenario in profile.scenarios.iter().flatten() {
+            if !scenarios.contains(scenario) {
+                problems.push((
+                    locate(content, &section, "scenarios"),
+                    format!(
+                        "unknown scenario {:?} in profile {:?}, expected one of {}",
+                        scenario,
+                        name,
+                        list(&scenarios)
+                    ),
+                ));
+            }
+        }
+    }
+
+    if let Some(artifacts) = &config.artifacts {
+        if !artifacts.bucket.starts_with("s3://") {
+            problems.push((
+                locate(content, "artifacts", "bucket"),
+                format!("bucket must be an s3:// url, got {:?}", artifacts.bucket),
+            ));
+        }
+        if artifacts.expires_in == 0 || artifacts.expires_in > MAX_EXPIRES_IN {
+            problems.push((
+                locate(content, "artifacts", "expires_in"),
+                format!(
+                    "expires_in must be between 1 and {} seconds, got {}",
+                    MAX_EXPIRES_IN, artifacts.expires_in
+                ),
+            ));
+        }
+    }
+
+    for (name, collector) in &config.collectors {
+        if let Err(e) = collector::build(name, collector) {
+            let section = format!("collectors.{}", name);
+            problems.push((
+                locate(content, &section, "kind").or_else(|| locate(content, &section, "")),
+                e,
+            ));
+        }
+    }
+
+    if let Some(digest) = &config.digest {
+        if digest.to.is_empty() {
+            problems.push((
+                locate(content, "digest", "to"),
+                "digest needs at least one recipient".to_string(),
+            ));
+        }
+    }
+
+    problems
+}
+
+// toml reports unknown keys at the line of their table, find the key itself
+fn unknown_key_line(content: &str, message: &str) -> Option<usize> {
+    let regex = Regex::new(r"unknown field `([^`]+)`(?:.*for key `([^`]+)`)?").unwrap();
+    let captures = regex.captures(message)?;
+    let section = captures.get(2).map_or("", |m| m.as_str());
+
+    locate(content, section, &captures[1])
+}
+
+/// 1-based line of `key` in table `section`, or of the table header itself
+/// when `key` is empty. The root table is `""`.
+fn locate(content: &str, section: &str, key: &str) -> Option<usize> {
+    let mut current = String::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            current = header.trim().replace(['"', ' '], "");
+            if key.is_empty() && current == section {
+                return Some(index + 1);
+            }
+            continue;
+        }
+
+        let name = line
+            .split('=')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .trim_matches('"');
+        if !key.is_empty() && current == section && name == key {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
+fn list(names: &BTreeSet<String>) -> String {
+    names.iter().cloned().collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn benchmarks() -> Vec<Benchmark> {
+        ["hello-world/axum", "hello-world/tide"]
+            .iter()
+            .map(|member| Benchmark {
+                scenario: "hello-world".to_string(),
+                member: PathBuf::from(member),
+                path: String::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn valid() {
+        let given = r#"
+[thresholds]
+rps = "3%"
+
+[thresholds.frameworks.tide]
+rps = "15%"
+
+[weights]
+hello-world = 1
+"#;
+        let config: Config = toml::from_str(given).expect("parse config fail");
+
+        assert_eq!(check(&config, given, &benchmarks()), Vec::new());
+    }
+
+    #[test]
+    fn unknown_key() {
+        let given = "[thresholds]\nrps = \"3%\"\nrsp = \"3%\"\n";
+        let message = toml::from_str::<Config>(given).unwrap_err().to_string();
+
+        assert_eq!(unknown_key_line(given, &message), Some(3));
+    }
+
+    #[test]
+    fn problems() {
+        let given = r#"toolchain = ""
+
+[thresholds.frameworks.rocket]
+rps = "15%"
+
+[weights]
+json = -1
+
+[artifacts]
+bucket = "bench-results"
+expires_in = 0
+
+[profiles.quick]
+rounds = 0
+scenarios = ["hello-world", "json"]
+
+[scripts]
+echo = "scripts/echo.lua"
+
+[floors]
+hello-world = 0
+
+[collectors.gpu]
+kind = "nvml"
+"#;
+        let config: Config = toml::from_str(given).expect("parse config fail");
+
+        let actual: Vec<String> = check(&config, given, &benchmarks())
+            .into_iter()
+            .map(|(line, message)| format!("{:?}: {}", line, message))
+            .collect();
+
+        insta::assert_snapshot!(actual.join("\n"));
+    }
+}
@@ -1,18 +1,27 @@
-use std::sync::Arc;
+use std::env;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
+use bytes::{Buf, Bytes};
 use http::Response;
 use hyper::Body;
-use hyper_fast::server::{ApiError, HttpResponse, HttpRoute, Service};
+use hyper_fast::server::{ApiError, HttpRequest, HttpResponse, HttpRoute, Service};
 use hyper_fast::server::{ServiceBuilder, ServiceDaemon, start_http_server};
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() -> Result<(), anyhow::Error> {
+    // the logging variant of a scenario runs with BENCH_LOG=1
+    if env::var("BENCH_LOG").as_deref() == Ok("1") {
+        tracing_subscriber::fmt().json().init();
+    }
+
     start_http_server("127.0.0.1:3000", ExampleServiceBuilder {}).await
 }
 
 pub struct ExampleService {
-    // any service level properties
+    /// Payload of the background scenario, refreshed by the daemon.
+    payload: RwLock<Bytes>,
 }
 
 pub struct ExampleServiceDaemon {}
@@ -23,17 +32,30 @@ pub struct ExampleServiceBuilder {
 
 #[async_trait]
 impl ServiceDaemon<ExampleService> for ExampleServiceDaemon {
-    async fn start(&self, _service: Arc<ExampleService>) {
-        //no impl for now.
+    async fn start(&self, service: Arc<ExampleService>) {
+        // its load is what the background scenario measures, in any other
+        // it would slow down the requests measured
+        if env::var("BENCHBOT_SCENARIO").is_ok_and(|scenario| scenario != "background") {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(PAYLOAD_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+            service.refresh_payload();
+        }
     }
 }
 
 #[async_trait]
 impl ServiceBuilder<ExampleService, ExampleServiceDaemon> for ExampleServiceBuilder {
     async fn build(self) -> anyhow::Result<(ExampleService, Option<ExampleServiceDaemon>)> {
-        let service = ExampleService {};
+        let service = ExampleService {
+            payload: RwLock::new(Bytes::new()),
+        };
+        service.refresh_payload();
 
-        Ok((service, None))
+        Ok((service, Some(ExampleServiceDaemon {})))
     }
 }
 
@@ -41,14 +63,37 @@ impl ServiceBuilder<ExampleService, ExampleServiceDaemon> for ExampleServiceBuil
 impl Service for ExampleService {
     async fn api_handler<'a>(
         &'a self,
-        _: Body,
+        body: Body,
         route: &HttpRoute<'a>,
         path: &[&str],
     ) -> Result<Response<Body>, ApiError> {
+        tracing::info!(method = %route.method, path = route.path, "request");
+
         match path {
             [] if matches!(route.method, &http::Method::GET) => {
                 self.get_test(route).await
             }
+            ["hello-world"] if matches!(route.method, &http::Method::GET) => {
+                self.get_hello_world(route).await
+            }
+            ["json"] if matches!(route.method, &http::Method::GET) => {
+                self.get_json(route).await
+            }
+            ["echo"] if matches!(route.method, &http::Method::POST) => {
+                self.post_echo(body, route).await
+            }
+            ["compute"] if matches!(route.method, &http::Method::GET) => {
+                self.get_compute(route).await
+            }
+            ["file"] if matches!(route.method, &http::Method::GET) => {
+                self.get_file(route).await
+            }
+            ["background"] if matches!(route.method, &http::Method::GET) => {
+                self.get_background(route).await
+            }
+            ["error"] if matches!(route.method, &http::Method::GET) => {
+                self.get_error(route).await
+            }
             _ => HttpResponse::not_found(route.path),
         }
     }
@@ -58,4 +103,94 @@ impl ExampleService {
     pub async fn get_test(&self, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
         HttpResponse::string(route, "GET::/api/test - test passed".to_string())
     }
+
+    pub async fn get_hello_world(&self, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
+        HttpResponse::string(route, "Hello, World!".to_string())
+    }
+
+    pub async fn get_json(&self, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
+        HttpResponse::json(route, &Message { message: "Hello, World!" })
+    }
+
+    pub async fn post_echo(&self, body: Body, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
+        let mut bytes = HttpRequest::bytes(route, body).await?;
+        let body = bytes.copy_to_bytes(bytes.remaining());
+
+        HttpResponse::ok(route, Body::from(body))
+    }
+
+    pub async fn get_compute(&self, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
+        HttpResponse::string(route, nth_prime(COMPUTE_NTH_PRIME).to_string())
+    }
+
+    pub async fn get_file(&self, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
+        let file = tokio::fs::read(STATIC_FILE)
+            .await
+            .map_err(|e| ApiError::InternalServerError(e.into()))?;
+
+        HttpResponse::ok(route, Body::from(file))
+    }
+
+    pub async fn get_background(&self, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
+        let payload = self.payload.read().unwrap().clone();
+
+        HttpResponse::ok(route, Body::from(payload))
+    }
+
+    pub async fn get_error(&self, route: &HttpRoute<'_>) -> Result<Response<Body>, ApiError> {
+        // hyper-fast drops the connection on an Err, errors are sent as responses
+        ApiError::BadRequest(anyhow::anyhow!("{} takes no requests", route.path)).into()
+    }
+
+    fn refresh_payload(&self) {
+        let refreshed_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let payload = Payload {
+            refreshed_at,
+            items: (0..PAYLOAD_ITEMS).map(|i| i * refreshed_at as u64 % 7919).collect(),
+        };
+
+        let payload = serde_json::to_vec(&payload).expect("payload serializes");
+        *self.payload.write().unwrap() = Bytes::from(payload);
+    }
+}
+
+/// Interval the daemon rebuilds the background payload at.
+const PAYLOAD_REFRESH_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Items of the background payload.
+const PAYLOAD_ITEMS: u64 = 1000;
+
+/// Prime the compute scenario responds with.
+const COMPUTE_NTH_PRIME: usize = 1000;
+
+/// File the file scenario responds with.
+const STATIC_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../static/index.html");
+
+#[derive(serde::Serialize)]
+struct Message {
+    message: &'static str,
+}
+
+#[derive(serde::Serialize)]
+struct Payload {
+    refreshed_at: u128,
+    items: Vec<u64>,
+}
+
+// trial division, the work is the point
+fn nth_prime(n: usize) -> u64 {
+    let mut found = 0;
+    let mut candidate = 1;
+
+    while found < n {
+        candidate += 1;
+        if (2..candidate).take_while(|d| d * d <= candidate).all(|d| candidate % d != 0) {
+            found += 1;
+        }
+    }
+
+    candidate
 }
\ No newline at end of file
@@ -0,0 +1,4 @@
+-- Request of the echo scenario: a POST with a short body, wrk sends a
+-- body-less GET otherwise. Headers are passed on the command line.
+wrk.method = "POST"
+wrk.body = "Hello, World!"